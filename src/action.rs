@@ -1,9 +1,139 @@
-use crate::pacman::Package;
+use std::path::PathBuf;
 
+use crate::components::Pane;
+use crate::desired::Difference;
+use crate::downgrade::Candidate;
+use crate::history::Revert;
+use crate::integrity::Report;
+use crate::keyring::Key;
+use crate::layout::View;
+use crate::makepkg::{Build, Plan, Review, Script};
+use crate::manifest::Change;
+use crate::mirrorlist::{Mirror, Speed};
+use crate::news::Feed;
+use crate::pacman::{Package, Reason};
+use crate::pacnew::{ConfigFile, Resolution};
+
+#[derive(Clone)]
 pub(crate) enum Action {
     SearchPackage(String),
     InstallPackage(String),
     UpdateInstallPackage(String),
     RemovePackage(String),
     SelectPackage(Package),
+    Focus(Pane),
+    Confirm(String, Box<Action>),
+    /// Asks for a line of text, passed on to the action with [`Action::with_input`].
+    Prompt(String, String, Box<Action>),
+    ExportPackages(PathBuf, Vec<Package>),
+    /// AUR search results for the query they were requested with.
+    AurPackagesFound(String, Vec<Package>),
+    AurPackageLoaded(Package),
+    Notify(String),
+    InstallAurPackage(String),
+    AurBuildPlanned(Plan),
+    BuildFetched(Build, Review),
+    /// The build was accepted at the given revision.
+    BuildReviewed(Build, String),
+    BuildCancelled,
+    BuildLogged(String),
+    /// The build produced the given package files.
+    BuildCompleted(Build, String, Vec<PathBuf>),
+    BuildFailed(String),
+    ViewScripts(Package),
+    /// Opens the script viewer with the scripts of a package.
+    ScriptsLoaded(String, Vec<Script>),
+    CloseViewer,
+    ShowView(View),
+    LoadLog,
+    /// Shows the details of a package by name in the packages view.
+    ShowPackage(String),
+    /// The AUR lookup of a package shown by name, `Err` with the reason it failed.
+    AurPackageLookedUp(String, Result<Option<Package>, String>),
+    /// Shows a package looked up by name in the packages view.
+    PackageFound(Package),
+    LoadCache,
+    /// Deletes package files and their signatures from the cache.
+    DeleteCacheFiles(Vec<PathBuf>),
+    /// Reverts a transaction, `true` to revert it even though some packages cannot be.
+    RevertTransaction(Revert, bool),
+    /// Lists the versions an installed package can be installed at.
+    ShowVersions(Package),
+    VersionsFound(String, Vec<Candidate>),
+    /// Installs a version of a package, adding it to `IgnorePkg` if set.
+    InstallVersion(String, Candidate, bool),
+    /// Looks for `.pacnew` and `.pacsave` files.
+    LoadConfigFiles,
+    ConfigFilesFound(Vec<ConfigFile>),
+    ResolveConfigFile(ConfigFile, Resolution),
+    /// Verifies the files of an installed package, or of all of them.
+    CheckIntegrity(Option<String>),
+    /// A package was checked by the run with the given number.
+    IntegrityChecked(usize, Report),
+    CancelIntegrityCheck,
+    /// Shows how the orphans change with new install reasons before setting them.
+    PreviewReasons(Vec<(String, Reason)>),
+    SetReasons(Vec<(String, Reason)>),
+    /// Holds a package at its installed version, or releases it.
+    ToggleHold(String),
+    /// Lists the packages installed only as optional dependencies.
+    LoadOptdepends,
+    /// Installs optional dependencies of a package as dependencies.
+    InstallOptdepends(Package, Vec<String>),
+    /// Opens the file picker in a directory, or where it was last.
+    BrowsePackageFiles(Option<PathBuf>),
+    /// Reads the metadata of a package file to show it before installing.
+    LoadPackageFile(PathBuf),
+    InstallPackageFile(PathBuf, String),
+    InstallPackageUrl(String),
+    RefreshDatabases,
+    /// A sync database download made progress, with the downloaded and total bytes.
+    RefreshProgressed(String, i64, i64),
+    /// Refreshes the databases through `pacman -Sy` when ptu cannot write them. Nothing is
+    /// upgraded, so [i] installs are refused until the system is upgraded with [I].
+    RefreshWithPacman,
+    DatabasesRefreshed(Result<(), String>),
+    LoadKeyring,
+    KeyringLoaded(Vec<Key>),
+    LoadMirrors,
+    /// Measures the given servers one after another.
+    MeasureMirrors(Vec<String>),
+    /// A server was measured by the run with the given number.
+    MirrorMeasured(usize, String, Result<Speed, String>),
+    CancelMirrorMeasurement,
+    /// Writes the mirrors in their order to the mirrorlist.
+    WriteMirrorlist(Vec<Mirror>),
+    LoadNews,
+    /// The feed and when the system was last fully upgraded.
+    NewsLoaded(Feed, Option<i64>),
+    /// Marks news items as read by id.
+    MarkNewsRead(Vec<String>),
+    /// Runs an upgrade even though there is unread news.
+    IgnoreNews(Box<Action>),
+    /// Writes the explicitly installed packages to a manifest, with their versions if set.
+    ExportManifest(PathBuf, bool),
+    /// Compares a manifest with the installed packages.
+    ImportManifest(PathBuf),
+    ApplyManifest(Vec<Change>),
+    /// Compares the desired state file with the installed packages.
+    LoadDesiredState,
+    /// Fixes the differences from the desired state that pacman can fix.
+    Reconcile(Vec<Difference>),
+}
+
+impl Action {
+    /// Fills in the text entered in a prompt.
+    pub(crate) fn with_input(self, input: String) -> Self {
+        match self {
+            Action::ExportPackages(_, packages) => {
+                Action::ExportPackages(PathBuf::from(input), packages)
+            }
+            Action::InstallPackageUrl(_) => Action::InstallPackageUrl(input),
+            Action::ExportManifest(_, with_versions) => {
+                Action::ExportManifest(PathBuf::from(input), with_versions)
+            }
+            Action::ImportManifest(_) => Action::ImportManifest(PathBuf::from(input)),
+            action => action,
+        }
+    }
 }
@@ -1,43 +1,188 @@
-use std::process;
+use std::collections::{BTreeSet, VecDeque};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use std::{mem, process};
 
 use crate::action::Action;
+use crate::aur;
+use crate::cache::{self, Cache};
+use crate::components::build_view::BuildView;
+use crate::components::cache_view::CacheView;
+use crate::components::dialog::Dialog;
+use crate::components::file_picker::FilePicker;
+use crate::components::history_view::HistoryView;
+use crate::components::integrity_view::IntegrityView;
+use crate::components::keyring_view::KeyringView;
+use crate::components::log_view::LogView;
+use crate::components::manifest_view::ManifestView;
+use crate::components::mirrors_view::MirrorsView;
+use crate::components::news_view::NewsView;
+use crate::components::optdepends_view::OptdependsView;
 use crate::components::package_info::PackageInfo;
 use crate::components::packages_table::PackagesTable;
-use crate::components::{package_input::PackageInput, Component};
-use crate::pacman::{self, Pacman};
-use crate::tui::TUI;
+use crate::components::pacnew_view::PacnewView;
+use crate::components::script_viewer::ScriptViewer;
+use crate::components::state_view::StateView;
+use crate::components::version_picker::VersionPicker;
+use crate::components::{package_input::PackageInput, Component, Pane};
+use crate::config::Config;
+use crate::desired::{self, DesiredState};
+use crate::downgrade::{self, Archive};
+use crate::export;
+use crate::hold::Holds;
+use crate::ignore;
+use crate::integrity;
+use crate::keyring;
+use crate::layout::{Areas, LayoutManager, View};
+use crate::makepkg::{self, Build, Script};
+use crate::manifest::{self, Change, Manifest};
+use crate::mirrorlist::{self, Fetch};
+use crate::news::{self, Feed, ReadItems};
+use crate::package_file;
+use crate::pacman::{self, Package, Pacman, Reason, Transaction};
+use crate::pacman_log::Log;
+use crate::pacnew::{self, Resolution};
+use crate::sync::{self, DbStatus, LastSync};
+use crate::tui::Tui;
 
+use chrono::Utc;
 use color_eyre::eyre;
 use ratatui::crossterm;
-use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Position};
+
+/// How long to wait for terminal input before checking for background results.
+const TICK: Duration = Duration::from_millis(100);
+/// AUR requests are only sent once the user stops typing or scrolling for this long.
+const AUR_DELAY: Duration = Duration::from_millis(300);
 
 pub(crate) struct App {
-    tui: TUI,
+    tui: Tui,
     components: Vec<Box<dyn Component>>,
     pacman: Pacman,
+    focus: Pane,
+    layout: LayoutManager,
+    areas: Areas,
+    /// Actions sent back by background threads.
+    sender: mpsc::Sender<Action>,
+    receiver: mpsc::Receiver<Action>,
+    query: String,
+    /// The package shown in the package info.
+    selected: Option<Package>,
+    aur_searches: Arc<AtomicUsize>,
+    aur_lookups: Arc<AtomicUsize>,
+    /// Bumped to stop the running integrity check.
+    integrity_checks: Arc<AtomicUsize>,
+    /// Bumped to stop the running mirror measurement.
+    mirror_measurements: Arc<AtomicUsize>,
+    fetcher: Arc<dyn Fetch>,
+    news: Feed,
+    read_news: ReadItems,
+    /// When the system was last fully upgraded, read from the pacman log.
+    last_upgrade: Option<i64>,
+    /// Set while running an upgrade confirmed despite unread news.
+    news_ignored: bool,
+    holds: Holds,
+    /// Where the file picker was last opened.
+    package_dir: PathBuf,
+    sync_statuses: Vec<DbStatus>,
+    last_sync: LastSync,
+    /// Whether the databases were refreshed after the last upgrade, so installing without
+    /// upgrading would be a partial upgrade.
+    upgrade_pending: bool,
+    /// The sync database download in progress.
+    refresh_progress: Option<String>,
+    config: Config,
+    /// The AUR package being installed and the package bases left to build for it.
+    build_target: String,
+    builds: VecDeque<Build>,
+    build_count: usize,
     should_exit: bool,
 }
 
 impl App {
     pub(crate) fn new() -> eyre::Result<Self> {
-        let tui = TUI::new()?;
+        let config = Config::load()?;
+        let tui = Tui::new(config.mouse)?;
         let should_exit = Default::default();
-        let pacman = Pacman::new()?;
+        let pacman = Pacman::new(&config)?;
+        let holds = Holds::load(&config.ignore_file);
+        let sync_statuses = pacman.sync_statuses();
+        let (sender, receiver) = mpsc::channel();
 
         Ok(Self {
             tui,
             components: vec![
                 Box::new(PackageInput::default()),
-                Box::new(PackagesTable::default()),
+                Box::new(PackagesTable::new(holds.names())),
                 Box::new(PackageInfo::default()),
+                Box::new(ScriptViewer::default()),
+                Box::new(LogView::default()),
+                Box::new(HistoryView::default()),
+                Box::new(CacheView::new(config.cache_keep)),
+                Box::new(PacnewView::default()),
+                Box::new(IntegrityView::default()),
+                Box::new(OptdependsView::default()),
+                Box::new(KeyringView::default()),
+                Box::new(MirrorsView::default()),
+                Box::new(NewsView::default()),
+                Box::new(StateView::default()),
+                Box::new(BuildView::default()),
+                Box::new(VersionPicker::default()),
+                Box::new(ManifestView::default()),
+                Box::new(FilePicker::default()),
+                Box::new(Dialog::default()),
             ],
             pacman,
+            focus: Pane::PackageInput,
+            layout: Default::default(),
+            areas: Default::default(),
+            sender,
+            receiver,
+            query: Default::default(),
+            selected: Default::default(),
+            aur_searches: Default::default(),
+            aur_lookups: Default::default(),
+            integrity_checks: Default::default(),
+            mirror_measurements: Default::default(),
+            fetcher: Arc::new(
+                ureq::AgentBuilder::new()
+                    .timeout(mirrorlist::TIMEOUT)
+                    .build(),
+            ),
+            news: Default::default(),
+            read_news: ReadItems::load(),
+            last_upgrade: None,
+            news_ignored: false,
+            holds,
+            package_dir: env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
+            sync_statuses,
+            last_sync: LastSync::load(),
+            upgrade_pending: false,
+            refresh_progress: None,
+            config,
+            build_target: Default::default(),
+            builds: Default::default(),
+            build_count: Default::default(),
             should_exit,
         })
     }
 
     pub(crate) fn run(&mut self) -> eyre::Result<()> {
-        TUI::enter()?;
+        self.tui.enter()?;
+        // the package details show who signed a package
+        let mut actions = vec![Action::LoadKeyring];
+        if self.config.news.enabled {
+            actions.push(Action::LoadNews);
+        }
+        self.handle_actions(&actions)?;
 
         while !self.should_exit {
             self.render()?;
@@ -45,32 +190,67 @@ impl App {
             self.handle_actions(&actions)?;
         }
 
-        TUI::exit()?;
+        Tui::exit()?;
 
         Ok(())
     }
 
     fn render(&mut self) -> eyre::Result<()> {
         self.tui.draw(|frame| {
+            let areas = self.layout.areas(frame.size());
+            let indicator = sync::indicator(
+                &self.last_sync,
+                &self.sync_statuses,
+                self.refresh_progress.as_deref(),
+            );
+            let [tabs_area, indicator_area] = Layout::horizontal([
+                Constraint::Fill(1),
+                Constraint::Length(indicator.width() as u16),
+            ])
+            .areas(areas.header());
+            frame.render_widget(self.layout.header(), tabs_area);
+            frame.render_widget(indicator, indicator_area);
+            if let Some(tabs_area) = areas.tabs() {
+                frame.render_widget(self.layout.tabs(), tabs_area);
+            }
             for component in self.components.iter_mut() {
-                let result = component.draw(frame, &frame.size());
+                let area = match component.pane() {
+                    Some(pane) => match areas.pane(pane) {
+                        Some(area) => area,
+                        None => continue,
+                    },
+                    None => frame.size(),
+                };
+                let result = component.draw(frame, &area);
                 if result.is_err() {
                     process::exit(1);
                 }
             }
+            self.areas = areas;
         })?;
 
         Ok(())
     }
 
     fn handle_events(&mut self) -> eyre::Result<Vec<Action>> {
-        let mut actions = Vec::new();
+        let mut actions = self.receiver.try_iter().collect::<Vec<_>>();
+
+        if !crossterm::event::poll(TICK)? {
+            return Ok(actions);
+        }
 
         match crossterm::event::read()? {
             Event::Key(key_event) => {
                 let component_actions = self.handle_key_event(&key_event)?;
                 actions.extend(component_actions);
             }
+            Event::Mouse(mouse_event) => {
+                let component_actions = self.handle_mouse_event(&mouse_event)?;
+                actions.extend(component_actions);
+            }
+            Event::Resize(_, _) => {
+                self.tui.autoresize()?;
+            }
             _ => {}
         }
 
@@ -78,11 +258,47 @@ impl App {
     }
 
     fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Vec<Action>> {
+        let mut actions = Vec::new();
+
+        if let Some(modal) = self.components.iter_mut().rev().find(|c| c.is_modal()) {
+            if let Some(component_actions) = modal.handle_key_event(key_event)? {
+                actions.extend(component_actions);
+            }
+            return Ok(actions);
+        }
+
         if key_event.code == KeyCode::Esc {
             self.should_exit = true;
         }
 
-        let mut actions = Vec::new();
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::CONTROL,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('k') => actions.push(Action::Focus(Pane::PackageInput)),
+                KeyCode::Char('j') => actions.push(Action::Focus(Pane::PackagesTable)),
+                KeyCode::Char('r') => actions.push(Action::RefreshDatabases),
+                KeyCode::Left | KeyCode::Up => self.layout.shrink(),
+                KeyCode::Right | KeyCode::Down => self.layout.grow(),
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code: KeyCode::Tab,
+                ..
+            } => actions.push(Action::Focus(self.layout.next(self.focus))),
+            KeyEvent {
+                code: KeyCode::F(n),
+                ..
+            } => {
+                if let Some(view) = View::from_key(n) {
+                    actions.push(Action::ShowView(view));
+                }
+            }
+            _ => {}
+        }
 
         for component in self.components.iter_mut() {
             let component_actions = component.handle_key_event(key_event)?;
@@ -94,11 +310,196 @@ impl App {
         Ok(actions)
     }
 
+    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) -> eyre::Result<Vec<Action>> {
+        let mut actions = Vec::new();
+
+        if let Some(modal) = self.components.iter_mut().rev().find(|c| c.is_modal()) {
+            if let Some(component_actions) = modal.handle_mouse_event(mouse_event)? {
+                actions.extend(component_actions);
+            }
+            return Ok(actions);
+        }
+
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        let clicked = mouse_event.kind == MouseEventKind::Down(MouseButton::Left);
+
+        if let Some(view) = self.areas.view_at(position) {
+            if clicked {
+                actions.push(Action::ShowView(view));
+            }
+            return Ok(actions);
+        }
+
+        if let Some(pane) = self.areas.tab_at(position) {
+            if clicked {
+                actions.push(Action::Focus(pane));
+            }
+            return Ok(actions);
+        }
+
+        let Some(pane) = self.areas.pane_at(position) else {
+            return Ok(actions);
+        };
+
+        if clicked {
+            actions.push(Action::Focus(pane));
+        }
+
+        for component in self.components.iter_mut() {
+            if component.pane() != Some(pane) {
+                continue;
+            }
+            let component_actions = component.handle_mouse_event(mouse_event)?;
+            if let Some(component_actions) = component_actions {
+                actions.extend(component_actions);
+            }
+        }
+
+        Ok(actions)
+    }
+
+    /// Runs `request` on a background thread after [`AUR_DELAY`], unless another request
+    /// sharing `counter` was made in the meantime.
+    fn debounce(
+        &self,
+        counter: &Arc<AtomicUsize>,
+        request: impl FnOnce() -> eyre::Result<Action> + Send + 'static,
+    ) {
+        let generation = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let counter = Arc::clone(counter);
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            thread::sleep(AUR_DELAY);
+            if counter.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let action = request()
+                .unwrap_or_else(|error| Action::Notify(format!("AUR request failed: {error}")));
+            // the receiver is gone once the app exits
+            let _ = sender.send(action);
+        });
+    }
+
+    /// Runs `task` on a background thread, reporting failures in a notice.
+    fn spawn(&self, task: impl FnOnce() -> eyre::Result<Action> + Send + 'static) {
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let action = task().unwrap_or_else(|error| Action::Notify(error.to_string()));
+            let _ = sender.send(action);
+        });
+    }
+
+    /// The unread news published since the last full system upgrade.
+    fn unread_news(&self) -> Vec<news::Item> {
+        news::unread_since(&self.news.items, &self.read_news, self.last_upgrade)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Shows an installed package found neither in the sync databases nor in the AUR.
+    fn show_local_package(&mut self, package_name: &str) -> eyre::Result<Vec<crate::event::Event>> {
+        match self.pacman.local_package(package_name) {
+            Some(package) => self.handle_action(&Action::PackageFound(package)),
+            None => Ok(vec![crate::event::Event::Notified(format!(
+                "{package_name} is neither installed nor available"
+            ))]),
+        }
+    }
+
+    /// Runs a build step on a background thread, logging the output of the commands it runs.
+    fn spawn_build_step(
+        &self,
+        step: impl FnOnce(&(dyn Fn(&str) + Sync)) -> eyre::Result<Action> + Send + 'static,
+    ) {
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            let log = |line: &str| {
+                let _ = sender.send(Action::BuildLogged(line.to_owned()));
+            };
+            let action = step(&log).unwrap_or_else(|error| Action::BuildFailed(error.to_string()));
+            let _ = sender.send(action);
+        });
+    }
+
+    /// Fetches the next package base for review, or finishes the installation.
+    fn next_build(&mut self, events: &mut Vec<crate::event::Event>) {
+        let Some(build) = self.builds.pop_front() else {
+            let target = mem::take(&mut self.build_target);
+            events.push(crate::event::Event::BuildFinished(format!(
+                "installed {target}"
+            )));
+            events.push(crate::event::Event::PackageInstalled(target));
+            return;
+        };
+        let Some(aur) = self.pacman.aur().cloned() else {
+            return;
+        };
+
+        let step = self.build_count - self.builds.len();
+        events.push(crate::event::Event::BuildStarted(format!(
+            "[{step}/{}] {}",
+            self.build_count, build.base
+        )));
+        self.spawn_build_step(move |log| {
+            let review = makepkg::fetch(&build, &aur, log)?;
+            Ok(Action::BuildFetched(build, review))
+        });
+    }
+
+    /// Holds or releases a package, writing the `IgnorePkg` include file when `persist` is set
+    /// or the package is in it, and describes the outcome.
+    fn set_hold(
+        &mut self,
+        package_name: &str,
+        held: bool,
+        persist: bool,
+        events: &mut Vec<crate::event::Event>,
+    ) -> eyre::Result<String> {
+        let saved = self.holds.set(package_name, held);
+        events.push(crate::event::Event::HoldsChanged(self.holds.names()));
+
+        let ignore_file = &self.config.ignore_file;
+        let listed = ignore::read(ignore_file).iter().any(|p| p == package_name);
+        let mut message = if held {
+            format!("{package_name} is held")
+        } else {
+            format!("{package_name} is released")
+        };
+        // the hold still applies until ptu exits
+        if let Err(error) = saved {
+            message.push_str(&format!(" for this session, could not save it: {error}"));
+        }
+        if held == listed || (held && !persist) {
+            return Ok(message);
+        }
+
+        let mut written = false;
+        self.tui.suspend(|| -> eyre::Result<()> {
+            let status = if held {
+                ignore::add(ignore_file, package_name)?
+            } else {
+                ignore::remove(ignore_file, package_name)?
+            };
+            written = status.success();
+            Ok(())
+        })?;
+        if !written {
+            message.push_str(&format!(", could not write {}", ignore_file.display()));
+        } else if held && !ignore::is_included(ignore_file) {
+            message.push_str(&format!(", {}", ignore::include_hint(ignore_file)));
+        }
+        Ok(message)
+    }
+
     fn handle_actions(&mut self, actions: &Vec<Action>) -> eyre::Result<()> {
         let mut events = Vec::new();
 
         for action in actions {
-            let app_events = self.handle_action(&action)?;
+            let app_events = self.handle_action(action)?;
             events.extend(app_events);
         }
 
@@ -116,10 +517,31 @@ impl App {
 
         match action {
             Action::SearchPackage(package_name) => {
-                let packages = self.pacman.search_package(package_name)?;
+                let packages = self.pacman.search_syncdbs(package_name)?;
                 events.push(crate::event::Event::FoundPackages(packages));
+                self.query = package_name.clone();
+
+                if let Some(aur) = self.pacman.aur().cloned() {
+                    let query = package_name.clone();
+                    self.debounce(&self.aur_searches, move || {
+                        let packages = aur.search(&query)?;
+                        Ok(Action::AurPackagesFound(query, packages))
+                    });
+                }
+            }
+            Action::AurPackagesFound(query, packages) => {
+                if *query == self.query {
+                    let packages = self.pacman.with_local(packages.clone());
+                    events.push(crate::event::Event::FoundAurPackages(packages));
+                }
             }
             Action::InstallPackage(package_name) => {
+                if self.upgrade_pending {
+                    events.push(crate::event::Event::Notified(format!(
+                        "the sync databases were refreshed since the last upgrade, press [I] to upgrade the system along with {package_name}"
+                    )));
+                    return Ok(events);
+                }
                 self.tui.suspend(|| -> eyre::Result<()> {
                     let status = pacman::install_package(package_name)?;
                     if status.success() {
@@ -129,13 +551,57 @@ impl App {
                 })?;
             }
             Action::UpdateInstallPackage(package_name) => {
-                self.tui.suspend(|| -> eyre::Result<()> {
-                    let status = pacman::update_install_package(package_name)?;
-                    if status.success() {
-                        events.push(crate::event::Event::PackageInstalled(package_name.clone()));
+                if self.holds.contains(package_name) {
+                    events.push(crate::event::Event::Notified(format!(
+                        "{package_name} is held, release it to upgrade it"
+                    )));
+                    return Ok(events);
+                }
+                if !self.news_ignored {
+                    let unread = self.unread_news();
+                    if !unread.is_empty() {
+                        let titles = unread
+                            .iter()
+                            .map(|item| format!("- {}", item.title))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        if self.config.news.block_upgrades {
+                            events.push(crate::event::Event::Notified(format!(
+                                "read the news since the last upgrade first:\n{titles}"
+                            )));
+                            events.extend(self.handle_action(&Action::ShowView(View::News))?);
+                        } else {
+                            events.extend(self.handle_action(&Action::Confirm(
+                                format!(
+                                    "{} unread news since the last upgrade:\n{titles}\nupgrade anyway?",
+                                    unread.len()
+                                ),
+                                Box::new(Action::IgnoreNews(Box::new(action.clone()))),
+                            ))?);
+                        }
+                        return Ok(events);
                     }
+                }
+                let held = self.holds.names();
+                let mut installed = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    installed = pacman::update_install_package(package_name, &held)?.success();
                     Ok(())
                 })?;
+                if installed {
+                    // the handle keeps the databases it read
+                    self.pacman = Pacman::new(&self.config)?;
+                    self.sync_statuses = self.pacman.sync_statuses();
+                    self.last_upgrade = Some(Utc::now().timestamp());
+                    // -Syu refreshes the databases too
+                    self.upgrade_pending = false;
+                    if let Err(error) = self.last_sync.record() {
+                        events.push(crate::event::Event::Notified(format!(
+                            "could not save when the databases were refreshed: {error}"
+                        )));
+                    }
+                    events.push(crate::event::Event::PackageInstalled(package_name.clone()));
+                }
             }
             Action::RemovePackage(package_name) => {
                 self.tui.suspend(|| -> eyre::Result<()> {
@@ -148,6 +614,1044 @@ impl App {
             }
             Action::SelectPackage(package) => {
                 events.push(crate::event::Event::PackageSelected(package.clone()));
+                events.push(crate::event::Event::SignatureLoaded(
+                    package.name.clone(),
+                    self.pacman.signature(package),
+                ));
+                // the optional dependencies are resolved once their view is shown
+                self.selected = Some(package.clone());
+
+                // search results lack the dependencies, licenses and the like
+                if package.source == aur::SOURCE {
+                    if let Some(aur) = self.pacman.aur().cloned() {
+                        let name = package.name.clone();
+                        self.debounce(&self.aur_lookups, move || {
+                            let package = aur.info(&[&name])?.into_iter().next();
+                            let package = package
+                                .ok_or_else(|| eyre::eyre!("package '{name}' was not found"))?;
+                            Ok(Action::AurPackageLoaded(package))
+                        });
+                    }
+                }
+            }
+            Action::AurPackageLoaded(package) => {
+                let selected = self.selected.as_ref();
+                if selected.is_some_and(|selected| selected.name == package.name) {
+                    let package = self.pacman.with_local(vec![package.clone()]).remove(0);
+                    let signature = self.pacman.signature(&package);
+                    events.push(crate::event::Event::PackageSelected(package.clone()));
+                    events.push(crate::event::Event::SignatureLoaded(
+                        package.name.clone(),
+                        signature,
+                    ));
+                    self.selected = Some(package);
+                }
+            }
+            Action::Focus(pane) => {
+                self.focus = *pane;
+                self.layout.focus(*pane);
+                events.push(crate::event::Event::PaneFocused(*pane));
+            }
+            Action::Confirm(message, action) => {
+                events.push(crate::event::Event::ConfirmationRequested(
+                    message.clone(),
+                    *action.clone(),
+                ));
+            }
+            Action::Prompt(message, input, action) => {
+                events.push(crate::event::Event::InputRequested(
+                    message.clone(),
+                    input.clone(),
+                    *action.clone(),
+                ));
+            }
+            Action::ExportPackages(path, packages) => {
+                let message = match export::write_packages(path, packages) {
+                    Ok(()) => format!("exported {} packages to {}", packages.len(), path.display()),
+                    Err(error) => format!("export failed: {error}"),
+                };
+                events.push(crate::event::Event::Notified(message));
+            }
+            Action::Notify(message) => {
+                events.push(crate::event::Event::Notified(message.clone()));
+            }
+            Action::InstallAurPackage(package_name) => {
+                if !self.build_target.is_empty() {
+                    events.push(crate::event::Event::Notified(format!(
+                        "{} is still being installed",
+                        self.build_target
+                    )));
+                    return Ok(events);
+                }
+                let Some(build_dir) = self.config.aur.build_dir() else {
+                    events.push(crate::event::Event::Notified(String::from(
+                        "no build directory, set aur.build_dir in the config",
+                    )));
+                    return Ok(events);
+                };
+
+                self.build_target = package_name.clone();
+                events.push(crate::event::Event::BuildStarted(format!(
+                    "installing {package_name}"
+                )));
+                events.push(crate::event::Event::BuildLogged(String::from(
+                    "resolving dependencies",
+                )));
+
+                // alpm handles cannot be shared between threads
+                let config = self.config.clone();
+                let package_name = package_name.clone();
+                self.spawn_build_step(move |_| {
+                    let pacman = Pacman::new(&config)?;
+                    let aur = pacman
+                        .aur()
+                        .ok_or_else(|| eyre::eyre!("the AUR is disabled"))?;
+                    let plan = makepkg::plan(&pacman, aur, &build_dir, &package_name)?;
+                    Ok(Action::AurBuildPlanned(plan))
+                });
+            }
+            Action::AurBuildPlanned(plan) => {
+                if !plan.repo_deps.is_empty() {
+                    let mut installed = false;
+                    self.tui.suspend(|| -> eyre::Result<()> {
+                        installed = pacman::install_dependencies(&plan.repo_deps)?.success();
+                        Ok(())
+                    })?;
+                    if !installed {
+                        self.builds.clear();
+                        self.build_target.clear();
+                        events.push(crate::event::Event::BuildFinished(String::from(
+                            "failed to install the dependencies from the sync databases",
+                        )));
+                        return Ok(events);
+                    }
+                }
+
+                self.builds = plan.builds.iter().cloned().collect();
+                self.build_count = self.builds.len();
+                self.next_build(&mut events);
+            }
+            Action::BuildFetched(build, review) => {
+                events.push(crate::event::Event::BuildReviewRequested(
+                    build.clone(),
+                    review.clone(),
+                ));
+            }
+            Action::BuildReviewed(build, revision) => {
+                let build = build.clone();
+                let revision = revision.clone();
+                self.spawn_build_step(move |log| {
+                    let files = makepkg::build(&build, log)?;
+                    Ok(Action::BuildCompleted(build, revision, files))
+                });
+            }
+            Action::BuildCancelled => {
+                self.builds.clear();
+                self.build_target.clear();
+                events.push(crate::event::Event::BuildFinished(String::from(
+                    "installation cancelled",
+                )));
+            }
+            Action::BuildLogged(line) => {
+                events.push(crate::event::Event::BuildLogged(line.clone()));
+            }
+            Action::BuildCompleted(build, revision, files) => {
+                let mut installed = Ok(false);
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    installed =
+                        pacman::install_files(files, build.as_deps).map(|status| status.success());
+                    Ok(())
+                })?;
+
+                match installed {
+                    Ok(true) => {
+                        // only installed builds skip the review of their changes next time
+                        if let Err(error) = makepkg::mark_built(build, revision) {
+                            events.push(crate::event::Event::Notified(format!(
+                                "could not record the build of {}: {error}",
+                                build.base
+                            )));
+                        }
+                        for package_name in &build.packages {
+                            events
+                                .push(crate::event::Event::PackageInstalled(package_name.clone()));
+                        }
+                        self.next_build(&mut events);
+                    }
+                    failed => {
+                        self.builds.clear();
+                        self.build_target.clear();
+                        let reason = failed.err().map(|error| format!(": {error}"));
+                        events.push(crate::event::Event::Notified(format!(
+                            "failed to install {}{}",
+                            build.packages.join(", "),
+                            reason.unwrap_or_default()
+                        )));
+                        events.push(crate::event::Event::BuildFinished(format!(
+                            "failed to install {}",
+                            build.packages.join(", ")
+                        )));
+                    }
+                }
+            }
+            Action::ViewScripts(package) => {
+                let mut scripts = Vec::new();
+                let base = package.base.as_ref().unwrap_or(&package.name);
+                let clone = self.config.aur.build_dir().map(|dir| dir.join(base));
+                match clone {
+                    Some(dir) if dir.join("PKGBUILD").exists() => match makepkg::scripts(&dir) {
+                        Ok(found) => scripts.extend(found),
+                        Err(error) => events.push(crate::event::Event::Notified(format!(
+                            "could not read the scripts in {}: {error}",
+                            dir.display()
+                        ))),
+                    },
+                    _ if package.source == aur::SOURCE => {
+                        if let Some(aur) = self.pacman.aur().cloned() {
+                            let base = base.clone();
+                            let name = package.name.clone();
+                            let install_script = self.pacman.install_script(&name);
+                            self.spawn(move || {
+                                let mut scripts = vec![Script {
+                                    name: String::from("PKGBUILD"),
+                                    content: aur.pkgbuild(&base)?,
+                                }];
+                                if let Some(path) = install_script {
+                                    scripts.push(Script::read("install (installed)", &path)?);
+                                }
+                                Ok(Action::ScriptsLoaded(name, scripts))
+                            });
+                            return Ok(events);
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some(path) = self.pacman.install_script(&package.name) {
+                    match Script::read("install (installed)", &path) {
+                        Ok(script) => scripts.push(script),
+                        Err(error) => events.push(crate::event::Event::Notified(format!(
+                            "could not read {}: {error}",
+                            path.display()
+                        ))),
+                    }
+                }
+
+                if scripts.is_empty() {
+                    // the notices above already say why there is nothing to show
+                    if events.is_empty() {
+                        events.push(crate::event::Event::Notified(format!(
+                            "{} has no PKGBUILD or install script to show",
+                            package.name
+                        )));
+                    }
+                } else {
+                    events.extend(
+                        self.handle_action(&Action::ScriptsLoaded(package.name.clone(), scripts))?,
+                    );
+                }
+            }
+            Action::ScriptsLoaded(package_name, scripts) => {
+                self.layout.open_viewer();
+                events.push(crate::event::Event::ScriptsLoaded(
+                    package_name.clone(),
+                    scripts.clone(),
+                ));
+                events.extend(self.handle_action(&Action::Focus(Pane::ScriptViewer))?);
+            }
+            Action::CloseViewer => {
+                self.layout.close_viewer();
+                if self.focus == Pane::ScriptViewer {
+                    events.extend(self.handle_action(&Action::Focus(Pane::PackagesTable))?);
+                }
+            }
+            Action::ShowView(view) => {
+                if matches!(view, View::Log | View::History) {
+                    events.extend(self.handle_action(&Action::LoadLog)?);
+                }
+                if matches!(view, View::History | View::Cache) {
+                    events.extend(self.handle_action(&Action::LoadCache)?);
+                }
+                if *view == View::Pacnew {
+                    events.extend(self.handle_action(&Action::LoadConfigFiles)?);
+                }
+                if *view == View::Optdepends {
+                    events.extend(self.handle_action(&Action::LoadOptdepends)?);
+                }
+                if *view == View::Keyring {
+                    events.extend(self.handle_action(&Action::LoadKeyring)?);
+                }
+                if *view == View::Mirrors {
+                    events.extend(self.handle_action(&Action::LoadMirrors)?);
+                }
+                if *view == View::State {
+                    events.extend(self.handle_action(&Action::LoadDesiredState)?);
+                }
+                events.extend(self.handle_action(&Action::Focus(view.pane()))?);
+            }
+            Action::LoadLog => match Log::read(&self.config.log_file) {
+                Ok(log) => {
+                    self.last_upgrade = log.last_upgrade();
+                    events.push(crate::event::Event::LogLoaded(log));
+                }
+                Err(error) => events.push(crate::event::Event::Notified(format!(
+                    "could not read {}: {error}",
+                    self.config.log_file.display()
+                ))),
+            },
+            Action::LoadCache => {
+                let cache = Cache::read(&self.config.cache_dirs);
+                let groups = cache.groups(&self.pacman.installed_versions());
+                events.push(crate::event::Event::CacheLoaded(cache));
+                events.push(crate::event::Event::CacheGrouped(groups));
+            }
+            Action::DeleteCacheFiles(paths) => {
+                let mut deleted = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    deleted = cache::remove_files(paths)?.success();
+                    Ok(())
+                })?;
+
+                let message = if deleted {
+                    format!("deleted {} files from the cache", paths.len())
+                } else {
+                    String::from("failed to delete the cache files")
+                };
+                events.push(crate::event::Event::Notified(message));
+                events.extend(self.handle_action(&Action::LoadCache)?);
+            }
+            Action::RevertTransaction(revert, partially) => {
+                if revert.missing() > 0 && !partially {
+                    events.push(crate::event::Event::Notified(format!(
+                        "transaction {} can only be reverted partially",
+                        revert.transaction + 1
+                    )));
+                    return Ok(events);
+                }
+
+                // one transaction, so removals are not left half done when a downgrade fails
+                let transaction = Transaction {
+                    files: revert.files(),
+                    remove: revert.removals(),
+                    ..Default::default()
+                };
+                let config = self.config.clone();
+                let mut outcome = Ok(false);
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    outcome = pacman::commit(&transaction, &config).map(|status| status.success());
+                    Ok(())
+                })?;
+                self.pacman = Pacman::new(&self.config)?;
+
+                let number = revert.transaction + 1;
+                let message = match outcome {
+                    Ok(true) => format!("reverted transaction {number}"),
+                    Ok(false) => format!("failed to revert transaction {number}"),
+                    Err(error) => format!("failed to revert transaction {number}: {error}"),
+                };
+                events.push(crate::event::Event::Notified(message));
+                events.extend(self.handle_action(&Action::LoadLog)?);
+                events.extend(self.handle_action(&Action::LoadCache)?);
+            }
+            Action::ShowVersions(package) => {
+                let package_name = &package.name;
+                let Some(installed_version) = package.installed_version.clone() else {
+                    events.push(crate::event::Event::Notified(format!(
+                        "{package_name} is not installed"
+                    )));
+                    return Ok(events);
+                };
+
+                let archive = self
+                    .config
+                    .archive
+                    .enabled
+                    .then(|| Archive::new(&self.config.archive));
+                let lookups = 1 + usize::from(archive.is_some());
+                events.push(crate::event::Event::VersionsRequested(
+                    package_name.clone(),
+                    installed_version,
+                    lookups,
+                ));
+
+                // loading and verifying the files reads them whole
+                let config = self.config.clone();
+                let name = package_name.clone();
+                self.spawn(move || {
+                    let pacman = Pacman::new(&config)?;
+                    let cache = Cache::read(&config.cache_dirs);
+                    let candidates = downgrade::cached(&pacman, &cache, &name);
+                    Ok(Action::VersionsFound(name, candidates))
+                });
+                if let Some(archive) = archive {
+                    let name = package_name.clone();
+                    let sender = self.sender.clone();
+                    self.spawn(move || {
+                        let candidates = archive.versions(&name).unwrap_or_else(|error| {
+                            let _ = sender.send(Action::Notify(format!(
+                                "could not list the archived versions: {error}"
+                            )));
+                            Vec::new()
+                        });
+                        Ok(Action::VersionsFound(name, candidates))
+                    });
+                }
+            }
+            Action::VersionsFound(package_name, candidates) => {
+                events.push(crate::event::Event::VersionsFound(
+                    package_name.clone(),
+                    candidates.clone(),
+                ));
+            }
+            Action::InstallVersion(package_name, candidate, hold) => {
+                let mut installed = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    installed = pacman::install_target(&candidate.target())?.success();
+                    Ok(())
+                })?;
+
+                let mut message = if installed {
+                    format!("installed {package_name} {}", candidate.version)
+                } else {
+                    format!("failed to install {package_name} {}", candidate.version)
+                };
+                // pacman has to skip a downgraded package as well
+                if installed && *hold {
+                    message.push('\n');
+                    message.push_str(&self.set_hold(package_name, true, true, &mut events)?);
+                }
+                events.push(crate::event::Event::Notified(message));
+                if installed {
+                    events.extend(self.handle_action(&Action::SearchPackage(self.query.clone()))?);
+                }
+            }
+            Action::ToggleHold(package_name) => {
+                let held = !self.holds.contains(package_name);
+                let persist = self.config.persist_holds;
+                let message = self.set_hold(package_name, held, persist, &mut events)?;
+                events.push(crate::event::Event::Notified(message));
+            }
+            Action::LoadConfigFiles => {
+                // walking /etc and diffing every file takes a while
+                let config = self.config.clone();
+                self.spawn(move || {
+                    let pacman = Pacman::new(&config)?;
+                    Ok(Action::ConfigFilesFound(pacnew::find(&pacman)))
+                });
+            }
+            Action::ConfigFilesFound(files) => {
+                events.push(crate::event::Event::ConfigFilesLoaded(files.clone()));
+            }
+            Action::ResolveConfigFile(file, resolution) => {
+                let mut resolved = Ok(false);
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    let status = match resolution {
+                        Resolution::Replace => pacnew::replace(file),
+                        Resolution::Merge => pacnew::merge(file),
+                        Resolution::Remove => pacnew::remove(file),
+                    };
+                    resolved = status.map(|status| status.success());
+                    Ok(())
+                })?;
+
+                let path = file.path.display();
+                match (resolution, resolved) {
+                    (_, Err(error)) => {
+                        events.push(crate::event::Event::Notified(format!(
+                            "could not resolve {path}: {error}"
+                        )));
+                    }
+                    (_, Ok(false)) => {
+                        events.push(crate::event::Event::Notified(format!(
+                            "could not resolve {path}"
+                        )));
+                    }
+                    (Resolution::Replace, _) => events.push(crate::event::Event::Notified(
+                        format!("replaced {} with {path}", file.live.display()),
+                    )),
+                    // the merged file is left to remove once the merge is checked
+                    (Resolution::Merge, _) => {
+                        events.extend(self.handle_action(&Action::Confirm(
+                            format!("merged, remove {path}?"),
+                            Box::new(Action::ResolveConfigFile(file.clone(), Resolution::Remove)),
+                        ))?)
+                    }
+                    (Resolution::Remove, _) => {}
+                }
+                events.extend(self.handle_action(&Action::LoadConfigFiles)?);
+            }
+            Action::CheckIntegrity(package_name) => {
+                let packages = match package_name {
+                    Some(package_name) => vec![package_name.clone()],
+                    None => {
+                        let mut packages = self
+                            .pacman
+                            .installed_versions()
+                            .into_keys()
+                            .collect::<Vec<_>>();
+                        packages.sort();
+                        packages
+                    }
+                };
+                let generation = self.integrity_checks.fetch_add(1, Ordering::SeqCst) + 1;
+                events.push(crate::event::Event::IntegrityCheckStarted(packages.len()));
+                events.extend(self.handle_action(&Action::Focus(Pane::Integrity))?);
+
+                let config = self.config.clone();
+                let counter = Arc::clone(&self.integrity_checks);
+                let sender = self.sender.clone();
+                thread::spawn(move || {
+                    let pacman = match Pacman::new(&config) {
+                        Ok(pacman) => pacman,
+                        Err(error) => {
+                            let _ = sender.send(Action::Notify(error.to_string()));
+                            return;
+                        }
+                    };
+                    let backup = pacman.backup_files().into_keys().collect();
+                    for package in packages {
+                        if counter.load(Ordering::SeqCst) != generation {
+                            return;
+                        }
+                        let report = integrity::check(&pacman, &package, &backup)
+                            .unwrap_or_else(|error| integrity::Report::failed(package, error));
+                        if sender
+                            .send(Action::IntegrityChecked(generation, report))
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                });
+            }
+            Action::IntegrityChecked(generation, report) => {
+                if *generation == self.integrity_checks.load(Ordering::SeqCst) {
+                    events.push(crate::event::Event::IntegrityChecked(report.clone()));
+                }
+            }
+            Action::CancelIntegrityCheck => {
+                self.integrity_checks.fetch_add(1, Ordering::SeqCst);
+                events.push(crate::event::Event::IntegrityCheckCancelled);
+            }
+            Action::PreviewReasons(reasons) => {
+                let orphans = |packages: Vec<pacman::Package>| {
+                    packages
+                        .into_iter()
+                        .map(|package| package.name)
+                        .collect::<BTreeSet<_>>()
+                };
+                let before = orphans(self.pacman.orphans());
+                let after = orphans(
+                    self.pacman
+                        .orphans_after(&reasons.iter().cloned().collect()),
+                );
+
+                let mut lines = Vec::new();
+                for (reason, label) in [
+                    (Reason::Explicit, "explicitly installed"),
+                    (Reason::Depend, "dependencies"),
+                ] {
+                    let package_names = reasons
+                        .iter()
+                        .filter(|(_, r)| *r == reason)
+                        .map(|(package_name, _)| package_name.as_str())
+                        .collect::<Vec<_>>();
+                    if !package_names.is_empty() {
+                        lines.push(format!("mark {} as {label}", package_names.join(", ")));
+                    }
+                }
+                lines.push(String::new());
+                let orphaned = after.difference(&before).cloned().collect::<Vec<_>>();
+                let adopted = before.difference(&after).cloned().collect::<Vec<_>>();
+                if orphaned.is_empty() && adopted.is_empty() {
+                    lines.push(String::from("the orphans stay the same"));
+                }
+                if !orphaned.is_empty() {
+                    lines.push(format!("new orphans: {}", orphaned.join(", ")));
+                }
+                if !adopted.is_empty() {
+                    lines.push(format!("no longer orphans: {}", adopted.join(", ")));
+                }
+
+                events.extend(self.handle_action(&Action::Confirm(
+                    lines.join("\n"),
+                    Box::new(Action::SetReasons(reasons.clone())),
+                ))?);
+            }
+            Action::SetReasons(reasons) => {
+                let changed = match self.pacman.set_reasons(reasons) {
+                    Ok(()) => Ok(true),
+                    // only root can lock the database, pacman asks for it otherwise
+                    Err(alpm::Error::HandleLock) => {
+                        let mut changed = false;
+                        self.tui.suspend(|| -> eyre::Result<()> {
+                            changed = pacman::set_reasons(reasons)?.success();
+                            Ok(())
+                        })?;
+                        Ok(changed)
+                    }
+                    Err(error) => Err(error),
+                };
+
+                match changed {
+                    Ok(true) => {
+                        // the handle keeps the install reasons it read
+                        self.pacman = Pacman::new(&self.config)?;
+                        events.push(crate::event::Event::ReasonsChanged(reasons.clone()));
+                        events.push(crate::event::Event::Notified(format!(
+                            "changed the install reason of {} packages",
+                            reasons.len()
+                        )));
+                    }
+                    Ok(false) => events.push(crate::event::Event::Notified(String::from(
+                        "failed to change the install reasons",
+                    ))),
+                    Err(error) => events.push(crate::event::Event::Notified(format!(
+                        "failed to change the install reasons: {error}"
+                    ))),
+                }
+            }
+            Action::LoadOptdepends => {
+                if let Some(package) = &self.selected {
+                    events.push(crate::event::Event::OptdependsLoaded(
+                        package.clone(),
+                        self.pacman.optdepends(package),
+                    ));
+                }
+                events.push(crate::event::Event::OptionalOnlyLoaded(
+                    self.pacman.optional_only(),
+                ));
+            }
+            Action::InstallOptdepends(package, package_names) => {
+                if self.upgrade_pending {
+                    events.push(crate::event::Event::Notified(String::from(
+                        "the sync databases were refreshed since the last upgrade, upgrade the system with [I] first",
+                    )));
+                    return Ok(events);
+                }
+                let mut installed = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    installed = pacman::install_dependencies(package_names)?.success();
+                    Ok(())
+                })?;
+
+                if installed {
+                    // the handle keeps the packages it read
+                    self.pacman = Pacman::new(&self.config)?;
+                    for package_name in package_names {
+                        events.push(crate::event::Event::PackageInstalled(package_name.clone()));
+                    }
+                    events.extend(self.handle_action(&Action::LoadOptdepends)?);
+                } else {
+                    events.push(crate::event::Event::Notified(format!(
+                        "failed to install the optional dependencies of {}",
+                        package.name
+                    )));
+                }
+            }
+            Action::BrowsePackageFiles(dir) => {
+                let dir = dir.clone().unwrap_or_else(|| self.package_dir.clone());
+                match package_file::list(&dir) {
+                    Ok(entries) => {
+                        self.package_dir = dir.clone();
+                        events.push(crate::event::Event::PackageFilesListed(dir, entries));
+                    }
+                    Err(error) => events.push(crate::event::Event::Notified(format!(
+                        "could not open {}: {error}",
+                        dir.display()
+                    ))),
+                }
+            }
+            Action::LoadPackageFile(path) => match self.pacman.load_file(path) {
+                Ok(package) => {
+                    let package = self.pacman.with_local(vec![package]).remove(0);
+                    events.push(crate::event::Event::PackageFileLoaded(
+                        path.clone(),
+                        package,
+                    ));
+                }
+                Err(error) => events.push(crate::event::Event::Notified(format!(
+                    "could not read {}: {error}",
+                    path.display()
+                ))),
+            },
+            Action::InstallPackageFile(path, package_name) => {
+                let mut installed = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    installed = pacman::install_files(std::slice::from_ref(path), false)?.success();
+                    Ok(())
+                })?;
+
+                if installed {
+                    // the handle keeps the packages it read
+                    self.pacman = Pacman::new(&self.config)?;
+                    events.push(crate::event::Event::PackageInstalled(package_name.clone()));
+                } else {
+                    events.push(crate::event::Event::Notified(format!(
+                        "failed to install {}",
+                        path.display()
+                    )));
+                }
+            }
+            Action::InstallPackageUrl(url) => {
+                let mut installed = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    installed = pacman::install_target(url)?.success();
+                    Ok(())
+                })?;
+
+                if installed {
+                    self.pacman = Pacman::new(&self.config)?;
+                    events.extend(self.handle_action(&Action::SearchPackage(self.query.clone()))?);
+                } else {
+                    events.push(crate::event::Event::Notified(format!(
+                        "failed to install {url}"
+                    )));
+                }
+            }
+            Action::RefreshDatabases => {
+                if self.refresh_progress.is_some() {
+                    return Ok(events);
+                }
+                self.refresh_progress = Some(String::from("refreshing the databases"));
+
+                let config = self.config.clone();
+                let sender = self.sender.clone();
+                self.spawn(move || {
+                    let mut pacman = Pacman::new(&config)?;
+                    let progress = move |file_name: &str, downloaded, total| {
+                        let _ = sender.send(Action::RefreshProgressed(
+                            file_name.to_owned(),
+                            downloaded,
+                            total,
+                        ));
+                    };
+                    let action = match pacman.update_syncdbs(progress) {
+                        Ok(_) => Action::DatabasesRefreshed(Ok(())),
+                        Err(alpm::Error::HandleLock) => Action::RefreshWithPacman,
+                        Err(error) => Action::DatabasesRefreshed(Err(error.to_string())),
+                    };
+                    Ok(action)
+                });
+            }
+            Action::RefreshProgressed(file_name, downloaded, total) => {
+                if self.refresh_progress.is_some() {
+                    let percent = if *total > 0 {
+                        downloaded * 100 / total
+                    } else {
+                        0
+                    };
+                    self.refresh_progress = Some(format!("syncing {file_name} {percent}%"));
+                }
+            }
+            Action::RefreshWithPacman => {
+                let mut refreshed = Ok(false);
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    refreshed = sync::refresh_with_pacman().map(|status| status.success());
+                    Ok(())
+                })?;
+                let result = match refreshed {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(String::from("pacman -Sy failed")),
+                    Err(error) => Err(error.to_string()),
+                };
+                events.extend(self.handle_action(&Action::DatabasesRefreshed(result))?);
+            }
+            Action::DatabasesRefreshed(result) => {
+                self.refresh_progress = None;
+                self.pacman = Pacman::new(&self.config)?;
+                self.sync_statuses = self.pacman.sync_statuses();
+                match result {
+                    Ok(()) => {
+                        // installing from newer databases without upgrading is a partial upgrade
+                        self.upgrade_pending = true;
+                        let mut message = String::from(
+                            "refreshed the sync databases, [i] installs wait for an upgrade, press [I] to upgrade along with a package",
+                        );
+                        if let Err(error) = self.last_sync.record() {
+                            message.push_str(&format!(
+                                "\ncould not save when they were refreshed: {error}"
+                            ));
+                        }
+                        events.push(crate::event::Event::Notified(message));
+                        events.extend(
+                            self.handle_action(&Action::SearchPackage(self.query.clone()))?,
+                        );
+                    }
+                    Err(error) => events.push(crate::event::Event::Notified(format!(
+                        "could not refresh the sync databases: {error}"
+                    ))),
+                }
+            }
+            Action::LoadKeyring => {
+                let gpg_dir = self.config.gpg_dir.clone();
+                self.spawn(move || Ok(Action::KeyringLoaded(keyring::list(&gpg_dir)?)));
+            }
+            Action::KeyringLoaded(keys) => {
+                events.push(crate::event::Event::KeyringLoaded(
+                    keys.clone(),
+                    self.pacman.packagers(),
+                ));
+            }
+            Action::LoadMirrors => match fs::read_to_string(&self.config.mirrorlist) {
+                Ok(content) => events.push(crate::event::Event::MirrorsLoaded(mirrorlist::parse(
+                    &content,
+                ))),
+                Err(error) => events.push(crate::event::Event::Notified(format!(
+                    "could not read {}: {error}",
+                    self.config.mirrorlist.display()
+                ))),
+            },
+            Action::MeasureMirrors(servers) => {
+                let generation = self.mirror_measurements.fetch_add(1, Ordering::SeqCst) + 1;
+                events.push(crate::event::Event::MirrorMeasurementStarted(servers.len()));
+
+                let servers = servers.clone();
+                let fetcher = Arc::clone(&self.fetcher);
+                let counter = Arc::clone(&self.mirror_measurements);
+                let sender = self.sender.clone();
+                thread::spawn(move || {
+                    for server in servers {
+                        if counter.load(Ordering::SeqCst) != generation {
+                            return;
+                        }
+                        let speed = mirrorlist::measure(fetcher.as_ref(), &server)
+                            .map_err(|error| error.to_string());
+                        if sender
+                            .send(Action::MirrorMeasured(generation, server, speed))
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                });
+            }
+            Action::MirrorMeasured(generation, server, speed) => {
+                if *generation == self.mirror_measurements.load(Ordering::SeqCst) {
+                    events.push(crate::event::Event::MirrorMeasured(
+                        server.clone(),
+                        speed.clone(),
+                    ));
+                }
+            }
+            Action::CancelMirrorMeasurement => {
+                self.mirror_measurements.fetch_add(1, Ordering::SeqCst);
+                events.push(crate::event::Event::MirrorMeasurementCancelled);
+            }
+            Action::WriteMirrorlist(mirrors) => {
+                let content = mirrorlist::render(mirrors);
+                let path = self.config.mirrorlist.clone();
+                let mut written = false;
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    written = mirrorlist::write(&path, &content)?.success();
+                    Ok(())
+                })?;
+
+                if written {
+                    events.push(crate::event::Event::Notified(format!(
+                        "wrote {}, the old one is {}.bak",
+                        path.display(),
+                        path.display()
+                    )));
+                    events.extend(self.handle_action(&Action::LoadMirrors)?);
+                } else {
+                    events.push(crate::event::Event::Notified(format!(
+                        "failed to write {}",
+                        path.display()
+                    )));
+                }
+            }
+            Action::LoadNews => {
+                let config = self.config.news.clone();
+                let log_file = self.config.log_file.clone();
+                self.spawn(move || {
+                    let feed = news::load(&config)?;
+                    let last_upgrade = Log::read(&log_file).ok().and_then(|log| log.last_upgrade());
+                    Ok(Action::NewsLoaded(feed, last_upgrade))
+                });
+            }
+            Action::NewsLoaded(feed, last_upgrade) => {
+                self.news = feed.clone();
+                self.last_upgrade = *last_upgrade;
+                events.push(crate::event::Event::NewsLoaded(
+                    feed.clone(),
+                    self.read_news.ids(),
+                ));
+                let unread = self.unread_news().len();
+                if unread > 0 {
+                    events.push(crate::event::Event::Notified(format!(
+                        "{unread} unread news since the last upgrade, see the news view"
+                    )));
+                }
+            }
+            Action::MarkNewsRead(ids) => {
+                // the items stay read until ptu exits
+                if let Err(error) = self.read_news.mark(ids) {
+                    events.push(crate::event::Event::Notified(format!(
+                        "could not save the read news: {error}"
+                    )));
+                }
+                events.push(crate::event::Event::NewsRead(ids.clone()));
+            }
+            Action::IgnoreNews(action) => {
+                self.news_ignored = true;
+                let result = self.handle_action(action);
+                self.news_ignored = false;
+                events.extend(result?);
+            }
+            Action::ExportManifest(path, with_versions) => {
+                let manifest = Manifest::from_system(&self.pacman, *with_versions);
+                let message = match manifest.write(path) {
+                    Ok(()) => format!(
+                        "exported {} explicitly installed packages to {}",
+                        manifest.len(),
+                        path.display()
+                    ),
+                    Err(error) => format!("export failed: {error}"),
+                };
+                events.push(crate::event::Event::Notified(message));
+            }
+            Action::ImportManifest(path) => match Manifest::read(path) {
+                Ok(manifest) => {
+                    let changes = manifest::compare(&manifest, &self.pacman);
+                    if changes.is_empty() {
+                        events.push(crate::event::Event::Notified(format!(
+                            "the installed packages match {}",
+                            path.display()
+                        )));
+                    } else {
+                        events.push(crate::event::Event::ManifestCompared(path.clone(), changes));
+                    }
+                }
+                Err(error) => events.push(crate::event::Event::Notified(format!(
+                    "could not read {}: {error}",
+                    path.display()
+                ))),
+            },
+            Action::ApplyManifest(changes) => {
+                let config = self.config.clone();
+                let mut applied = Ok(false);
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    applied = manifest::apply(changes, &config).map(|status| status.success());
+                    Ok(())
+                })?;
+                // the handle keeps the packages it read
+                self.pacman = Pacman::new(&self.config)?;
+
+                if let Ok(true) = applied {
+                    let mut reasons = Vec::new();
+                    for change in changes.iter().filter(|change| change.is_applicable()) {
+                        let name = change.name().to_owned();
+                        events.push(match change {
+                            Change::Install { .. } | Change::Version { .. } => {
+                                crate::event::Event::PackageInstalled(name)
+                            }
+                            Change::Remove(_) => crate::event::Event::PackageRemoved(name),
+                            Change::Explicit(_) => {
+                                reasons.push((name, Reason::Explicit));
+                                continue;
+                            }
+                            Change::Depend(_) => {
+                                reasons.push((name, Reason::Depend));
+                                continue;
+                            }
+                        });
+                    }
+                    if !reasons.is_empty() {
+                        events.push(crate::event::Event::ReasonsChanged(reasons));
+                    }
+                    let skipped = changes
+                        .iter()
+                        .filter(|change| !change.is_applicable())
+                        .map(Change::name)
+                        .collect::<Vec<_>>();
+                    events.push(crate::event::Event::Notified(if skipped.is_empty() {
+                        format!("applied {} changes", changes.len())
+                    } else {
+                        format!(
+                            "applied {} changes, install {} by hand",
+                            changes.len() - skipped.len(),
+                            skipped.join(", ")
+                        )
+                    }));
+                } else {
+                    let reason = applied.err().map(|error| format!(": {error}"));
+                    events.push(crate::event::Event::Notified(format!(
+                        "failed to apply the manifest{}",
+                        reason.unwrap_or_default()
+                    )));
+                }
+            }
+            Action::LoadDesiredState => {
+                let Some(path) = self.config.desired_state() else {
+                    events.push(crate::event::Event::Notified(String::from(
+                        "no desired state file, set desired_state in the config",
+                    )));
+                    return Ok(events);
+                };
+                let differences = DesiredState::read(&path)
+                    .map(|state| desired::compare(&state, &self.pacman, &self.holds))
+                    .map_err(|error| error.to_string());
+                events.push(crate::event::Event::DesiredStateCompared(path, differences));
+            }
+            Action::Reconcile(differences) => {
+                let mut outcome = Ok(false);
+                self.tui.suspend(|| -> eyre::Result<()> {
+                    outcome = desired::reconcile(differences, &mut self.holds, &self.config)
+                        .map(|status| status.success());
+                    Ok(())
+                })?;
+                self.pacman = Pacman::new(&self.config)?;
+                events.push(crate::event::Event::HoldsChanged(self.holds.names()));
+                events.push(crate::event::Event::Notified(match outcome {
+                    Ok(true) => format!(
+                        "fixed {} differences from the desired state",
+                        differences.len()
+                    ),
+                    Ok(false) => String::from("failed to reconcile with the desired state"),
+                    Err(error) => format!("failed to reconcile with the desired state: {error}"),
+                }));
+                events.extend(self.handle_action(&Action::LoadDesiredState)?);
+            }
+            Action::ShowPackage(package_name) => {
+                if let Some(package) = self.pacman.sync_package(package_name) {
+                    events.extend(self.handle_action(&Action::PackageFound(package))?);
+                } else if let Some(aur) = self.pacman.aur().cloned() {
+                    let name = package_name.clone();
+                    self.spawn(move || {
+                        let package = aur
+                            .info(&[&name])
+                            .map(|packages| packages.into_iter().next())
+                            .map_err(|error| error.to_string());
+                        Ok(Action::AurPackageLookedUp(name, package))
+                    });
+                } else {
+                    events.extend(self.show_local_package(package_name)?);
+                }
+            }
+            Action::AurPackageLookedUp(package_name, package) => match package {
+                Ok(Some(package)) => {
+                    let package = self.pacman.with_local(vec![package.clone()]).remove(0);
+                    events.extend(self.handle_action(&Action::PackageFound(package))?);
+                }
+                Ok(None) => events.extend(self.show_local_package(package_name)?),
+                Err(error) => {
+                    events.push(crate::event::Event::Notified(format!(
+                        "AUR request failed: {error}"
+                    )));
+                    events.extend(self.show_local_package(package_name)?);
+                }
+            },
+            Action::PackageFound(package) => {
+                events.push(crate::event::Event::FoundPackages(vec![package.clone()]));
+                events.extend(self.handle_action(&Action::SelectPackage(package.clone()))?);
+                events.extend(self.handle_action(&Action::Focus(Pane::PackageInfo))?);
+            }
+            Action::BuildFailed(message) => {
+                self.builds.clear();
+                self.build_target.clear();
+                events.push(crate::event::Event::BuildFinished(format!(
+                    "build failed: {message}"
+                )));
             }
         };
 
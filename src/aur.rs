@@ -0,0 +1,319 @@
+use std::time::Duration;
+
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AurConfig;
+use crate::pacman::{Dep, Package};
+
+pub(crate) const SOURCE: &str = "aur";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Metadata only the AUR has.
+#[derive(Clone, Serialize)]
+pub(crate) struct AurInfo {
+    pub(crate) votes: u64,
+    pub(crate) popularity: f64,
+    pub(crate) maintainer: Option<String>,
+    /// Unix timestamp of when the package was flagged out of date.
+    pub(crate) out_of_date: Option<i64>,
+    /// Unix timestamp.
+    pub(crate) last_modified: i64,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(rename = "type")]
+    kind: String,
+    error: Option<String>,
+    #[serde(default)]
+    results: Vec<RpcPackage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RpcPackage {
+    name: String,
+    package_base: Option<String>,
+    version: String,
+    description: Option<String>,
+    #[serde(rename = "URL")]
+    url: Option<String>,
+    num_votes: u64,
+    popularity: f64,
+    out_of_date: Option<i64>,
+    maintainer: Option<String>,
+    last_modified: i64,
+    #[serde(default)]
+    depends: Vec<String>,
+    #[serde(default)]
+    opt_depends: Vec<String>,
+    #[serde(default)]
+    make_depends: Vec<String>,
+    #[serde(default)]
+    check_depends: Vec<String>,
+    #[serde(default)]
+    conflicts: Vec<String>,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    replaces: Vec<String>,
+    #[serde(default)]
+    groups: Vec<String>,
+    #[serde(default)]
+    license: Vec<String>,
+}
+
+impl From<RpcPackage> for Package {
+    fn from(pkg: RpcPackage) -> Self {
+        let deps = |deps: Vec<String>| deps.iter().map(|dep| Dep::parse(dep)).collect();
+
+        Self {
+            name: pkg.name,
+            source: SOURCE.to_owned(),
+            version: pkg.version,
+            description: pkg.description,
+            base: pkg.package_base,
+            url: pkg.url,
+            licenses: pkg.license,
+            groups: pkg.groups,
+            depends: deps(pkg.depends),
+            optdepends: deps(pkg.opt_depends),
+            makedepends: deps(pkg.make_depends),
+            checkdepends: deps(pkg.check_depends),
+            provides: deps(pkg.provides),
+            conflicts: deps(pkg.conflicts),
+            replaces: deps(pkg.replaces),
+            aur: Some(AurInfo {
+                votes: pkg.num_votes,
+                popularity: pkg.popularity,
+                maintainer: pkg.maintainer,
+                out_of_date: pkg.out_of_date,
+                last_modified: pkg.last_modified,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Client for the AUR RPC interface.
+#[derive(Clone)]
+pub(crate) struct Aur {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl Aur {
+    pub(crate) fn new(config: &AurConfig) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+        Self {
+            url: config.url.trim_end_matches('/').to_owned(),
+            agent,
+        }
+    }
+
+    pub(crate) fn search(&self, query: &str) -> eyre::Result<Vec<Package>> {
+        // the AUR refuses queries shorter than two characters
+        if query.chars().count() < 2 {
+            return Ok(Vec::new());
+        }
+
+        let request = self
+            .request("search")
+            .query("by", "name-desc")
+            .query("arg", query);
+        self.call(request)
+    }
+
+    pub(crate) fn info(&self, names: &[&str]) -> eyre::Result<Vec<Package>> {
+        let request = names.iter().fold(self.request("info"), |request, name| {
+            request.query("arg[]", name)
+        });
+        self.call(request)
+    }
+
+    /// The packages providing `name`, with the details a search leaves out.
+    pub(crate) fn providers(&self, name: &str) -> eyre::Result<Vec<Package>> {
+        let request = self
+            .request("search")
+            .query("by", "provides")
+            .query("arg", name);
+        let names = self
+            .call(request)?
+            .into_iter()
+            .map(|package| package.name)
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.info(&names.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+
+    /// The git URL of a package base.
+    pub(crate) fn clone_url(&self, base: &str) -> String {
+        format!("{}/{base}.git", self.url)
+    }
+
+    /// The PKGBUILD of a package base, read without cloning it.
+    pub(crate) fn pkgbuild(&self, base: &str) -> eyre::Result<String> {
+        let pkgbuild = self
+            .agent
+            .get(&format!("{}/cgit/aur.git/plain/PKGBUILD", self.url))
+            .query("h", base)
+            .call()?
+            .into_string()?;
+        Ok(pkgbuild)
+    }
+
+    fn request(&self, kind: &str) -> ureq::Request {
+        self.agent
+            .get(&format!("{}/rpc/", self.url))
+            .query("v", "5")
+            .query("type", kind)
+    }
+
+    fn call(&self, request: ureq::Request) -> eyre::Result<Vec<Package>> {
+        let response: Response = request.call()?.into_json()?;
+
+        if response.kind == "error" {
+            let error = response.error.unwrap_or_default();
+            // broad queries are rejected instead of truncated
+            if error.starts_with("Too many package results") {
+                return Ok(Vec::new());
+            }
+            eyre::bail!("AUR request failed: {error}");
+        }
+
+        Ok(response.results.into_iter().map(Package::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead as _, BufReader, Write as _};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    const SEARCH: &str = r#"{"resultcount":1,"results":[{"Description":"A foo tool",
+"FirstSubmitted":1600000000,"ID":1,"LastModified":1700000000,"Maintainer":"jane",
+"Name":"foo-git","NumVotes":42,"OutOfDate":null,"PackageBase":"foo","PackageBaseID":1,
+"Popularity":1.5,"URL":"https://foo.example.org","URLPath":"/cgit/aur.git/snapshot/foo.tar.gz",
+"Version":"1.0.r3-1"}],"type":"search","version":5}"#;
+
+    const INFO: &str = r#"{"resultcount":1,"results":[{"Depends":["glibc>=2.38","bar"],
+"Description":"A foo tool","LastModified":1700000000,"License":["MIT"],"Maintainer":null,
+"MakeDepends":["git"],"Name":"foo-git","NumVotes":42,"OutOfDate":1710000000,
+"PackageBase":"foo","Popularity":1.5,"Provides":["foo=1.0"],"Conflicts":["foo"],
+"URL":null,"Version":"1.0.r3-1"}],"type":"multiinfo","version":5}"#;
+
+    /// Answers one connection per response with its status and JSON body, returning the base
+    /// URL to point the client at and the request lines it was sent.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    // skip the headers up to the empty line
+                    let mut header = String::new();
+                    while reader.read_line(&mut header).unwrap() > 2 {
+                        header.clear();
+                    }
+                    write!(
+                        stream,
+                        "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                    .unwrap();
+                    request_line.trim_end().to_owned()
+                })
+                .collect()
+        });
+        (url, server)
+    }
+
+    fn aur(url: String) -> Aur {
+        Aur::new(&AurConfig {
+            url,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn decodes_search_results() {
+        let (url, server) = serve(vec![(200, SEARCH)]);
+
+        let packages = aur(url).search("foo").unwrap();
+        let requests = server.join().unwrap();
+
+        assert!(requests[0].starts_with("GET /rpc/?v=5&type=search&by=name-desc&arg=foo "));
+        let [package] = &packages[..] else {
+            panic!("expected one package");
+        };
+        assert_eq!(package.name, "foo-git");
+        assert_eq!(package.base.as_deref(), Some("foo"));
+        assert_eq!(package.version, "1.0.r3-1");
+        assert_eq!(package.source, SOURCE);
+        let info = package.aur.as_ref().unwrap();
+        assert_eq!(info.votes, 42);
+        assert_eq!(info.maintainer.as_deref(), Some("jane"));
+        assert_eq!(info.out_of_date, None);
+        assert_eq!(info.last_modified, 1_700_000_000);
+    }
+
+    #[test]
+    fn decodes_package_info() {
+        let (url, server) = serve(vec![(200, INFO)]);
+
+        let packages = aur(url).info(&["foo-git", "bar"]).unwrap();
+        let requests = server.join().unwrap();
+
+        assert!(requests[0].contains("type=info&arg%5B%5D=foo-git&arg%5B%5D=bar "));
+        let package = &packages[0];
+        let depstrings = |deps: &[Dep]| deps.iter().map(Dep::to_depstring).collect::<Vec<_>>();
+        assert_eq!(depstrings(&package.depends), ["glibc>=2.38", "bar"]);
+        assert_eq!(depstrings(&package.makedepends), ["git"]);
+        assert_eq!(depstrings(&package.provides), ["foo=1.0"]);
+        assert_eq!(depstrings(&package.conflicts), ["foo"]);
+        assert_eq!(package.licenses, ["MIT"]);
+        assert_eq!(package.url, None);
+        let info = package.aur.as_ref().unwrap();
+        assert_eq!(info.maintainer, None);
+        assert_eq!(info.out_of_date, Some(1_710_000_000));
+    }
+
+    #[test]
+    fn reports_errors_but_not_broad_queries() {
+        let (url, server) = serve(vec![
+            (
+                200,
+                r#"{"error":"Too many package results.","resultcount":0,"results":[],"type":"error","version":5}"#,
+            ),
+            (
+                200,
+                r#"{"error":"Incorrect request type specified.","resultcount":0,"results":[],"type":"error","version":5}"#,
+            ),
+            (503, r#"{"error":"unavailable"}"#),
+        ]);
+        let aur = aur(url);
+
+        assert!(aur.search("li").unwrap().is_empty());
+        let error = aur.info(&["foo"]).err().unwrap();
+        assert!(error
+            .to_string()
+            .contains("Incorrect request type specified."));
+        assert!(aur.search("foo").is_err());
+        // too short to be sent at all
+        assert!(aur.search("f").unwrap().is_empty());
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use color_eyre::eyre;
+
+pub(crate) const CACHE_DIR: &str = "/var/cache/pacman/pkg";
+/// How many versions of each package are kept by default, like `paccache`.
+pub(crate) const KEEP: usize = 3;
+
+/// A package file in the package cache.
+#[derive(Clone)]
+pub(crate) struct CachedPackage {
+    pub(crate) name: String,
+    /// `[epoch:]pkgver-pkgrel`
+    pub(crate) version: String,
+    pub(crate) path: PathBuf,
+    /// The detached signature next to the file.
+    pub(crate) signature: Option<PathBuf>,
+    /// Size in bytes of the file and its signature.
+    pub(crate) size: u64,
+}
+
+/// Splits a `<name>-<pkgver>-<pkgrel>-<arch>.pkg.tar.<ext>` file name into the name, the
+/// version and the architecture.
+pub(crate) fn parse_file_name(file_name: &str) -> Option<(&str, String, &str)> {
+    if file_name.ends_with(".sig") {
+        return None;
+    }
+    let (stem, _) = file_name.split_once(".pkg.tar")?;
+    let mut parts = stem.rsplitn(4, '-');
+    let arch = parts.next()?;
+    let pkgrel = parts.next()?;
+    let pkgver = parts.next()?;
+    let name = parts.next()?;
+
+    Some((name, format!("{pkgver}-{pkgrel}"), arch))
+}
+
+impl CachedPackage {
+    fn parse(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        let (name, version, _) = parse_file_name(file_name)?;
+
+        let mut signature = path.as_os_str().to_owned();
+        signature.push(".sig");
+        let signature = PathBuf::from(signature);
+        let signature_size = fs::metadata(&signature).ok().map(|metadata| metadata.len());
+        let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+
+        Some(Self {
+            name: name.to_owned(),
+            version,
+            path: path.to_owned(),
+            signature: signature_size.is_some().then_some(signature),
+            size: size + signature_size.unwrap_or_default(),
+        })
+    }
+
+    /// The file and its signature.
+    pub(crate) fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        std::iter::once(&self.path).chain(&self.signature)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Cache {
+    pub(crate) packages: Vec<CachedPackage>,
+}
+
+impl Cache {
+    /// Lists the package files in `dirs`, skipping the ones that cannot be read.
+    pub(crate) fn read(dirs: &[PathBuf]) -> Self {
+        let packages = dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|entry| CachedPackage::parse(&entry.path()))
+            .collect();
+
+        Self { packages }
+    }
+
+    pub(crate) fn find(&self, name: &str, version: &str) -> Option<&CachedPackage> {
+        self.packages
+            .iter()
+            .find(|package| package.name == name && package.version == version)
+    }
+
+    /// Groups the files by package, given the installed version of each package.
+    pub(crate) fn groups(&self, installed: &HashMap<String, String>) -> Vec<Group> {
+        let mut groups: HashMap<&str, Vec<CachedPackage>> = HashMap::new();
+        for package in &self.packages {
+            groups
+                .entry(&package.name)
+                .or_default()
+                .push(package.clone());
+        }
+
+        let mut groups = groups
+            .into_iter()
+            .map(|(name, mut files)| {
+                files.sort_by(|a, b| alpm::vercmp(b.version.as_str(), a.version.as_str()));
+                Group {
+                    name: name.to_owned(),
+                    installed: installed.get(name).cloned(),
+                    size: files.iter().map(|file| file.size).sum(),
+                    files,
+                }
+            })
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+}
+
+/// Which files to remove, like `paccache -r`.
+#[derive(Clone, Copy)]
+pub(crate) struct Policy {
+    /// How many of the most recent versions of each package to keep.
+    pub(crate) keep: usize,
+    /// Only remove the files of packages that are not installed.
+    pub(crate) uninstalled_only: bool,
+}
+
+/// The cached files of a package, newest first.
+#[derive(Clone)]
+pub(crate) struct Group {
+    pub(crate) name: String,
+    pub(crate) installed: Option<String>,
+    pub(crate) files: Vec<CachedPackage>,
+    /// Size in bytes of all the files.
+    pub(crate) size: u64,
+}
+
+impl Group {
+    pub(crate) fn is_installed(&self, file: &CachedPackage) -> bool {
+        self.installed.as_ref() == Some(&file.version)
+    }
+
+    /// Whether the file at `index` is older than the `keep` most recent ones.
+    pub(crate) fn is_old(&self, index: usize, keep: usize) -> bool {
+        index >= keep
+    }
+
+    /// The files `policy` removes, never the installed version.
+    pub(crate) fn removable(&self, policy: Policy) -> impl Iterator<Item = &CachedPackage> {
+        let skip = policy.uninstalled_only && self.installed.is_some();
+        self.files
+            .iter()
+            .enumerate()
+            .filter(move |&(index, file)| {
+                !skip && self.is_old(index, policy.keep) && !self.is_installed(file)
+            })
+            .map(|(_, file)| file)
+    }
+}
+
+/// Deletes cache files, they are owned by root.
+pub(crate) fn remove_files(paths: &[PathBuf]) -> eyre::Result<ExitStatus> {
+    let status = Command::new("sudo")
+        .arg("rm")
+        .arg("-f")
+        .arg("--")
+        .args(paths)
+        .status()?;
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_package_file_names() {
+        assert_eq!(
+            parse_file_name("linux-6.9.2.arch1-1-x86_64.pkg.tar.zst"),
+            Some(("linux", String::from("6.9.2.arch1-1"), "x86_64"))
+        );
+        assert_eq!(
+            parse_file_name("python-setuptools-1:69.5.1-1-any.pkg.tar.zst"),
+            Some(("python-setuptools", String::from("1:69.5.1-1"), "any"))
+        );
+        assert_eq!(
+            parse_file_name("lib32-gcc-libs-14.1.1+r58+gfc9fb69ad62-1-x86_64.pkg.tar.xz"),
+            Some((
+                "lib32-gcc-libs",
+                String::from("14.1.1+r58+gfc9fb69ad62-1"),
+                "x86_64"
+            ))
+        );
+    }
+
+    #[test]
+    fn skips_signatures_and_other_files() {
+        assert_eq!(
+            parse_file_name("linux-6.9.2.arch1-1-x86_64.pkg.tar.zst.sig"),
+            None
+        );
+        assert_eq!(parse_file_name("download-a1b2c3.part"), None);
+        assert_eq!(parse_file_name("linux-x86_64.pkg.tar.zst"), None);
+    }
+}
@@ -0,0 +1,373 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use color_eyre::eyre;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::desired::{self, DesiredState, Difference};
+use crate::export::{self, Format};
+use crate::format;
+use crate::hold::Holds;
+use crate::manifest::Manifest;
+use crate::news::{self, ReadItems};
+use crate::pacman::{ConfigPaths, Dep, Package, Pacman, Transaction};
+use crate::pacman_log::Log;
+
+/// Pacman terminal UI, launched when no command is given.
+#[derive(Parser)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+    #[command(flatten)]
+    pub(crate) options: Options,
+}
+
+#[derive(Args)]
+pub(crate) struct Options {
+    /// Print package names only, ignored by formats other than table
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Search the sync databases and the AUR
+    Search { query: String },
+    /// Show the details of a package
+    Info { package: String },
+    /// List the dependencies of a package
+    Deps { package: String },
+    /// List packages installed as dependencies that nothing requires
+    Orphans,
+    /// List installed packages with a newer version available
+    Upgrades,
+    /// Find the installed packages owning a file
+    Owns { path: String },
+    /// Write the explicitly installed packages by repository, as TOML for .toml files
+    ExportManifest {
+        /// Printed as text when not given
+        path: Option<PathBuf>,
+        /// Include the installed versions
+        #[arg(long)]
+        versions: bool,
+    },
+    /// Bring the installed packages and holds in line with the desired state file
+    Reconcile {
+        /// List the differences and fail if there are any, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// The desired state file, the configured one by default
+        #[arg(long)]
+        state: Option<PathBuf>,
+    },
+    /// Commit a transaction, run through sudo when the database lock cannot be taken
+    #[command(hide = true)]
+    Commit {
+        #[command(flatten)]
+        transaction: Transaction,
+        #[command(flatten)]
+        paths: ConfigPaths,
+    },
+}
+
+pub(crate) fn run(command: Command, options: &Options) -> eyre::Result<()> {
+    let config = Config::load()?;
+    let mut pacman = Pacman::new(&config)?;
+
+    match command {
+        Command::Search { query } => {
+            let mut packages = pacman.search_syncdbs(&query)?;
+            if let Some(aur) = pacman.aur() {
+                match aur.search(&query) {
+                    Ok(aur_packages) => packages.extend(pacman.with_local(aur_packages)),
+                    Err(error) => eprintln!("could not search the AUR: {error}"),
+                }
+            }
+            write_packages(
+                &packages,
+                options,
+                &["name", "source", "version", "installed"],
+                |package| {
+                    vec![
+                        package.name.clone(),
+                        package.source.clone(),
+                        package.version.clone(),
+                        package.installed_version.clone().unwrap_or_default(),
+                    ]
+                },
+            )?;
+        }
+        Command::Info { package: name } => {
+            let package = find_package(&pacman, &name)?;
+            match options.format {
+                Format::Table if options.quiet => println!("{}", package.name),
+                Format::Table => print_info(&package),
+                format => export::write_stdout(format, &[package], &export::PACKAGE_HEADER, |p| {
+                    export::package_row(p)
+                })?,
+            }
+        }
+        Command::Deps { package: name } => {
+            let package = find_package(&pacman, &name)?;
+            let deps = package
+                .depends
+                .iter()
+                .map(|dep| DepRecord {
+                    dep,
+                    installed: pacman.is_satisfied(dep),
+                })
+                .collect::<Vec<_>>();
+            if options.quiet && options.format == Format::Table {
+                deps.iter()
+                    .for_each(|record| println!("{}", record.dep.name));
+            } else {
+                export::write_stdout(
+                    options.format,
+                    &deps,
+                    &["dependency", "installed"],
+                    |record| {
+                        let installed = if record.installed { "yes" } else { "no" };
+                        vec![record.dep.to_depstring(), installed.to_owned()]
+                    },
+                )?;
+            }
+        }
+        Command::Orphans => {
+            let packages = pacman.orphans();
+            write_packages(
+                &packages,
+                options,
+                &["name", "version", "installed size"],
+                |package| {
+                    vec![
+                        package.name.clone(),
+                        package.version.clone(),
+                        format::size(package.installed_size),
+                    ]
+                },
+            )?;
+        }
+        Command::Upgrades => {
+            let holds = Holds::load(&config.ignore_file);
+            let mut packages = pacman.upgrades();
+            packages.retain(|package| !holds.contains(&package.name));
+            write_packages(
+                &packages,
+                options,
+                &["name", "source", "installed", "available"],
+                |package| {
+                    vec![
+                        package.name.clone(),
+                        package.source.clone(),
+                        package.installed_version.clone().unwrap_or_default(),
+                        package.version.clone(),
+                    ]
+                },
+            )?;
+            warn_unread_news(&config);
+        }
+        Command::Owns { path } => {
+            let packages = pacman.owners(&path);
+            if packages.is_empty() {
+                eyre::bail!("no package owns {path}");
+            }
+            write_packages(&packages, options, &["name", "version"], |package| {
+                vec![package.name.clone(), package.version.clone()]
+            })?;
+        }
+        Command::ExportManifest { path, versions } => {
+            let manifest = Manifest::from_system(&pacman, versions);
+            match path {
+                Some(path) => manifest.write(&path)?,
+                None => print!("{}", manifest.to_text()),
+            }
+        }
+        Command::Reconcile { dry_run, state } => {
+            let path = state.or_else(|| config.desired_state()).ok_or_else(|| {
+                eyre::eyre!("no desired state file, set desired_state in the config")
+            })?;
+            let desired = DesiredState::read(&path)?;
+            let mut holds = Holds::load(&config.ignore_file);
+            let differences = desired::compare(&desired, &pacman, &holds);
+            if dry_run {
+                write_differences(&differences, options)?;
+                if !differences.is_empty() {
+                    eyre::bail!("{} differences from {}", differences.len(), path.display());
+                }
+                return Ok(());
+            }
+
+            let (fixable, by_hand): (Vec<_>, Vec<_>) = differences
+                .into_iter()
+                .partition(|difference| difference.fix.is_some());
+            if !fixable.is_empty() && !desired::reconcile(&fixable, &mut holds, &config)?.success()
+            {
+                eyre::bail!("failed to reconcile with {}", path.display());
+            }
+            if !by_hand.is_empty() {
+                write_differences(&by_hand, options)?;
+                eyre::bail!("{} differences are left to fix by hand", by_hand.len());
+            }
+        }
+        Command::Commit { transaction, paths } => {
+            let config = paths.apply(&config);
+            Pacman::new(&config)?.commit(&transaction, &config)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks a package up in the sync databases, then the AUR and then the local database for
+/// foreign packages.
+fn find_package(pacman: &Pacman, name: &str) -> eyre::Result<Package> {
+    pacman
+        .sync_package(name)
+        .or_else(|| aur_package(pacman, name))
+        .or_else(|| pacman.local_package(name))
+        .ok_or_else(|| eyre::eyre!("package '{name}' was not found"))
+}
+
+/// Looks a package up in the AUR, warning instead of failing when it cannot be reached.
+fn aur_package(pacman: &Pacman, name: &str) -> Option<Package> {
+    match pacman.aur()?.info(&[name]) {
+        Ok(packages) => pacman.with_local(packages).into_iter().next(),
+        Err(error) => {
+            eprintln!("could not look {name} up in the AUR: {error}");
+            None
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DepRecord<'a> {
+    #[serde(flatten)]
+    dep: &'a Dep,
+    installed: bool,
+}
+
+fn write_packages(
+    packages: &[Package],
+    options: &Options,
+    header: &[&str],
+    row: impl Fn(&Package) -> Vec<String>,
+) -> eyre::Result<()> {
+    if options.quiet && options.format == Format::Table {
+        packages
+            .iter()
+            .for_each(|package| println!("{}", package.name));
+        return Ok(());
+    }
+
+    export::write_stdout(options.format, packages, header, row)
+}
+
+fn write_differences(differences: &[Difference], options: &Options) -> eyre::Result<()> {
+    if options.quiet && options.format == Format::Table {
+        differences
+            .iter()
+            .for_each(|difference| println!("{}", difference.name));
+        return Ok(());
+    }
+
+    export::write_stdout(
+        options.format,
+        differences,
+        &["kind", "name", "difference", "fix"],
+        |difference| {
+            vec![
+                difference.kind.to_string(),
+                difference.name.clone(),
+                difference.detail.clone(),
+                difference.fix_label(),
+            ]
+        },
+    )
+}
+
+fn print_info(package: &Package) {
+    let join = |values: Vec<String>| {
+        if values.is_empty() {
+            String::from("-")
+        } else {
+            values.join("  ")
+        }
+    };
+    let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| String::from("-"));
+
+    let rows = [
+        ("name", package.name.clone()),
+        ("version", package.version.clone()),
+        ("installed", optional(&package.installed_version)),
+        ("description", optional(&package.description)),
+        ("source", package.source.clone()),
+        ("arch", optional(&package.arch)),
+        ("url", optional(&package.url)),
+        ("licenses", join(package.licenses.clone())),
+        ("groups", join(package.groups.clone())),
+        (
+            "depends",
+            join(package.depends.iter().map(|d| d.to_depstring()).collect()),
+        ),
+        (
+            "provides",
+            join(package.provides.iter().map(|d| d.to_depstring()).collect()),
+        ),
+        (
+            "conflicts",
+            join(package.conflicts.iter().map(|d| d.to_depstring()).collect()),
+        ),
+        ("download size", format::size(package.download_size)),
+        ("installed size", format::size(package.installed_size)),
+        ("packager", optional(&package.packager)),
+        ("build date", format::date(package.build_date)),
+        (
+            "install date",
+            package
+                .local
+                .as_deref()
+                .unwrap_or(package)
+                .install_date
+                .map_or_else(|| String::from("-"), format::date),
+        ),
+        (
+            "install reason",
+            package
+                .local
+                .as_deref()
+                .unwrap_or(package)
+                .reason
+                .map_or_else(|| String::from("-"), |reason| reason.to_string()),
+        ),
+    ];
+
+    for (key, value) in rows {
+        println!("{key:<16}: {value}");
+    }
+}
+
+/// Lists the unread news since the last upgrade on stderr, keeping stdout parseable.
+fn warn_unread_news(config: &Config) {
+    if !config.news.enabled {
+        return;
+    }
+    let feed = match news::load(&config.news) {
+        Ok(feed) => feed,
+        Err(error) => {
+            eprintln!("could not check the news: {error}");
+            return;
+        }
+    };
+    let since = Log::read(&config.log_file)
+        .ok()
+        .and_then(|log| log.last_upgrade());
+    let read = ReadItems::load();
+    for item in news::unread_since(&feed.items, &read, since) {
+        eprintln!("unread news: {} {}", item.title, item.link);
+    }
+}
@@ -1,21 +1,103 @@
+pub(crate) mod build_view;
+pub(crate) mod cache_view;
+pub(crate) mod dialog;
+pub(crate) mod file_picker;
+pub(crate) mod history_view;
+pub(crate) mod integrity_view;
+pub(crate) mod keyring_view;
+pub(crate) mod log_view;
+pub(crate) mod manifest_view;
+pub(crate) mod mirrors_view;
+pub(crate) mod news_view;
+pub(crate) mod optdepends_view;
 pub(crate) mod package_info;
 pub(crate) mod package_input;
 pub(crate) mod packages_table;
+pub(crate) mod pacnew_view;
+pub(crate) mod script_viewer;
+pub(crate) mod state_view;
+pub(crate) mod version_picker;
 
 use crate::action::Action;
 use crate::event::Event;
 
 use color_eyre::eyre;
-use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::layout::Rect;
 use ratatui::Frame;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Pane {
+    PackageInput,
+    PackagesTable,
+    PackageInfo,
+    ScriptViewer,
+    Log,
+    History,
+    Cache,
+    Pacnew,
+    Integrity,
+    Optdepends,
+    Keyring,
+    Mirrors,
+    News,
+    State,
+}
+
+impl Pane {
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Pane::PackageInput => "search",
+            Pane::PackagesTable => "packages",
+            Pane::PackageInfo => "info",
+            Pane::ScriptViewer => "scripts",
+            Pane::Log => "log",
+            Pane::History => "history",
+            Pane::Cache => "cache",
+            Pane::Pacnew => "pacnew",
+            Pane::Integrity => "integrity",
+            Pane::Optdepends => "optdepends",
+            Pane::Keyring => "keyring",
+            Pane::Mirrors => "mirrors",
+            Pane::News => "news",
+            Pane::State => "state",
+        }
+    }
+
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Pane::PackageInput => Pane::PackagesTable,
+            Pane::PackagesTable => Pane::PackageInfo,
+            Pane::PackageInfo => Pane::ScriptViewer,
+            Pane::ScriptViewer => Pane::Log,
+            Pane::Log => Pane::History,
+            Pane::History => Pane::Cache,
+            Pane::Cache => Pane::Pacnew,
+            Pane::Pacnew => Pane::Integrity,
+            Pane::Integrity => Pane::Optdepends,
+            Pane::Optdepends => Pane::Keyring,
+            Pane::Keyring => Pane::Mirrors,
+            Pane::Mirrors => Pane::News,
+            Pane::News => Pane::State,
+            Pane::State => Pane::PackageInput,
+        }
+    }
+}
+
 pub(crate) trait Component {
     fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
         let _ = key_event;
         Ok(None)
     }
 
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        let _ = mouse_event;
+        Ok(None)
+    }
+
     fn update(&mut self, event: &Event) -> eyre::Result<()> {
         let _ = event;
         Ok(())
@@ -26,4 +108,14 @@ pub(crate) trait Component {
         let _ = area;
         Ok(())
     }
+
+    /// The pane the component is drawn in, `None` for overlays drawn over the whole frame.
+    fn pane(&self) -> Option<Pane> {
+        None
+    }
+
+    /// Modal components receive all input while they are open.
+    fn is_modal(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,215 @@
+use color_eyre::eyre;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize as _};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::Component;
+use crate::event::Event;
+use crate::highlight;
+use crate::makepkg::{Build, Review};
+use crate::theme::Theme;
+
+#[derive(Default)]
+enum Stage {
+    #[default]
+    Hidden,
+    Running,
+    Reviewing(Build, Review),
+    Finished,
+}
+
+/// Progress and output of AUR builds, and the review before each of them.
+#[derive(Default)]
+pub(crate) struct BuildView {
+    stage: Stage,
+    title: String,
+    log: Vec<String>,
+    review: Vec<Line<'static>>,
+    /// `None` follows the end of the log.
+    scroll: Option<usize>,
+    theme: Theme,
+}
+
+impl BuildView {
+    fn review_lines(build: &Build, review: &Review) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        match &review.diff {
+            None => lines.push(Line::from(format!("{} was never built before", build.base)).bold()),
+            Some(diff) if diff.is_empty() => {
+                lines.push(Line::from("no changes since the last build").bold());
+            }
+            Some(diff) => {
+                lines.push(Line::from("changes since the last build").bold());
+                lines.extend(diff.lines().map(highlight::diff));
+            }
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("PKGBUILD").bold());
+        lines.extend(
+            review
+                .pkgbuild
+                .lines()
+                .map(|line| Line::from(highlight::shell(line))),
+        );
+        lines
+    }
+
+    fn scroll_down(&mut self) {
+        if let Some(scroll) = &mut self.scroll {
+            *scroll = scroll.saturating_add(1);
+        }
+    }
+
+    fn scroll_up(&mut self) {
+        self.scroll = Some(self.scroll.unwrap_or(usize::MAX).saturating_sub(1));
+    }
+}
+
+impl Component for BuildView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        let mut actions = Vec::new();
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
+            KeyCode::Char('g') | KeyCode::Home => self.scroll = Some(0),
+            KeyCode::Char('G') | KeyCode::End => self.scroll = None,
+            code => match &self.stage {
+                Stage::Reviewing(build, review) => match code {
+                    KeyCode::Char('y') => {
+                        actions.push(Action::BuildReviewed(
+                            build.clone(),
+                            review.revision.clone(),
+                        ));
+                        self.stage = Stage::Running;
+                        self.scroll = None;
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => {
+                        actions.push(Action::BuildCancelled);
+                        self.stage = Stage::Finished;
+                        self.scroll = None;
+                    }
+                    _ => {}
+                },
+                // the build keeps going in the background
+                Stage::Running | Stage::Finished => {
+                    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+                        self.stage = Stage::Hidden;
+                    }
+                }
+                Stage::Hidden => {}
+            },
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => self.scroll_down(),
+            MouseEventKind::ScrollUp => self.scroll_up(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::BuildStarted(title) => {
+                if matches!(self.stage, Stage::Hidden | Stage::Finished) {
+                    self.log.clear();
+                }
+                self.stage = Stage::Running;
+                self.title = title.clone();
+                self.scroll = None;
+            }
+            Event::BuildLogged(line) => {
+                self.log.push(line.clone());
+            }
+            Event::BuildReviewRequested(build, review) => {
+                self.review = Self::review_lines(build, review);
+                self.stage = Stage::Reviewing(build.clone(), review.clone());
+                self.scroll = Some(0);
+            }
+            Event::BuildFinished(message) => {
+                self.log.push(String::new());
+                self.log.push(message.clone());
+                self.stage = Stage::Finished;
+                self.scroll = None;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let (lines, help) = match self.stage {
+            Stage::Hidden => return Ok(()),
+            Stage::Reviewing(..) => (self.review.clone(), " [y] build  [n] cancel  [j/k] scroll "),
+            Stage::Running => (
+                self.log
+                    .iter()
+                    .map(|line| Line::from(line.clone()))
+                    .collect(),
+                " [j/k] scroll  [Esc] hide ",
+            ),
+            Stage::Finished => (
+                self.log
+                    .iter()
+                    .map(|line| Line::from(line.clone()))
+                    .collect(),
+                " [Esc] close ",
+            ),
+        };
+
+        let [_, area, _] = Layout::vertical([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .areas(*area);
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .areas(area);
+
+        let block = Block::bordered()
+            .title(self.title.clone())
+            .title_bottom(help)
+            .border_style(Style::default().fg(self.theme.active));
+        let height = usize::from(block.inner(area).height);
+        let max_scroll = lines.len().saturating_sub(height);
+        let scroll = self
+            .scroll
+            .map_or(max_scroll, |scroll| scroll.min(max_scroll));
+        if self.scroll.is_some() {
+            self.scroll = Some(scroll);
+        }
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((scroll as u16, 0)),
+            area,
+        );
+        Ok(())
+    }
+
+    fn is_modal(&self) -> bool {
+        !matches!(self.stage, Stage::Hidden)
+    }
+}
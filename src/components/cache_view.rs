@@ -0,0 +1,437 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::cache::{Group, Policy};
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::theme::Theme;
+
+/// Cached package files grouped by package, and deleting them.
+pub(crate) struct CacheView {
+    groups: Vec<Group>,
+    /// Package files marked for deletion.
+    marked: HashSet<PathBuf>,
+    policy: Policy,
+    state: TableState,
+    files_state: TableState,
+    /// Whether keys move through the files of the selected package.
+    in_files: bool,
+    /// Lists the marked files instead of the files of the selected package.
+    preview: bool,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl CacheView {
+    pub(crate) fn new(keep: usize) -> Self {
+        Self {
+            groups: Vec::new(),
+            marked: HashSet::new(),
+            policy: Policy {
+                keep,
+                uninstalled_only: false,
+            },
+            state: TableState::default(),
+            files_state: TableState::default(),
+            in_files: false,
+            preview: false,
+            theme: Theme::default(),
+            active: false,
+            area: Rect::default(),
+        }
+    }
+
+    fn selected_group(&self) -> Option<&Group> {
+        self.groups.get(self.state.selected()?)
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.in_files {
+            let len = self.selected_group().map_or(0, |group| group.files.len());
+            if len > 0 {
+                self.files_state.select(Some(index.min(len - 1)));
+            }
+        } else if !self.groups.is_empty() {
+            self.state.select(Some(index.min(self.groups.len() - 1)));
+            self.files_state = TableState::default().with_selected(Some(0));
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        if self.in_files {
+            self.files_state.selected()
+        } else {
+            self.state.selected()
+        }
+    }
+
+    /// Marks or unmarks the selected file, or all the files of the selected package.
+    fn toggle(&mut self) {
+        let Some(group) = self.selected_group() else {
+            return;
+        };
+        let paths = if self.in_files {
+            let file = self
+                .files_state
+                .selected()
+                .and_then(|index| group.files.get(index));
+            file.map(|file| vec![file.path.clone()]).unwrap_or_default()
+        } else {
+            group.files.iter().map(|file| file.path.clone()).collect()
+        };
+
+        if paths.iter().all(|path| self.marked.contains(path)) {
+            for path in &paths {
+                self.marked.remove(path);
+            }
+        } else {
+            self.marked.extend(paths);
+        }
+    }
+
+    /// Marks the files the policy removes, replacing the marks.
+    fn apply_policy(&mut self) {
+        self.marked = self
+            .groups
+            .iter()
+            .flat_map(|group| group.removable(self.policy))
+            .map(|file| file.path.clone())
+            .collect();
+        self.preview = true;
+    }
+
+    /// The marked files and the size they free.
+    fn marked_files(&self) -> (Vec<PathBuf>, u64) {
+        let files = self
+            .groups
+            .iter()
+            .flat_map(|group| &group.files)
+            .filter(|file| self.marked.contains(&file.path));
+
+        let mut paths = Vec::new();
+        let mut size = 0;
+        for file in files {
+            paths.extend(file.paths().cloned());
+            size += file.size;
+        }
+        (paths, size)
+    }
+
+    fn delete_action(&self) -> Option<Action> {
+        let (paths, size) = self.marked_files();
+        if paths.is_empty() {
+            return None;
+        }
+
+        Some(Action::Confirm(
+            format!(
+                "delete {} package files and their signatures, freeing {}?",
+                self.marked.len(),
+                format::size(size)
+            ),
+            Box::new(Action::DeleteCacheFiles(paths)),
+        ))
+    }
+
+    fn flags(&self, group: &Group, index: usize) -> Line<'static> {
+        let file = &group.files[index];
+        if group.is_installed(file) {
+            Line::from("installed").fg(Color::Green)
+        } else if group.installed.is_none() {
+            Line::from("uninstalled").fg(Color::Yellow)
+        } else if group.is_old(index, self.policy.keep) {
+            Line::from("old").fg(Color::Yellow)
+        } else {
+            Line::default()
+        }
+    }
+
+    fn draw_groups(&mut self, frame: &mut Frame, area: Rect, border_color: Color) {
+        let total = self.groups.iter().map(|group| group.size).sum();
+        let rows = self.groups.iter().map(|group| {
+            let old = (0..group.files.len())
+                .filter(|&index| group.is_old(index, self.policy.keep))
+                .count();
+            let flags = if group.installed.is_none() {
+                Line::from("uninstalled").fg(Color::Yellow)
+            } else if old > 0 {
+                Line::from(format!("{old} old")).fg(Color::Yellow)
+            } else {
+                Line::default()
+            };
+            let marked = group
+                .files
+                .iter()
+                .filter(|file| self.marked.contains(&file.path))
+                .count();
+            Row::new(vec![
+                Cell::from(if marked > 0 { "*" } else { "" }),
+                Cell::from(group.name.clone()),
+                Cell::from(group.files.len().to_string()),
+                Cell::from(format::size(group.size)),
+                Cell::from(flags),
+            ])
+        });
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(11),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["", "package", "versions", "size", ""])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!(
+                        "cache: {} packages, {}",
+                        self.groups.len(),
+                        format::size(total)
+                    ))
+                    .title_bottom(
+                        " [Space] mark  [a] apply policy  [+/-] keep  [u] uninstalled only  \
+                         [c] clear  [x] preview ",
+                    )
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(if self.in_files {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().add_modifier(Modifier::REVERSED)
+            });
+        frame.render_stateful_widget(table, area, &mut self.state);
+    }
+
+    fn draw_files(&mut self, frame: &mut Frame, area: Rect, border_color: Color) {
+        let Some(group) = self.selected_group() else {
+            frame.render_widget(
+                Block::bordered().border_style(Style::default().fg(border_color)),
+                area,
+            );
+            return;
+        };
+
+        let rows = group
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let marked = self.marked.contains(&file.path);
+                Row::new(vec![
+                    Cell::from(if marked { "*" } else { "" }),
+                    Cell::from(file.version.clone()),
+                    Cell::from(format::size(file.size)),
+                    Cell::from(self.flags(group, index)),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(11),
+            Constraint::Length(11),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["", "version", "size", ""]).style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!("files of {}", group.name))
+                    .title_bottom(" [h/l] packages/files ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(if self.in_files {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            });
+        frame.render_stateful_widget(table, area, &mut self.files_state);
+    }
+
+    /// What deleting the marked files would do, without doing it.
+    fn draw_preview(&self, frame: &mut Frame, area: Rect, border_color: Color) {
+        let (_, size) = self.marked_files();
+        let mut lines = vec![
+            Line::from(format!(
+                "{} files would be deleted, freeing {}",
+                self.marked.len(),
+                format::size(size)
+            ))
+            .bold(),
+            Line::from(format!(
+                "policy: keep {} versions{}",
+                self.policy.keep,
+                if self.policy.uninstalled_only {
+                    " of uninstalled packages"
+                } else {
+                    ""
+                }
+            )),
+            Line::default(),
+        ];
+        for group in &self.groups {
+            for file in &group.files {
+                if self.marked.contains(&file.path) {
+                    lines.push(Line::from(format!(
+                        "{} {} ({})",
+                        group.name,
+                        file.version,
+                        format::size(file.size)
+                    )));
+                }
+            }
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title("dry run")
+                    .title_bottom(" [Enter] delete  [x] back ")
+                    .border_style(Style::default().fg(border_color)),
+            ),
+            area,
+        );
+    }
+}
+
+impl Component for CacheView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        // control keys belong to the app
+        if !matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        ) {
+            return Ok(Some(actions));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.selected_index().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.selected_index().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char('h') | KeyCode::Left => self.in_files = false,
+            KeyCode::Char('l') | KeyCode::Right => {
+                self.in_files = true;
+                self.preview = false;
+            }
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('a') => self.apply_policy(),
+            KeyCode::Char('+') => self.policy.keep += 1,
+            KeyCode::Char('-') => self.policy.keep = self.policy.keep.saturating_sub(1),
+            KeyCode::Char('u') => self.policy.uninstalled_only = !self.policy.uninstalled_only,
+            KeyCode::Char('c') => self.marked.clear(),
+            KeyCode::Char('x') => self.preview = !self.preview,
+            KeyCode::Enter if self.preview => actions.extend(self.delete_action()),
+            KeyCode::Char('r') => actions.push(Action::LoadCache),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                self.in_files = mouse_event.column >= self.area.right();
+                let offset = if self.in_files {
+                    self.files_state.offset()
+                } else {
+                    self.state.offset()
+                };
+                if mouse_event.row >= first_row {
+                    self.select(offset + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.select(self.selected_index().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.selected_index().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::CacheGrouped(groups) => {
+                self.groups = groups.clone();
+                let paths = self
+                    .groups
+                    .iter()
+                    .flat_map(|group| &group.files)
+                    .map(|file| &file.path)
+                    .collect::<HashSet<_>>();
+                self.marked.retain(|path| paths.contains(path));
+                let selected = self.state.selected().unwrap_or_default();
+                let in_files = std::mem::replace(&mut self.in_files, false);
+                self.select(selected);
+                self.in_files = in_files;
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Cache;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [groups_area, files_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(*area);
+        self.area = groups_area;
+
+        self.draw_groups(frame, groups_area, border_color);
+        if self.preview {
+            self.draw_preview(frame, files_area, border_color);
+        } else {
+            self.draw_files(frame, files_area, border_color);
+        }
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Cache)
+    }
+}
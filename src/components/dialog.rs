@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::Component;
+use crate::event::Event;
+use crate::theme::Theme;
+
+enum Request {
+    Confirm(String, Action),
+    Prompt(String, String, Action),
+    Notice(String),
+}
+
+#[derive(Default)]
+pub(crate) struct Dialog {
+    /// The shown request first, the ones that arrived while it was open after it.
+    requests: VecDeque<Request>,
+    theme: Theme,
+    confirm_button: Rect,
+    cancel_button: Rect,
+}
+
+impl Dialog {
+    fn confirm(&mut self) -> Option<Vec<Action>> {
+        match self.requests.pop_front()? {
+            Request::Confirm(_, action) => Some(vec![action]),
+            Request::Prompt(_, input, action) => Some(vec![action.with_input(input)]),
+            Request::Notice(_) => None,
+        }
+    }
+
+    fn cancel(&mut self) -> Option<Vec<Action>> {
+        self.requests.pop_front();
+        None
+    }
+}
+
+impl Component for Dialog {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        let actions = match self.requests.front_mut() {
+            Some(Request::Prompt(_, input, _)) => match *key_event {
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                } => self.confirm(),
+                KeyEvent {
+                    code: KeyCode::Esc, ..
+                } => self.cancel(),
+                KeyEvent {
+                    code: KeyCode::Backspace,
+                    ..
+                } => {
+                    input.pop();
+                    None
+                }
+                KeyEvent {
+                    modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                    code: KeyCode::Char(char),
+                    ..
+                } => {
+                    input.push(char);
+                    None
+                }
+                _ => None,
+            },
+            Some(Request::Notice(_)) => match key_event.code {
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => self.cancel(),
+                _ => None,
+            },
+            _ => match key_event.code {
+                KeyCode::Char('y') | KeyCode::Enter => self.confirm(),
+                KeyCode::Char('n') | KeyCode::Esc => self.cancel(),
+                _ => None,
+            },
+        };
+
+        Ok(actions)
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return Ok(None);
+        }
+
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        let actions = if self.confirm_button.contains(position) {
+            self.confirm()
+        } else if self.cancel_button.contains(position) {
+            self.cancel()
+        } else {
+            None
+        };
+
+        Ok(actions)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::ConfirmationRequested(message, action) => {
+                self.requests
+                    .push_back(Request::Confirm(message.clone(), action.clone()));
+            }
+            Event::InputRequested(message, input, action) => {
+                self.requests.push_back(Request::Prompt(
+                    message.clone(),
+                    input.clone(),
+                    action.clone(),
+                ));
+            }
+            Event::Notified(message) => {
+                self.requests.push_back(Request::Notice(message.clone()));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let Some(request) = self.requests.front() else {
+            return Ok(());
+        };
+
+        let (title, text, confirm_label, cancel_label) = match request {
+            Request::Confirm(message, _) => (
+                "confirm",
+                message.lines().map(Line::from).collect(),
+                " [Y]es ",
+                " [N]o ",
+            ),
+            Request::Prompt(message, input, _) => (
+                "input",
+                vec![
+                    Line::from(message.as_str()),
+                    Line::from(format!("> {input}")),
+                ],
+                " OK ",
+                " Cancel ",
+            ),
+            Request::Notice(message) => (
+                "notice",
+                message.lines().map(Line::from).collect(),
+                " OK ",
+                "",
+            ),
+        };
+
+        let [_, area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(text.len() as u16 + 4),
+            Constraint::Fill(1),
+        ])
+        .areas(*area);
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Percentage(50),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        let block = Block::bordered()
+            .title(title)
+            .border_style(Style::default().fg(self.theme.active));
+        let inner = block.inner(area);
+        let [message_area, buttons_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        [_, self.confirm_button, _, self.cancel_button, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(confirm_label.len() as u16),
+            Constraint::Length(2),
+            Constraint::Length(cancel_label.len() as u16),
+            Constraint::Fill(1),
+        ])
+        .areas(buttons_area);
+
+        let button_style = Style::default().add_modifier(Modifier::REVERSED);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), message_area);
+        frame.render_widget(
+            Paragraph::new(confirm_label).style(button_style),
+            self.confirm_button,
+        );
+        frame.render_widget(
+            Paragraph::new(cancel_label).style(button_style),
+            self.cancel_button,
+        );
+        Ok(())
+    }
+
+    fn is_modal(&self) -> bool {
+        !self.requests.is_empty()
+    }
+}
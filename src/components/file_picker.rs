@@ -0,0 +1,271 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Clear, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::Component;
+use crate::event::Event;
+use crate::format;
+use crate::package_file::{Change, Entry};
+use crate::pacman::Package;
+use crate::theme::Theme;
+
+/// Browses directories for package files and installs them.
+#[derive(Default)]
+pub(crate) struct FilePicker {
+    /// The directory shown, `None` while the picker is closed.
+    dir: Option<PathBuf>,
+    entries: Vec<Entry>,
+    /// The package file read last.
+    loaded: Option<(PathBuf, Package)>,
+    state: TableState,
+    theme: Theme,
+}
+
+impl FilePicker {
+    fn select(&mut self, index: usize) {
+        if !self.entries.is_empty() {
+            self.state.select(Some(index.min(self.entries.len() - 1)));
+        }
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.state.selected()?)
+    }
+
+    /// The loaded package if it is the selected file.
+    fn selected_package(&self) -> Option<&Package> {
+        let entry = self.selected()?;
+        let (path, package) = self.loaded.as_ref()?;
+        (*path == entry.path).then_some(package)
+    }
+
+    fn open_action(&self) -> Option<Action> {
+        let entry = self.selected()?;
+        if entry.is_dir {
+            return Some(Action::BrowsePackageFiles(Some(entry.path.clone())));
+        }
+        if self.selected_package().is_some() {
+            return self.install_action();
+        }
+        Some(Action::LoadPackageFile(entry.path.clone()))
+    }
+
+    fn install_action(&self) -> Option<Action> {
+        let package = self.selected_package()?;
+        let path = self.selected()?.path.clone();
+        Some(Action::Confirm(
+            format!(
+                "install {} {} from {}?\n{}",
+                package.name,
+                package.version,
+                path.display(),
+                Change::new(package)
+            ),
+            Box::new(Action::InstallPackageFile(path, package.name.clone())),
+        ))
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(entry) = self.selected() else {
+            return vec![Line::from("no package files here")];
+        };
+        if entry.is_dir {
+            return vec![Line::from("[Enter] open the directory")];
+        }
+        let Some(package) = self.selected_package() else {
+            return vec![
+                Line::from(entry.name.clone()),
+                Line::from(format::size(entry.size)),
+                Line::default(),
+                Line::from("[Enter] read the package"),
+            ];
+        };
+
+        let change = Change::new(package);
+        let change_color = match change {
+            Change::Install | Change::Upgrade(_) => Color::Green,
+            Change::Downgrade(_) => Color::Yellow,
+            Change::Reinstall => Color::Reset,
+        };
+        let mut lines = vec![
+            Line::from(format!("{} {}", package.name, package.version)).bold(),
+            Line::from(change.to_string()).fg(change_color),
+            Line::default(),
+        ];
+        let fields = [
+            ("description", package.description.clone()),
+            ("arch", package.arch.clone()),
+            ("packager", package.packager.clone()),
+            ("build date", Some(format::date(package.build_date))),
+            ("installed size", Some(format::size(package.installed_size))),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                lines.push(Line::from(format!("{key}: {value}")));
+            }
+        }
+        if !package.depends.is_empty() {
+            let depends = package
+                .depends
+                .iter()
+                .map(|dep| dep.to_depstring())
+                .collect::<Vec<_>>();
+            lines.push(Line::from(format!("depends: {}", depends.join(", "))));
+        }
+        lines
+    }
+}
+
+impl Component for FilePicker {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        let mut actions = Vec::new();
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+                if let Some(action) = self.open_action() {
+                    if matches!(action, Action::Confirm(..)) {
+                        self.dir = None;
+                    }
+                    actions.push(action);
+                }
+            }
+            KeyCode::Backspace | KeyCode::Char('h') | KeyCode::Left => {
+                let parent = self.dir.as_ref().and_then(|dir| dir.parent());
+                if let Some(parent) = parent {
+                    actions.push(Action::BrowsePackageFiles(Some(parent.to_owned())));
+                }
+            }
+            KeyCode::Char('u') => {
+                self.dir = None;
+                actions.push(Action::Prompt(
+                    String::from("install a package from a URL"),
+                    String::from("https://"),
+                    Box::new(Action::InstallPackageUrl(String::new())),
+                ));
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.dir = None,
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::PackageFilesListed(dir, entries) => {
+                // going up keeps the directory that was left selected
+                let previous = self.dir.replace(dir.clone());
+                self.entries = entries.clone();
+                let index = previous
+                    .and_then(|previous| entries.iter().position(|entry| entry.path == previous));
+                self.state = TableState::default();
+                self.select(index.unwrap_or_default());
+            }
+            Event::PackageFileLoaded(path, package) => {
+                self.loaded = Some((path.clone(), package.clone()));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        let [_, area, _] = Layout::vertical([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .areas(*area);
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .areas(area);
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area);
+
+        let rows = self.entries.iter().map(|entry| {
+            if entry.is_dir {
+                Row::new(vec![
+                    Cell::from(format!("{}/", entry.name)).fg(Color::Blue),
+                    Cell::from(""),
+                ])
+            } else {
+                Row::new(vec![
+                    Cell::from(entry.name.clone()),
+                    Cell::from(format::size(entry.size)),
+                ])
+            }
+        });
+        let widths = [Constraint::Fill(1), Constraint::Length(11)];
+        let table = Table::new(rows, widths)
+            .block(
+                Block::bordered()
+                    .title(dir.display().to_string())
+                    .title_bottom(" [Enter] open  [h] up  [u] URL  [Esc] close ")
+                    .border_style(Style::default().fg(self.theme.active)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let details = Paragraph::new(self.details())
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::bordered()
+                    .title("package")
+                    .title_bottom(if self.selected_package().is_some() {
+                        " [Enter] install "
+                    } else {
+                        ""
+                    })
+                    .border_style(Style::default().fg(self.theme.active)),
+            );
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+        frame.render_widget(details, details_area);
+        Ok(())
+    }
+
+    fn is_modal(&self) -> bool {
+        self.dir.is_some()
+    }
+}
@@ -0,0 +1,311 @@
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::cache::Cache;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::history::{Revert, Step};
+use crate::pacman_log::{Log, Operation};
+use crate::theme::Theme;
+
+/// Transactions from the pacman log and how to revert them.
+#[derive(Default)]
+pub(crate) struct HistoryView {
+    log: Log,
+    cache: Cache,
+    /// How to revert the selected transaction.
+    revert: Option<Revert>,
+    state: TableState,
+    scroll: usize,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl HistoryView {
+    /// Transactions are listed newest first.
+    fn selected_transaction(&self) -> Option<usize> {
+        let selected = self.state.selected()?;
+        self.log.transactions.len().checked_sub(selected + 1)
+    }
+
+    fn select(&mut self, index: usize) {
+        if !self.log.transactions.is_empty() {
+            self.state
+                .select(Some(index.min(self.log.transactions.len() - 1)));
+            self.scroll = 0;
+        }
+        self.revert = self
+            .selected_transaction()
+            .map(|transaction| Revert::new(&self.log, transaction, &self.cache));
+    }
+
+    /// Asks to revert the selected transaction. When some previous versions are not cached,
+    /// only reverting `partially` is offered.
+    fn revert_action(&self, partially: bool) -> Option<Action> {
+        let revert = self.revert.clone()?;
+        if revert.steps.is_empty() {
+            return None;
+        }
+
+        let missing = revert.missing();
+        if missing > 0 && !partially {
+            return Some(Action::Notify(format!(
+                "{missing} packages cannot be reverted, their previous versions are not cached\npress [U] to revert the others and leave these as they are"
+            )));
+        }
+        let mut message = format!(
+            "revert transaction {}: install {} and remove {} packages?",
+            revert.transaction + 1,
+            revert.files().len(),
+            revert.removals().len()
+        );
+        let reinstalls = revert.reinstalls();
+        if reinstalls > 0 {
+            message.push_str(&format!(
+                "\n{reinstalls} removed packages come back as explicitly installed"
+            ));
+        }
+        if missing > 0 {
+            message.push_str(&format!(
+                "\nthis reverts it partially, {missing} packages stay as they are"
+            ));
+        }
+        Some(Action::Confirm(
+            message,
+            Box::new(Action::RevertTransaction(revert, partially)),
+        ))
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let (Some(transaction), Some(revert)) = (self.selected_transaction(), &self.revert) else {
+            return Vec::new();
+        };
+        let info = &self.log.transactions[transaction];
+
+        let mut lines = vec![
+            Line::from(format!(
+                "transaction {} at {}",
+                transaction + 1,
+                format::date(info.started)
+            ))
+            .bold(),
+            Line::from(info.command.clone().unwrap_or_default()),
+        ];
+        if !info.completed {
+            lines.push(Line::from("not completed").fg(Color::Red));
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("changes").bold().fg(Color::Magenta));
+        for &i in &info.entries {
+            let operation = &self.log.entries[i].operation;
+            if let Some(package) = operation.package() {
+                lines.push(Line::from(format!(
+                    "{} {package} {}",
+                    operation.kind(),
+                    operation.details()
+                )));
+            } else if matches!(operation, Operation::Warning(_) | Operation::Error(_)) {
+                lines.push(Line::from(operation.details()).fg(Color::LightRed));
+            }
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("revert").bold().fg(Color::Magenta));
+        if revert.steps.is_empty() {
+            lines.push(Line::from("nothing to revert"));
+        }
+        for (package, step) in &revert.steps {
+            let line = match step {
+                Step::Install(version, _) => {
+                    Line::from(format!("install {package} {version} from the cache"))
+                        .fg(Color::Green)
+                }
+                Step::Reinstall(version, _) => Line::from(format!(
+                    "reinstall {package} {version} from the cache, as explicitly installed"
+                ))
+                .fg(Color::Green),
+                Step::Remove => Line::from(format!("remove {package}")).fg(Color::Yellow),
+                Step::Missing(version) => Line::from(vec![
+                    Span::raw(format!("cannot reinstall {package} {version}")),
+                    Span::raw(", not in the cache").add_modifier(Modifier::ITALIC),
+                ])
+                .fg(Color::Red),
+            };
+            lines.push(line);
+        }
+
+        lines
+    }
+}
+
+impl Component for HistoryView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.select(self.state.selected().map_or(0, |i| i + 1));
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+                }
+                KeyCode::Char('g') | KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(usize::MAX),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Char('u') => actions.extend(self.revert_action(false)),
+                KeyCode::Char('r') => {
+                    actions.push(Action::LoadLog);
+                    actions.push(Action::LoadCache);
+                }
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code: KeyCode::Char('G'),
+                ..
+            } => self.select(usize::MAX),
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code: KeyCode::Char('U'),
+                ..
+            } => actions.extend(self.revert_action(true)),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if mouse_event.column < self.area.right() && mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::LogLoaded(log) => {
+                self.log = log.clone();
+                self.state = TableState::default();
+                self.select(0);
+            }
+            Event::CacheLoaded(cache) => {
+                self.cache = cache.clone();
+                self.select(self.state.selected().unwrap_or_default());
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::History;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(*area);
+        self.area = list_area;
+
+        let rows = self
+            .log
+            .transactions
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, transaction)| {
+                let changes = transaction
+                    .entries
+                    .iter()
+                    .filter(|&&entry| self.log.entries[entry].operation.package().is_some())
+                    .count();
+                Row::new(vec![
+                    (i + 1).to_string(),
+                    format::date(transaction.started),
+                    changes.to_string(),
+                    transaction.command.clone().unwrap_or_default(),
+                ])
+            });
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Length(16),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["#", "time", "changes", "command"])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title("transactions")
+                    .title_bottom(" [u] revert  [U] revert partially  [r] reload  [PgUp/PgDn] scroll details ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+
+        let block = Block::bordered().border_style(Style::default().fg(border_color));
+        let lines = self.details();
+        let max_scroll = lines
+            .len()
+            .saturating_sub(usize::from(block.inner(details_area).height));
+        self.scroll = self.scroll.min(max_scroll);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            details_area,
+        );
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::History)
+    }
+}
@@ -0,0 +1,280 @@
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::integrity::Report;
+use crate::theme::Theme;
+
+/// Progress and results of verifying installed files, like `pacman -Qkk`.
+#[derive(Default)]
+pub(crate) struct IntegrityView {
+    /// Packages with changed or missing files.
+    reports: Vec<Report>,
+    total: usize,
+    done: usize,
+    files: usize,
+    /// Files only root can read.
+    unchecked: usize,
+    running: bool,
+    /// The package checked last.
+    current: String,
+    state: TableState,
+    scroll: usize,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl IntegrityView {
+    fn select(&mut self, index: usize) {
+        if !self.reports.is_empty() {
+            self.state.select(Some(index.min(self.reports.len() - 1)));
+            self.scroll = 0;
+        }
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(report) = self.state.selected().and_then(|i| self.reports.get(i)) else {
+            return Vec::new();
+        };
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{}: {} of {} files changed",
+                report.package,
+                report.files.len(),
+                report.checked
+            ))
+            .bold(),
+            Line::default(),
+        ];
+        for file in &report.files {
+            let color = if file.is_missing() {
+                Color::Red
+            } else {
+                Color::Yellow
+            };
+            let problems = file
+                .problems
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(Line::from(vec![
+                Span::raw(file.path.display().to_string()).fg(color),
+                Span::raw(format!("  {problems}")).add_modifier(Modifier::DIM),
+            ]));
+        }
+        lines
+    }
+}
+
+impl Component for IntegrityView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.select(self.state.selected().map_or(0, |i| i + 1));
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+                }
+                KeyCode::Char('g') | KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(usize::MAX),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::Char('a') => actions.push(Action::CheckIntegrity(None)),
+                KeyCode::Char('x') if self.running => actions.push(Action::CancelIntegrityCheck),
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code: KeyCode::Char('G'),
+                ..
+            } => self.select(usize::MAX),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        let in_list = self
+            .area
+            .contains((mouse_event.column, mouse_event.row).into());
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if in_list && mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_add(1),
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::IntegrityCheckStarted(total) => {
+                self.reports.clear();
+                self.total = *total;
+                self.done = 0;
+                self.files = 0;
+                self.unchecked = 0;
+                self.running = *total > 0;
+                self.current.clear();
+                self.state = TableState::default();
+            }
+            Event::IntegrityChecked(report) => {
+                self.done += 1;
+                self.files += report.checked;
+                self.unchecked += report.unchecked;
+                self.running = self.done < self.total;
+                self.current = report.package.clone();
+                if !report.files.is_empty() {
+                    self.reports.push(report.clone());
+                    if self.state.selected().is_none() {
+                        self.select(0);
+                    }
+                }
+            }
+            Event::IntegrityCheckCancelled => {
+                self.running = false;
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Integrity;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [progress_area, area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(*area);
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(area);
+        self.area = list_area;
+
+        let ratio = if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 / self.total as f64
+        };
+        let label = if self.running {
+            format!("{}/{} {}", self.done, self.total, self.current)
+        } else if self.total == 0 {
+            String::from("press [a] to check all installed packages")
+        } else {
+            let mut label = format!(
+                "checked {} files of {} packages, {} with changes",
+                self.files,
+                self.done,
+                self.reports.len()
+            );
+            if self.unchecked > 0 {
+                label.push_str(&format!(", {} not checked, run as root", self.unchecked));
+            }
+            label
+        };
+        frame.render_widget(
+            Gauge::default()
+                .block(
+                    Block::bordered()
+                        .title("integrity")
+                        .title_bottom(if self.running {
+                            " [x] cancel "
+                        } else {
+                            " [a] check all "
+                        })
+                        .border_style(Style::default().fg(border_color)),
+                )
+                .gauge_style(Style::default().fg(Color::Green))
+                .ratio(ratio)
+                .label(label),
+            progress_area,
+        );
+
+        let rows = self.reports.iter().map(|report| {
+            let missing = report.files.iter().filter(|file| file.is_missing()).count();
+            Row::new(vec![
+                report.package.clone(),
+                (report.files.len() - missing).to_string(),
+                missing.to_string(),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(7),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["package", "modified", "missing"])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(Block::bordered().border_style(Style::default().fg(border_color)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+
+        let block = Block::bordered()
+            .title_bottom(" [PgUp/PgDn] scroll ")
+            .border_style(Style::default().fg(border_color));
+        let lines = self.details();
+        let max_scroll = lines
+            .len()
+            .saturating_sub(usize::from(block.inner(details_area).height));
+        self.scroll = self.scroll.min(max_scroll);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            details_area,
+        );
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Integrity)
+    }
+}
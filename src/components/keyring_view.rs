@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::keyring::Key;
+use crate::theme::Theme;
+
+/// The keys in the pacman keyring and the packagers they belong to.
+#[derive(Default)]
+pub(crate) struct KeyringView {
+    keys: Vec<Key>,
+    /// Installed packages by packager.
+    packagers: HashMap<String, usize>,
+    state: TableState,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl KeyringView {
+    fn select(&mut self, index: usize) {
+        if !self.keys.is_empty() {
+            self.state.select(Some(index.min(self.keys.len() - 1)));
+        }
+    }
+
+    fn color(key: &Key) -> Color {
+        if key.is_expired() || !key.validity.is_trusted() {
+            Color::Red
+        } else {
+            Color::Reset
+        }
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(key) = self.state.selected().and_then(|i| self.keys.get(i)) else {
+            return Vec::new();
+        };
+
+        let mut lines = vec![
+            Line::from(key.name().to_owned()).bold(),
+            Line::from(key.fingerprint.clone()),
+            Line::default(),
+        ];
+        let expires = match key.expires {
+            Some(expires) if key.is_expired() => {
+                Span::raw(format!("{} (expired)", format::date(expires))).fg(Color::Red)
+            }
+            Some(expires) => Span::raw(format::date(expires)),
+            None => Span::raw("never"),
+        };
+        let fields = [
+            (
+                "validity",
+                Span::raw(key.validity.to_string()).fg(Self::color(key)),
+            ),
+            ("owner trust", Span::raw(key.owner_trust.to_string())),
+            (
+                "created",
+                Span::raw(key.created.map_or_else(String::new, format::date)),
+            ),
+            ("expires", expires),
+        ];
+        for (name, value) in fields {
+            lines.push(Line::from(vec![
+                Span::raw(format!("{name:12}")).fg(Color::Cyan),
+                value,
+            ]));
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("user ids").bold().fg(Color::Magenta));
+        lines.extend(key.uids.iter().map(|uid| Line::from(uid.clone())));
+
+        lines.push(Line::default());
+        lines.push(Line::from("packagers").bold().fg(Color::Magenta));
+        let packagers = key.packagers(&self.packagers);
+        if packagers.is_empty() {
+            lines.push(
+                Line::from("built none of the installed packages").add_modifier(Modifier::DIM),
+            );
+        }
+        for (packager, count) in packagers {
+            lines.push(Line::from(format!(
+                "{packager}: {count} installed packages"
+            )));
+        }
+        lines
+    }
+}
+
+impl Component for KeyringView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        // control keys belong to the app
+        if !matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        ) {
+            return Ok(Some(actions));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char('r') => actions.push(Action::LoadKeyring),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                let in_list = self
+                    .area
+                    .contains((mouse_event.column, mouse_event.row).into());
+                if in_list && mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::KeyringLoaded(keys, packagers) => {
+                self.keys = keys.clone();
+                self.keys.sort_by_key(|key| key.name().to_lowercase());
+                self.packagers = packagers.clone();
+                self.select(self.state.selected().unwrap_or_default());
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Keyring;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(*area);
+        self.area = list_area;
+
+        let rows = self
+            .keys
+            .iter()
+            .map(|key| {
+                let packages = key
+                    .packagers(&self.packagers)
+                    .iter()
+                    .map(|(_, count)| count)
+                    .sum::<usize>();
+                Row::new(vec![
+                    Cell::from(key.key_id().to_owned()),
+                    Cell::from(key.name().to_owned()),
+                    Cell::from(key.validity.to_string()),
+                    Cell::from(key.owner_trust.to_string()),
+                    Cell::from(
+                        key.expires
+                            .map_or_else(|| String::from("never"), format::date),
+                    ),
+                    Cell::from(packages.to_string()),
+                ])
+                .style(Style::default().fg(Self::color(key)))
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Fill(1),
+            Constraint::Length(9),
+            Constraint::Length(11),
+            Constraint::Length(16),
+            Constraint::Length(8),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new([
+                    "key",
+                    "owner",
+                    "validity",
+                    "owner trust",
+                    "expires",
+                    "packages",
+                ])
+                .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!("{} keys in the pacman keyring", self.keys.len()))
+                    .title_bottom(" [r] reload ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+
+        frame.render_widget(
+            Paragraph::new(self.details())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().border_style(Style::default().fg(border_color))),
+            details_area,
+        );
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Keyring)
+    }
+}
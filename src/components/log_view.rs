@@ -0,0 +1,464 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveDate};
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::widgets::{Block, Cell, Row, Sparkline, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::pacman_log::{Entry, Kind, Log, Operation};
+use crate::theme::Theme;
+
+const KINDS: [Kind; 9] = [
+    Kind::Installed,
+    Kind::Upgraded,
+    Kind::Downgraded,
+    Kind::Reinstalled,
+    Kind::Removed,
+    Kind::Transaction,
+    Kind::Hook,
+    Kind::Warning,
+    Kind::Other,
+];
+const TIMELINE_HEIGHT: u16 = 5;
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    DateTime::from_timestamp(timestamp, 0).map(|time| time.with_timezone(&Local).date_naive())
+}
+
+fn kind_color(kind: Kind) -> Color {
+    match kind {
+        Kind::Installed => Color::Green,
+        Kind::Upgraded => Color::Cyan,
+        Kind::Downgraded => Color::Yellow,
+        Kind::Reinstalled => Color::Blue,
+        Kind::Removed => Color::Red,
+        Kind::Transaction => Color::Magenta,
+        Kind::Hook | Kind::Other => Color::Reset,
+        Kind::Warning => Color::LightRed,
+    }
+}
+
+/// Parses `from..to`, either end may be left out, or a single day.
+fn parse_dates(text: &str) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+    let parse = |date: &str| {
+        let date = date.trim();
+        if date.is_empty() {
+            return Ok(None);
+        }
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("invalid date {date}, expected YYYY-MM-DD"))
+    };
+
+    match text.split_once("..") {
+        Some((from, to)) => Ok((parse(from)?, parse(to)?)),
+        None => {
+            let date = parse(text)?;
+            Ok((date, date))
+        }
+    }
+}
+
+enum Filter {
+    Package,
+    Dates,
+}
+
+/// Entries of the pacman log grouped by transaction.
+#[derive(Default)]
+pub(crate) struct LogView {
+    log: Log,
+    /// Indices of the entries passing the filters.
+    visible: Vec<usize>,
+    state: TableState,
+    package: String,
+    kind: Option<Kind>,
+    dates: (Option<NaiveDate>, Option<NaiveDate>),
+    dates_text: String,
+    input: Option<(Filter, String)>,
+    error: Option<String>,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl LogView {
+    fn matches(&self, entry: &Entry) -> bool {
+        let kind = entry.operation.kind();
+        if self.kind.is_some_and(|filter| filter != kind) {
+            return false;
+        }
+        if !self.package.is_empty()
+            && !entry
+                .operation
+                .package()
+                .is_some_and(|package| package.contains(&self.package))
+        {
+            return false;
+        }
+        let date = local_date(entry.time);
+        if self
+            .dates
+            .0
+            .is_some_and(|from| date.is_some_and(|date| date < from))
+        {
+            return false;
+        }
+        if self
+            .dates
+            .1
+            .is_some_and(|to| date.is_some_and(|date| date > to))
+        {
+            return false;
+        }
+        true
+    }
+
+    fn filter(&mut self) {
+        self.visible = (0..self.log.entries.len())
+            .filter(|&i| self.matches(&self.log.entries[i]))
+            .collect();
+        // newest first
+        self.visible.reverse();
+        self.state.select((!self.visible.is_empty()).then_some(0));
+    }
+
+    fn selected_entry(&self) -> Option<&Entry> {
+        let index = self.visible.get(self.state.selected()?)?;
+        self.log.entries.get(*index)
+    }
+
+    fn select(&mut self, index: usize) {
+        if !self.visible.is_empty() {
+            self.state.select(Some(index.min(self.visible.len() - 1)));
+        }
+    }
+
+    fn next(&mut self) {
+        self.select(self.state.selected().map_or(0, |i| i + 1));
+    }
+
+    fn previous(&mut self) {
+        self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+    }
+
+    /// Moves to the start of the next or previous transaction in the list.
+    fn jump_transaction(&mut self, forward: bool) {
+        let current = self.state.selected().unwrap_or_default();
+        let is_start = |i: &usize| {
+            self.log.entries[self.visible[*i]].operation == Operation::TransactionStarted
+        };
+        let target = if forward {
+            (current + 1..self.visible.len()).find(is_start)
+        } else {
+            (0..current).rev().find(is_start)
+        };
+        if let Some(target) = target {
+            self.state.select(Some(target));
+        }
+    }
+
+    fn cycle_kind(&mut self) {
+        self.kind = match self.kind {
+            None => Some(KINDS[0]),
+            Some(kind) => KINDS
+                .iter()
+                .position(|k| *k == kind)
+                .and_then(|i| KINDS.get(i + 1))
+                .copied(),
+        };
+        self.filter();
+    }
+
+    fn handle_input(&mut self, key_event: &KeyEvent) {
+        let Some((filter, input)) = &mut self.input else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Enter => {
+                match filter {
+                    Filter::Package => self.package = input.clone(),
+                    Filter::Dates => match parse_dates(input) {
+                        Ok(dates) => {
+                            self.dates = dates;
+                            self.dates_text = input.clone();
+                            self.error = None;
+                        }
+                        Err(error) => self.error = Some(error),
+                    },
+                }
+                self.input = None;
+                self.filter();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(char)
+                if matches!(
+                    key_event.modifiers,
+                    KeyModifiers::NONE | KeyModifiers::SHIFT
+                ) =>
+            {
+                input.push(char);
+            }
+            _ => {}
+        }
+    }
+
+    fn status(&self) -> String {
+        if let Some((filter, input)) = &self.input {
+            return match filter {
+                Filter::Package => format!(" package: {input} "),
+                Filter::Dates => format!(" dates (from..to): {input} "),
+            };
+        }
+        if let Some(error) = &self.error {
+            return format!(" {error} ");
+        }
+
+        let mut filters = Vec::new();
+        if !self.package.is_empty() {
+            filters.push(format!("package {}", self.package));
+        }
+        if let Some(kind) = self.kind {
+            filters.push(kind.to_string());
+        }
+        if !self.dates_text.is_empty() {
+            filters.push(self.dates_text.clone());
+        }
+        if filters.is_empty() {
+            String::from(" [/] package  [a] action  [d] dates  [[/]] transaction  [Enter] details ")
+        } else {
+            format!(" {} entries, {} ", self.visible.len(), filters.join(", "))
+        }
+    }
+
+    /// Package operations per day, ending with the newest visible entry.
+    fn timeline(&self, width: usize) -> Vec<u64> {
+        let mut days = BTreeMap::new();
+        for &i in &self.visible {
+            let entry = &self.log.entries[i];
+            if entry.operation.package().is_none() {
+                continue;
+            }
+            if let Some(date) = local_date(entry.time) {
+                *days.entry(date).or_insert(0) += 1;
+            }
+        }
+        let Some((&last, _)) = days.last_key_value() else {
+            return Vec::new();
+        };
+
+        (0..width)
+            .rev()
+            .filter_map(|days_ago| last.checked_sub_days(chrono::Days::new(days_ago as u64)))
+            .map(|date| days.get(&date).copied().unwrap_or_default())
+            .collect()
+    }
+
+    fn row(&self, entry: &Entry) -> Row<'static> {
+        let kind = entry.operation.kind();
+        let transaction = entry
+            .transaction
+            .map(|i| (i + 1).to_string())
+            .unwrap_or_default();
+        let details = match (&entry.operation, entry.transaction) {
+            (Operation::TransactionStarted, Some(i)) => {
+                let command = self.log.transactions[i].command.as_deref();
+                format!("started {}", command.unwrap_or_default())
+            }
+            (operation, _) => operation.details(),
+        };
+
+        let row = Row::new(vec![
+            Cell::from(format::date(entry.time)),
+            Cell::from(transaction),
+            Cell::from(kind.to_string()).fg(kind_color(kind)),
+            Cell::from(entry.operation.package().unwrap_or_default().to_owned()),
+            Cell::from(details),
+        ]);
+        if entry.operation == Operation::TransactionStarted {
+            row.bold()
+        } else {
+            row
+        }
+    }
+}
+
+impl Component for LogView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+        if self.input.is_some() {
+            self.handle_input(key_event);
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('j') | KeyCode::Down => self.next(),
+                KeyCode::Char('k') | KeyCode::Up => self.previous(),
+                KeyCode::Char('g') | KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(usize::MAX),
+                KeyCode::Char(']') => self.jump_transaction(true),
+                KeyCode::Char('[') => self.jump_transaction(false),
+                KeyCode::Char('/') => {
+                    self.input = Some((Filter::Package, self.package.clone()));
+                }
+                KeyCode::Char('d') => {
+                    self.input = Some((Filter::Dates, self.dates_text.clone()));
+                }
+                KeyCode::Char('a') => self.cycle_kind(),
+                KeyCode::Char('c') => {
+                    self.package.clear();
+                    self.kind = None;
+                    self.dates = (None, None);
+                    self.dates_text.clear();
+                    self.error = None;
+                    self.filter();
+                }
+                KeyCode::Char('r') => actions.push(Action::LoadLog),
+                KeyCode::Enter => {
+                    if let Some(package) = self
+                        .selected_entry()
+                        .and_then(|entry| entry.operation.package())
+                    {
+                        actions.push(Action::ShowPackage(package.to_owned()));
+                    }
+                }
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code: KeyCode::Char('G'),
+                ..
+            } => self.select(usize::MAX),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown => self.next(),
+            MouseEventKind::ScrollUp => self.previous(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::LogLoaded(log) => {
+                self.log = log.clone();
+                self.filter();
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Log;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [timeline_area, table_area] =
+            Layout::vertical([Constraint::Length(TIMELINE_HEIGHT), Constraint::Fill(1)])
+                .areas(*area);
+        self.area = table_area;
+
+        let timeline_block = Block::bordered()
+            .title("package operations per day")
+            .border_style(Style::default().fg(self.theme.inactive));
+        let timeline = self.timeline(usize::from(timeline_block.inner(timeline_area).width));
+        frame.render_widget(
+            Sparkline::default()
+                .block(timeline_block)
+                .data(&timeline)
+                .style(Style::default().fg(Color::Cyan)),
+            timeline_area,
+        );
+
+        // logs grow large, only the rows on screen are built
+        let height = usize::from(table_area.height.saturating_sub(3)).max(1);
+        let selected = self.state.selected().unwrap_or_default();
+        let mut offset = self.state.offset().min(selected);
+        if selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+        *self.state.offset_mut() = offset;
+        let rows = self
+            .visible
+            .iter()
+            .skip(offset)
+            .take(height)
+            .map(|&i| self.row(&self.log.entries[i]))
+            .collect::<Vec<_>>();
+        let mut state = TableState::default()
+            .with_selected(self.state.selected().map(|selected| selected - offset));
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Length(5),
+            Constraint::Length(11),
+            Constraint::Length(24),
+            Constraint::Fill(1),
+        ];
+        let header = Row::new(["time", "#", "action", "package", "details"])
+            .style(Style::new().bold().fg(Color::Magenta));
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::bordered()
+                    .title("pacman log")
+                    .title_bottom(self.status())
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, table_area, &mut state);
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Log)
+    }
+
+    fn is_modal(&self) -> bool {
+        self.input.is_some()
+    }
+}
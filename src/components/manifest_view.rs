@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::widgets::{Block, Cell, Clear, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::Component;
+use crate::event::Event;
+use crate::manifest::Change;
+use crate::theme::Theme;
+
+/// What importing a manifest would change, with the changes to apply checked.
+#[derive(Default)]
+pub(crate) struct ManifestView {
+    path: Option<PathBuf>,
+    changes: Vec<(Change, bool)>,
+    state: TableState,
+    theme: Theme,
+}
+
+impl ManifestView {
+    fn select(&mut self, index: usize) {
+        if !self.changes.is_empty() {
+            self.state.select(Some(index.min(self.changes.len() - 1)));
+        }
+    }
+
+    fn toggle(&mut self) {
+        let Some((change, checked)) = self.state.selected().and_then(|i| self.changes.get_mut(i))
+        else {
+            return;
+        };
+        if change.is_applicable() {
+            *checked = !*checked;
+        }
+    }
+
+    fn apply_action(&self) -> Option<Action> {
+        let path = self.path.as_ref()?;
+        let changes = self
+            .changes
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(change, _)| change.clone())
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return None;
+        }
+
+        let count = |matches: fn(&Change) -> bool| changes.iter().filter(|c| matches(c)).count();
+        let message = format!(
+            "apply {} from {}?\n{} to install, {} to change, {} to remove",
+            changes.len(),
+            path.display(),
+            count(|change| matches!(change, Change::Install { .. })),
+            count(|change| {
+                matches!(
+                    change,
+                    Change::Version { .. } | Change::Explicit(_) | Change::Depend(_)
+                )
+            }),
+            count(|change| matches!(change, Change::Remove(_))),
+        );
+        Some(Action::Confirm(
+            message,
+            Box::new(Action::ApplyManifest(changes)),
+        ))
+    }
+}
+
+impl Component for ManifestView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        let mut actions = Vec::new();
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Enter => {
+                if let Some(action) = self.apply_action() {
+                    actions.push(action);
+                    self.path = None;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.path = None,
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        if let Event::ManifestCompared(path, changes) = event {
+            self.path = Some(path.clone());
+            // removals and demotions are opt-in, a manifest of another machine rarely lists
+            // everything
+            self.changes = changes
+                .iter()
+                .map(|change| {
+                    let checked = change.is_applicable()
+                        && !matches!(change, Change::Remove(_) | Change::Depend(_));
+                    (change.clone(), checked)
+                })
+                .collect();
+            self.state = TableState::default();
+            self.select(0);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let [_, area, _] = Layout::vertical([
+            Constraint::Percentage(15),
+            Constraint::Percentage(70),
+            Constraint::Percentage(15),
+        ])
+        .areas(*area);
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Percentage(15),
+            Constraint::Percentage(70),
+            Constraint::Percentage(15),
+        ])
+        .areas(area);
+
+        let rows = self.changes.iter().map(|(change, checked)| {
+            let color = match change {
+                Change::Install { .. } => Color::Green,
+                Change::Remove(_) => Color::Red,
+                Change::Version { .. } | Change::Explicit(_) | Change::Depend(_) => Color::Yellow,
+            };
+            let row = Row::new(vec![
+                Cell::from(if *checked { "[x]" } else { "[ ]" }),
+                Cell::from(change.to_string().fg(color)),
+            ]);
+            if change.is_applicable() {
+                row
+            } else {
+                row.style(Style::default().add_modifier(Modifier::DIM))
+            }
+        });
+        let widths = [Constraint::Length(3), Constraint::Fill(1)];
+
+        let checked = self.changes.iter().filter(|(_, checked)| *checked).count();
+        let table = Table::new(rows, widths)
+            .block(
+                Block::bordered()
+                    .title(format!(
+                        "{checked} of {} changes to import {}",
+                        self.changes.len(),
+                        path.display()
+                    ))
+                    .title_bottom(" [Space] toggle  [Enter] apply  [Esc] close ")
+                    .border_style(Style::default().fg(self.theme.active)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(table, area, &mut self.state);
+        Ok(())
+    }
+
+    fn is_modal(&self) -> bool {
+        self.path.is_some()
+    }
+}
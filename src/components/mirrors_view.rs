@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::widgets::{Block, Cell, Gauge, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::mirrorlist::{self, Mirror, Speed};
+use crate::theme::Theme;
+
+/// The servers of the mirrorlist, measured and ranked by speed.
+#[derive(Default)]
+pub(crate) struct MirrorsView {
+    mirrors: Vec<Mirror>,
+    /// Results by server.
+    speeds: HashMap<String, Result<Speed, String>>,
+    /// Whether the mirrors differ from the mirrorlist.
+    changed: bool,
+    total: usize,
+    done: usize,
+    running: bool,
+    state: TableState,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl MirrorsView {
+    fn select(&mut self, index: usize) {
+        if !self.mirrors.is_empty() {
+            self.state.select(Some(index.min(self.mirrors.len() - 1)));
+        }
+    }
+
+    fn toggle(&mut self) {
+        let Some(mirror) = self.state.selected().and_then(|i| self.mirrors.get_mut(i)) else {
+            return;
+        };
+        mirror.enabled = !mirror.enabled;
+        self.changed = true;
+    }
+
+    /// Orders the mirrors fastest first, then the failed ones, then the unmeasured ones.
+    fn sort(&mut self) {
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| self.mirrors.get(i))
+            .map(|mirror| mirror.server.clone());
+        mirrorlist::rank(&mut self.mirrors, &self.speeds);
+        self.changed = true;
+
+        let index = self
+            .mirrors
+            .iter()
+            .position(|mirror| Some(&mirror.server) == selected.as_ref());
+        self.select(index.unwrap_or_default());
+    }
+
+    fn measure_action(&self, all: bool) -> Option<Action> {
+        let servers = self
+            .mirrors
+            .iter()
+            .filter(|mirror| all || mirror.enabled)
+            .map(|mirror| mirror.server.clone())
+            .collect::<Vec<_>>();
+        (!servers.is_empty()).then_some(Action::MeasureMirrors(servers))
+    }
+
+    fn write_action(&self) -> Option<Action> {
+        let enabled = self.mirrors.iter().filter(|mirror| mirror.enabled).count();
+        if enabled == 0 {
+            return None;
+        }
+        Some(Action::Confirm(
+            format!(
+                "write the mirrorlist with {enabled} of {} servers enabled? the old one is kept as a .bak file",
+                self.mirrors.len()
+            ),
+            Box::new(Action::WriteMirrorlist(self.mirrors.clone())),
+        ))
+    }
+
+    fn speed_cells(&self, mirror: &Mirror) -> [Cell<'static>; 2] {
+        match self.speeds.get(&mirror.server) {
+            Some(Ok(speed)) => [
+                Cell::from(format!("{}/s", format::size(speed.throughput as u64))),
+                Cell::from(format!("{} ms", speed.latency.as_millis())),
+            ],
+            Some(Err(error)) => [
+                Cell::from("failed").fg(Color::Red),
+                Cell::from(error.clone()),
+            ],
+            None => [Cell::from(""), Cell::from("")],
+        }
+    }
+}
+
+impl Component for MirrorsView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        // control keys belong to the app
+        if !matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        ) {
+            return Ok(Some(actions));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('s') => self.sort(),
+            KeyCode::Char('m') if !self.running => actions.extend(self.measure_action(false)),
+            KeyCode::Char('M') if !self.running => actions.extend(self.measure_action(true)),
+            KeyCode::Char('x') if self.running => actions.push(Action::CancelMirrorMeasurement),
+            KeyCode::Char('w') => actions.extend(self.write_action()),
+            KeyCode::Char('r') => actions.push(Action::LoadMirrors),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        let in_list = self
+            .area
+            .contains((mouse_event.column, mouse_event.row).into());
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if in_list && mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::MirrorsLoaded(mirrors) => {
+                self.mirrors = mirrors.clone();
+                self.changed = false;
+                self.select(self.state.selected().unwrap_or_default());
+            }
+            Event::MirrorMeasurementStarted(total) => {
+                self.total = *total;
+                self.done = 0;
+                self.running = *total > 0;
+            }
+            Event::MirrorMeasured(server, speed) => {
+                self.done += 1;
+                self.running = self.done < self.total;
+                self.speeds.insert(server.clone(), speed.clone());
+            }
+            Event::MirrorMeasurementCancelled => {
+                self.running = false;
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Mirrors;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [progress_area, list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(*area);
+        self.area = list_area;
+
+        let ratio = if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 / self.total as f64
+        };
+        let label = if self.running {
+            format!("measured {} of {} servers", self.done, self.total)
+        } else if self.total == 0 {
+            String::from("press [m] to measure the enabled servers, [M] all of them")
+        } else {
+            format!("measured {} servers, [s] sorts them by speed", self.done)
+        };
+        frame.render_widget(
+            Gauge::default()
+                .block(
+                    Block::bordered()
+                        .title("mirrors")
+                        .title_bottom(if self.running {
+                            " [x] cancel "
+                        } else {
+                            " [m] measure enabled  [M] measure all "
+                        })
+                        .border_style(Style::default().fg(border_color)),
+                )
+                .gauge_style(Style::default().fg(Color::Green))
+                .ratio(ratio)
+                .label(label),
+            progress_area,
+        );
+
+        let rows = self
+            .mirrors
+            .iter()
+            .map(|mirror| {
+                let [speed, latency] = self.speed_cells(mirror);
+                let row = Row::new(vec![
+                    Cell::from(if mirror.enabled { "[x]" } else { "[ ]" }),
+                    Cell::from(mirror.server.clone()),
+                    Cell::from(mirror.country.clone().unwrap_or_default()),
+                    speed,
+                    latency,
+                ]);
+                if mirror.enabled {
+                    row
+                } else {
+                    row.style(Style::default().add_modifier(Modifier::DIM))
+                }
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(3),
+            Constraint::Fill(3),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Fill(1),
+        ];
+        let enabled = self.mirrors.iter().filter(|mirror| mirror.enabled).count();
+        let title = if self.changed {
+            format!(
+                "{enabled} of {} servers enabled, not written yet",
+                self.mirrors.len()
+            )
+        } else {
+            format!("{enabled} of {} servers enabled", self.mirrors.len())
+        };
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["", "server", "country", "speed", "latency"])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(" [Space] enable  [s] sort by speed  [w] write  [r] reload ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Mirrors)
+    }
+}
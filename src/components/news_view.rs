@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::time::UNIX_EPOCH;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::news::{Feed, Item};
+use crate::theme::Theme;
+
+/// The news feed, unread items first in line to be read before upgrading.
+#[derive(Default)]
+pub(crate) struct NewsView {
+    feed: Feed,
+    read: HashSet<String>,
+    state: TableState,
+    scroll: usize,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl NewsView {
+    fn select(&mut self, index: usize) {
+        if !self.feed.items.is_empty() {
+            self.state
+                .select(Some(index.min(self.feed.items.len() - 1)));
+            self.scroll = 0;
+        }
+    }
+
+    fn selected(&self) -> Option<&Item> {
+        self.feed.items.get(self.state.selected()?)
+    }
+
+    fn unread(&self) -> impl Iterator<Item = &Item> {
+        self.feed
+            .items
+            .iter()
+            .filter(|item| !self.read.contains(&item.id))
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(item) = self.selected() else {
+            return Vec::new();
+        };
+
+        let mut lines = vec![
+            Line::from(item.title.clone()).bold(),
+            Line::from(item.published.map_or_else(String::new, format::date)),
+            Line::from(item.link.clone()).fg(Color::Blue),
+            Line::default(),
+        ];
+        lines.extend(
+            item.description
+                .lines()
+                .map(|line| Line::from(line.to_owned())),
+        );
+        lines
+    }
+}
+
+impl Component for NewsView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        // control keys belong to the app
+        if !matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        ) {
+            return Ok(Some(actions));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(item) = self.selected() {
+                    if !self.read.contains(&item.id) {
+                        actions.push(Action::MarkNewsRead(vec![item.id.clone()]));
+                    }
+                }
+            }
+            KeyCode::Char('a') => {
+                let ids = self
+                    .unread()
+                    .map(|item| item.id.clone())
+                    .collect::<Vec<_>>();
+                if !ids.is_empty() {
+                    actions.push(Action::MarkNewsRead(ids));
+                }
+            }
+            KeyCode::Char('r') => actions.push(Action::LoadNews),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        let in_list = self
+            .area
+            .contains((mouse_event.column, mouse_event.row).into());
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if in_list && mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_add(1),
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::NewsLoaded(feed, read) => {
+                self.feed = feed.clone();
+                self.read = read.iter().cloned().collect();
+                // start at the oldest unread item, the one to read first
+                let index = self
+                    .feed
+                    .items
+                    .iter()
+                    .rposition(|item| !self.read.contains(&item.id));
+                self.state = TableState::default();
+                self.select(index.unwrap_or_default());
+            }
+            Event::NewsRead(ids) => {
+                self.read.extend(ids.iter().cloned());
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::News;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(*area);
+        self.area = list_area;
+
+        let rows = self
+            .feed
+            .items
+            .iter()
+            .map(|item| {
+                let row = Row::new(vec![
+                    Cell::from(item.published.map_or_else(String::new, format::date)),
+                    Cell::from(item.title.clone()),
+                ]);
+                if self.read.contains(&item.id) {
+                    row.style(Style::default().add_modifier(Modifier::DIM))
+                } else {
+                    row.style(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+        let widths = [Constraint::Length(16), Constraint::Fill(1)];
+        let mut title = format!(
+            "{} unread of {} news",
+            self.unread().count(),
+            self.feed.items.len()
+        );
+        if let Some(cached) = self.feed.cached {
+            let fetched = cached
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |fetched| fetched.as_secs() as i64);
+            title.push_str(&format!(", offline copy from {}", format::date(fetched)));
+        }
+        let table = Table::new(rows, widths)
+            .header(Row::new(["published", "title"]).style(Style::new().bold().fg(Color::Magenta)))
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(" [Enter] mark read  [a] mark all read  [r] reload ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+
+        let block = Block::bordered()
+            .title_bottom(" [PgUp/PgDn] scroll ")
+            .border_style(Style::default().fg(border_color));
+        let lines = self.details();
+        let max_scroll = lines
+            .len()
+            .saturating_sub(usize::from(block.inner(details_area).height));
+        self.scroll = self.scroll.min(max_scroll);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            details_area,
+        );
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::News)
+    }
+}
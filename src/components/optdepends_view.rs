@@ -0,0 +1,334 @@
+use std::collections::HashSet;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::format;
+use crate::pacman::{Optdep, Package};
+use crate::theme::Theme;
+
+/// The optional dependencies of the selected package, and the packages installed only as
+/// optional dependencies.
+#[derive(Default)]
+pub(crate) struct OptdependsView {
+    package: Option<Package>,
+    optdepends: Vec<Optdep>,
+    /// Optional dependencies marked by name to install together.
+    marked: HashSet<String>,
+    optional_only: Vec<(Package, Vec<String>)>,
+    state: TableState,
+    optional_only_state: TableState,
+    /// Whether keys move through the packages installed as optional dependencies.
+    in_optional_only: bool,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl OptdependsView {
+    fn select(&mut self, index: usize) {
+        if self.in_optional_only {
+            if !self.optional_only.is_empty() {
+                let index = index.min(self.optional_only.len() - 1);
+                self.optional_only_state.select(Some(index));
+            }
+        } else if !self.optdepends.is_empty() {
+            self.state
+                .select(Some(index.min(self.optdepends.len() - 1)));
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        if self.in_optional_only {
+            self.optional_only_state.selected()
+        } else {
+            self.state.selected()
+        }
+    }
+
+    fn selected_optdep(&self) -> Option<&Optdep> {
+        self.optdepends.get(self.state.selected()?)
+    }
+
+    /// Marks or unmarks the selected optional dependency, unless it is installed.
+    fn toggle(&mut self) {
+        let Some(optdep) = self.selected_optdep() else {
+            return;
+        };
+        if optdep.installed.is_some() || optdep.available.is_none() {
+            return;
+        }
+        let name = optdep.dep.name.clone();
+        if !self.marked.remove(&name) {
+            self.marked.insert(name);
+        }
+    }
+
+    /// Installs the marked optional dependencies, or the selected one.
+    fn install_action(&self) -> Option<Action> {
+        let package = self.package.clone()?;
+        let mut package_names = self
+            .optdepends
+            .iter()
+            .filter(|optdep| self.marked.contains(&optdep.dep.name))
+            .filter_map(|optdep| optdep.available.clone())
+            .collect::<Vec<_>>();
+        if package_names.is_empty() {
+            let optdep = self
+                .selected_optdep()
+                .filter(|optdep| optdep.installed.is_none())?;
+            package_names.extend(optdep.available.clone());
+        }
+        if package_names.is_empty() {
+            return None;
+        }
+
+        Some(Action::Confirm(
+            format!(
+                "install {} as dependencies of {}?",
+                package_names.join(", "),
+                package.name
+            ),
+            Box::new(Action::InstallOptdepends(package, package_names)),
+        ))
+    }
+
+    fn status(optdep: &Optdep) -> Line<'static> {
+        match (&optdep.installed, &optdep.available) {
+            (Some(installed), _) if *installed == optdep.dep.name => {
+                Line::from("installed").fg(Color::Green)
+            }
+            (Some(installed), _) => Line::from(format!("installed ({installed})")).fg(Color::Green),
+            (None, Some(_)) => Line::from("available"),
+            (None, None) => Line::from("not found").fg(Color::Yellow),
+        }
+    }
+
+    fn draw_optdepends(&mut self, frame: &mut Frame, area: Rect, border_color: Color) {
+        let rows = self
+            .optdepends
+            .iter()
+            .map(|optdep| {
+                let marked = self.marked.contains(&optdep.dep.name);
+                Row::new(vec![
+                    Cell::from(if marked { "*" } else { "" }),
+                    Cell::from(optdep.dep.to_depstring()),
+                    Cell::from(Self::status(optdep)),
+                    Cell::from(optdep.dep.description.clone().unwrap_or_default()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Length(24),
+            Constraint::Length(20),
+            Constraint::Fill(1),
+        ];
+        let title = match &self.package {
+            Some(package) if self.optdepends.is_empty() => {
+                format!("{} has no optional dependencies", package.name)
+            }
+            Some(package) => format!("optional dependencies of {}", package.name),
+            None => String::from("select a package to see its optional dependencies"),
+        };
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["", "dependency", "status", "description"])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(" [Space] mark  [Enter] install as dependencies ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(if self.in_optional_only {
+                Style::default()
+            } else {
+                Style::default().add_modifier(Modifier::REVERSED)
+            });
+        frame.render_stateful_widget(table, area, &mut self.state);
+    }
+
+    fn draw_optional_only(&mut self, frame: &mut Frame, area: Rect, border_color: Color) {
+        let rows = self
+            .optional_only
+            .iter()
+            .map(|(package, optional_for)| {
+                Row::new(vec![
+                    package.name.clone(),
+                    format::size(package.installed_size),
+                    optional_for.join(", "),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(24),
+            Constraint::Length(11),
+            Constraint::Fill(1),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["package", "size", "optional for"])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(format!(
+                        "{} packages installed only as optional dependencies",
+                        self.optional_only.len()
+                    ))
+                    .title_bottom(" [Enter] show  [h/l] switch list  [r] reload ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(if self.in_optional_only {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            });
+        frame.render_stateful_widget(table, area, &mut self.optional_only_state);
+    }
+}
+
+impl Component for OptdependsView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        // control keys belong to the app
+        if !matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        ) {
+            return Ok(Some(actions));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.selected_index().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.selected_index().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char('h') | KeyCode::Left => self.in_optional_only = false,
+            KeyCode::Char('l') | KeyCode::Right => self.in_optional_only = true,
+            KeyCode::Char(' ') if !self.in_optional_only => self.toggle(),
+            KeyCode::Enter if self.in_optional_only => {
+                let selected = self
+                    .optional_only_state
+                    .selected()
+                    .and_then(|index| self.optional_only.get(index));
+                if let Some((package, _)) = selected {
+                    actions.push(Action::ShowPackage(package.name.clone()));
+                }
+            }
+            KeyCode::Enter => actions.extend(self.install_action()),
+            KeyCode::Char('r') => actions.push(Action::LoadOptdepends),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                self.in_optional_only = mouse_event.column >= self.area.right();
+                let offset = if self.in_optional_only {
+                    self.optional_only_state.offset()
+                } else {
+                    self.state.offset()
+                };
+                if mouse_event.row >= first_row {
+                    self.select(offset + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.select(self.selected_index().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.selected_index().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::OptdependsLoaded(package, optdepends) => {
+                let same_package = self
+                    .package
+                    .as_ref()
+                    .is_some_and(|previous| previous.name == package.name);
+                if !same_package {
+                    self.marked.clear();
+                    self.state = TableState::default();
+                }
+                self.package = Some(package.clone());
+                self.optdepends = optdepends.clone();
+                self.marked.retain(|name| {
+                    optdepends
+                        .iter()
+                        .any(|optdep| optdep.dep.name == *name && optdep.installed.is_none())
+                });
+                self.select(self.state.selected().unwrap_or_default());
+            }
+            Event::OptionalOnlyLoaded(packages) => {
+                self.optional_only = packages.clone();
+                self.optional_only
+                    .sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+                let in_optional_only = std::mem::replace(&mut self.in_optional_only, true);
+                self.select(self.optional_only_state.selected().unwrap_or_default());
+                self.in_optional_only = in_optional_only;
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Optdepends;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [optdepends_area, optional_only_area] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(*area);
+        self.area = optdepends_area;
+
+        self.draw_optdepends(frame, optdepends_area, border_color);
+        self.draw_optional_only(frame, optional_only_area, border_color);
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Optdepends)
+    }
+}
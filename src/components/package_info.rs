@@ -1,49 +1,400 @@
+use std::{fmt, mem};
+
 use color_eyre::eyre;
 use ratatui::{
-    layout::{Constraint, Layout, Rect},
-    style::Style,
-    widgets::{Block, Row, Table},
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind},
+    layout::Rect,
+    style::{Color, Style, Stylize as _},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
     Frame,
 };
 
-use crate::{components::Component, event::Event, pacman::Package, theme::Theme};
+use crate::{
+    action::Action,
+    aur::AurInfo,
+    components::{Component, Pane},
+    event::Event,
+    format,
+    keyring::Key,
+    pacman::{Package, PackageSignature, Signature},
+    theme::Theme,
+};
+
+const KEY_WIDTH: usize = 16;
+
+type Field = (&'static str, fn(&Package) -> Vec<String>);
+
+fn sections(package: &Package) -> Vec<(&'static str, Vec<Field>)> {
+    let mut sections: Vec<(&str, Vec<Field>)> = vec![
+        (
+            "summary",
+            vec![
+                ("name", |p| vec![p.name.clone()]),
+                ("version", |p| vec![p.version.clone()]),
+                ("description", |p| optional(&p.description)),
+                ("source", |p| vec![p.source.clone()]),
+                ("arch", |p| optional(&p.arch)),
+                ("url", |p| optional(&p.url)),
+                ("base", |p| optional(&p.base)),
+                ("packager", |p| optional(&p.packager)),
+                ("filename", |p| optional(&p.filename)),
+                ("install reason", |p| display(&p.reason)),
+            ],
+        ),
+        (
+            "sizes",
+            vec![
+                ("download size", |p| vec![format::size(p.download_size)]),
+                ("installed size", |p| vec![format::size(p.installed_size)]),
+            ],
+        ),
+        (
+            "dates",
+            vec![
+                ("build date", |p| vec![format::date(p.build_date)]),
+                ("install date", |p| {
+                    p.install_date.map(format::date).into_iter().collect()
+                }),
+            ],
+        ),
+        ("licenses", vec![("licenses", |p| p.licenses.clone())]),
+        ("groups", vec![("groups", |p| p.groups.clone())]),
+        (
+            "dependencies",
+            vec![
+                ("depends", |p| display_all(&p.depends)),
+                ("optdepends", |p| display_all(&p.optdepends)),
+                ("makedepends", |p| display_all(&p.makedepends)),
+                ("checkdepends", |p| display_all(&p.checkdepends)),
+            ],
+        ),
+        (
+            "relations",
+            vec![
+                ("provides", |p| display_all(&p.provides)),
+                ("conflicts", |p| display_all(&p.conflicts)),
+                ("replaces", |p| display_all(&p.replaces)),
+            ],
+        ),
+        (
+            "validation",
+            vec![
+                ("validated by", |p| display_all(&p.validation)),
+                ("md5sum", |p| optional(&p.md5sum)),
+                ("sha256sum", |p| optional(&p.sha256sum)),
+            ],
+        ),
+    ];
+
+    if package.aur.is_some() {
+        let fields: Vec<Field> = vec![
+            ("votes", |p| aur(p, |aur| aur.votes.to_string())),
+            ("popularity", |p| {
+                aur(p, |aur| format!("{:.2}", aur.popularity))
+            }),
+            ("maintainer", |p| {
+                aur(p, |aur| {
+                    aur.maintainer
+                        .clone()
+                        .unwrap_or_else(|| String::from("orphaned"))
+                })
+            }),
+            ("out of date", |p| {
+                aur(p, |aur| {
+                    aur.out_of_date
+                        .map_or_else(|| String::from("no"), format::date)
+                })
+            }),
+            ("last modified", |p| {
+                aur(p, |aur| format::date(aur.last_modified))
+            }),
+        ];
+        sections.push(("aur", fields));
+    }
+
+    sections
+}
+
+fn aur(package: &Package, value: impl Fn(&AurInfo) -> String) -> Vec<String> {
+    package.aur.iter().map(value).collect()
+}
+
+fn optional(value: &Option<String>) -> Vec<String> {
+    value.iter().cloned().collect()
+}
+
+fn display<T: fmt::Display>(value: &Option<T>) -> Vec<String> {
+    value.iter().map(T::to_string).collect()
+}
+
+fn display_all<T: fmt::Display>(values: &[T]) -> Vec<String> {
+    values.iter().map(T::to_string).collect()
+}
+
+/// Word wraps `text` to `width` columns, hard splitting words that do not fit.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+        while line.chars().count() > width {
+            let rest = line.chars().skip(width).collect();
+            lines.push(line.chars().take(width).collect());
+            line = rest;
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn wrap_all(values: &[String], width: usize) -> Vec<String> {
+    if values.is_empty() {
+        return vec![String::from("-")];
+    }
+    values.iter().flat_map(|value| wrap(value, width)).collect()
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!("{text:width$}")
+}
 
 #[derive(Default)]
 pub(crate) struct PackageInfo {
     package: Package,
+    /// The signature of the package, once loaded.
+    signature: Option<PackageSignature>,
+    keys: Vec<Key>,
     theme: Theme,
+    active: bool,
+    scroll: usize,
+}
+
+impl PackageInfo {
+    fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+
+    fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    fn lines(&self, width: usize) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        if self.package.name.is_empty() {
+            return lines;
+        }
+
+        let local = self.package.local.as_deref();
+        let value_width = match local {
+            Some(_) => width.saturating_sub(KEY_WIDTH + 1) / 2,
+            None => width.saturating_sub(KEY_WIDTH),
+        };
+
+        if local.is_some() {
+            lines.push(Line::from(vec![
+                Span::raw(pad("", KEY_WIDTH)),
+                Span::raw(pad("available", value_width + 1)).bold(),
+                Span::raw("installed").bold(),
+            ]));
+        }
+
+        for (title, fields) in sections(&self.package) {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(title).bold().fg(Color::Magenta));
+
+            for (key, value) in fields {
+                let available = value(&self.package);
+                let available_lines = wrap_all(&available, value_width);
+                let installed = local.map(value);
+                let installed_lines = installed
+                    .as_deref()
+                    .map(|installed| wrap_all(installed, value_width))
+                    .unwrap_or_default();
+                // highlight installed values that differ from the available ones
+                let installed_style = match &installed {
+                    Some(installed) if *installed != available => {
+                        Style::default().fg(Color::Yellow)
+                    }
+                    _ => Style::default(),
+                };
+
+                let height = available_lines.len().max(installed_lines.len());
+                for i in 0..height {
+                    let key = if i == 0 { key } else { "" };
+                    let available = available_lines.get(i).map_or("", String::as_str);
+                    let mut spans = vec![Span::raw(pad(key, KEY_WIDTH)).fg(Color::Cyan)];
+                    if local.is_some() {
+                        let installed = installed_lines.get(i).map_or("", String::as_str);
+                        spans.push(Span::raw(pad(available, value_width + 1)));
+                        spans.push(Span::styled(installed.to_owned(), installed_style));
+                    } else {
+                        spans.push(Span::raw(available.to_owned()));
+                    }
+                    lines.push(Line::from(spans));
+                }
+            }
+        }
+
+        lines.extend(self.signature_lines());
+        lines
+    }
+
+    fn signature_lines(&self) -> Vec<Line<'static>> {
+        let Some(signature) = &self.signature else {
+            return Vec::new();
+        };
+
+        let color = match signature.status {
+            Signature::Valid => Color::Green,
+            Signature::Invalid | Signature::Missing => Color::Red,
+            Signature::Unverified => Color::Yellow,
+        };
+        let status = match signature.status {
+            Signature::Valid => String::from("valid, checked when installed"),
+            Signature::Unverified => String::from("signed, not verified at install"),
+            status => status.to_string(),
+        };
+        let mut lines = vec![
+            Line::default(),
+            Line::from("signature").bold().fg(Color::Magenta),
+            Line::from(vec![
+                Span::raw(pad("version", KEY_WIDTH)).fg(Color::Cyan),
+                Span::raw(signature.version.clone()),
+            ]),
+            Line::from(vec![
+                Span::raw(pad("status", KEY_WIDTH)).fg(Color::Cyan),
+                Span::raw(status).fg(color),
+            ]),
+        ];
+
+        for (i, key_id) in signature.key_ids.iter().enumerate() {
+            let label = if i == 0 { "signed by" } else { "" };
+            let key = self.keys.iter().find(|key| key.matches(key_id));
+            let owner = match key {
+                Some(key) if key.is_expired() => {
+                    Span::raw(format!("{} (expired)", key.name())).fg(Color::Red)
+                }
+                Some(key) if !key.validity.is_trusted() => {
+                    Span::raw(format!("{} ({})", key.name(), key.validity)).fg(Color::Red)
+                }
+                Some(key) => Span::raw(key.name().to_owned()),
+                None if self.keys.is_empty() => Span::raw(""),
+                None => Span::raw("not in the keyring").fg(Color::Yellow),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(pad(label, KEY_WIDTH)).fg(Color::Cyan),
+                Span::raw(format!("{key_id} ")),
+                owner,
+            ]));
+        }
+
+        lines
+    }
 }
 
 impl Component for PackageInfo {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
+                KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
+                KeyCode::Char('g') | KeyCode::Home => self.scroll = 0,
+                KeyCode::End => self.scroll = usize::MAX,
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code: KeyCode::Char('G'),
+                ..
+            } => self.scroll = usize::MAX,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => self.scroll_down(),
+            MouseEventKind::ScrollUp => self.scroll_up(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
     fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
-        let area = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(*area)[1];
-        let rows = [
-            Row::new(vec!["description", &self.package.description]),
-            Row::new(vec!["version", &self.package.version]),
-            Row::new(vec!["filename", &self.package.filename]),
-            Row::new(vec!["base", &self.package.base]),
-            Row::new(vec!["url", &self.package.url]),
-            Row::new(vec!["packager", &self.package.packager]),
-            Row::new(vec!["md5sum", &self.package.md5sum]),
-            Row::new(vec!["sha256sum", &self.package.sha256sum]),
-            Row::new(vec!["arch", &self.package.arch]),
-        ];
-        let widths = [Constraint::Length(15), Constraint::Percentage(100)];
-        let table = Table::new(rows, widths)
-            .block(Block::bordered().border_style(Style::default().fg(self.theme.active)));
-        frame.render_widget(table, area);
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let block = Block::bordered().border_style(Style::default().fg(border_color));
+        let inner = block.inner(*area);
+        let lines = self.lines(usize::from(inner.width));
+        let max_scroll = lines.len().saturating_sub(usize::from(inner.height));
+        self.scroll = self.scroll.min(max_scroll);
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .scroll((self.scroll as u16, 0));
+        frame.render_widget(paragraph, *area);
         Ok(())
     }
 
     fn update(&mut self, event: &Event) -> eyre::Result<()> {
         match event {
             Event::PackageSelected(package) => {
+                if package.name != self.package.name {
+                    self.signature = None;
+                }
                 self.package = package.clone();
+                self.scroll = 0;
+            }
+            Event::SignatureLoaded(package_name, signature)
+                if *package_name == self.package.name =>
+            {
+                self.signature = Some(signature.clone());
+            }
+            Event::KeyringLoaded(keys, _) => {
+                self.keys = keys.clone();
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::PackageInfo;
             }
             _ => {}
         }
 
         Ok(())
     }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::PackageInfo)
+    }
 }
@@ -1,28 +1,51 @@
+use std::collections::HashSet;
 use std::str::FromStr as _;
+use std::time::{Duration, Instant};
 
 use color_eyre::eyre;
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Rect};
 use ratatui::style::{Color, Modifier, Style, Stylize as _};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Cell, Row, Table, TableState};
 use ratatui::Frame;
 
 use crate::action::Action;
-use crate::components::Component;
+use crate::aur;
+use crate::components::{Component, Pane};
 use crate::event::Event;
+use crate::layout::View;
 use crate::{pacman::Package, theme::Theme};
 
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
 #[derive(Default)]
 pub(crate) struct PackagesTable {
     state: TableState,
     packages: Vec<Package>,
+    /// Installed packages marked by name to change several at once.
+    marked: HashSet<String>,
+    held: HashSet<String>,
     theme: Theme,
     active: bool,
+    area: Rect,
+    last_click: Option<(usize, Instant)>,
 }
 
 impl PackagesTable {
+    pub(crate) fn new(held: Vec<String>) -> Self {
+        Self {
+            held: held.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
     fn next(&mut self) {
+        if self.packages.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.packages.len() - 1 {
@@ -37,6 +60,9 @@ impl PackagesTable {
     }
 
     fn previous(&mut self) {
+        if self.packages.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -58,35 +84,65 @@ impl PackagesTable {
     fn reset_selection(&mut self) {
         self.state.select(Some(0));
     }
-}
 
-impl Component for PackagesTable {
-    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
-        let actions = None;
+    fn select_package_action(&mut self) -> Option<Action> {
+        let package = self.get_selected_package()?;
+        Some(Action::SelectPackage(package.clone()))
+    }
 
-        match key_event {
-            KeyEvent {
-                modifiers: KeyModifiers::CONTROL,
-                code,
-                ..
-            } => match code {
-                KeyCode::Char('j') => self.active = true,
-                KeyCode::Char('k') => self.active = false,
-                _ => {}
-            },
-            KeyEvent {
-                modifiers: KeyModifiers::NONE,
-                code,
-                ..
-            } => match code {
-                KeyCode::Tab => self.active = !self.active,
-                _ => {}
-            },
-            _ => {}
+    fn toggle_mark(&mut self) {
+        let Some(package) = self.get_selected_package() else {
+            return;
+        };
+        if !package.is_installed() {
+            return;
+        }
+        let package_name = package.name.clone();
+        if !self.marked.remove(&package_name) {
+            self.marked.insert(package_name);
+        }
+    }
+
+    /// Flips the install reasons of the marked packages, or of the selected one.
+    fn toggle_reasons_action(&mut self) -> Option<Action> {
+        let selected = self
+            .state
+            .selected()
+            .and_then(|index| self.packages.get(index));
+        let reasons = self
+            .packages
+            .iter()
+            .filter(|package| {
+                if self.marked.is_empty() {
+                    selected.is_some_and(|selected| selected.name == package.name)
+                } else {
+                    self.marked.contains(&package.name)
+                }
+            })
+            .filter_map(|package| {
+                let reason = package.install_reason()?;
+                Some((package.name.clone(), reason.toggled()))
+            })
+            .collect::<Vec<_>>();
+        (!reasons.is_empty()).then_some(Action::PreviewReasons(reasons))
+    }
+
+    /// Maps a terminal row to the index of the package rendered on it.
+    fn row_at(&self, row: u16) -> Option<usize> {
+        // skip the borders and the header
+        let first_row = self.area.y + 2;
+        if row < first_row || row + 1 >= self.area.bottom() {
+            return None;
         }
+        let index = self.state.offset() + usize::from(row - first_row);
+        (index < self.packages.len()).then_some(index)
+    }
+}
 
+impl Component for PackagesTable {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
         if !self.active {
-            return Ok(actions);
+            return Ok(None);
         }
 
         let mut actions = Vec::new();
@@ -121,13 +177,64 @@ impl Component for PackagesTable {
                 KeyCode::Char('i') => {
                     if let Some(package) = self.get_selected_package() {
                         let package_name = package.name.to_string();
-                        actions.push(Action::InstallPackage(package_name));
+                        if package.source == aur::SOURCE {
+                            actions.push(Action::InstallAurPackage(package_name));
+                        } else {
+                            actions.push(Action::InstallPackage(package_name));
+                        }
+                    }
+                }
+                KeyCode::Char('e') => {
+                    actions.push(Action::Prompt(
+                        String::from("export packages to (.json, .jsonl, .csv or .tsv)"),
+                        String::from("packages.json"),
+                        Box::new(Action::ExportPackages(
+                            Default::default(),
+                            self.packages.clone(),
+                        )),
+                    ));
+                }
+                KeyCode::Char('x') => {
+                    actions.push(Action::Prompt(
+                        String::from("export explicitly installed packages to (.toml or text)"),
+                        String::from("packages.toml"),
+                        Box::new(Action::ExportManifest(Default::default(), false)),
+                    ));
+                }
+                KeyCode::Char('m') => {
+                    actions.push(Action::Prompt(
+                        String::from("import packages from (.toml or text)"),
+                        String::from("packages.toml"),
+                        Box::new(Action::ImportManifest(Default::default())),
+                    ));
+                }
+                KeyCode::Char('d') => {
+                    if let Some(package) = self.get_selected_package() {
+                        actions.push(Action::ShowVersions(package.clone()));
+                    }
+                }
+                KeyCode::Char('v') => {
+                    if let Some(package) = self.get_selected_package() {
+                        if package.is_installed() {
+                            actions.push(Action::CheckIntegrity(Some(package.name.clone())));
+                        }
+                    }
+                }
+                KeyCode::Char(' ') => self.toggle_mark(),
+                KeyCode::Char('t') => actions.extend(self.toggle_reasons_action()),
+                KeyCode::Char('o') => actions.push(Action::ShowView(View::Optdepends)),
+                KeyCode::Char('p') => {
+                    if let Some(package) = self.get_selected_package() {
+                        actions.push(Action::ViewScripts(package.clone()));
                     }
                 }
                 KeyCode::Char('r') => {
                     if let Some(package) = self.get_selected_package() {
                         let package_name = package.name.to_string();
-                        actions.push(Action::RemovePackage(package_name));
+                        actions.push(Action::Confirm(
+                            format!("remove {package_name}?"),
+                            Box::new(Action::RemovePackage(package_name)),
+                        ));
                     }
                 }
                 _ => {}
@@ -145,10 +252,29 @@ impl Component for PackagesTable {
                         actions.push(Action::SelectPackage(package.clone()));
                     }
                 }
+                KeyCode::Char('H') => {
+                    if let Some(package) = self.get_selected_package() {
+                        if package.is_installed() {
+                            actions.push(Action::ToggleHold(package.name.clone()));
+                        }
+                    }
+                }
+                KeyCode::Char('U') => actions.push(Action::BrowsePackageFiles(None)),
+                KeyCode::Char('X') => {
+                    actions.push(Action::Prompt(
+                        String::from("export explicitly installed packages with their versions to"),
+                        String::from("packages.toml"),
+                        Box::new(Action::ExportManifest(Default::default(), true)),
+                    ));
+                }
                 KeyCode::Char('I') => {
                     if let Some(package) = self.get_selected_package() {
                         let package_name = package.name.to_string();
-                        actions.push(Action::UpdateInstallPackage(package_name));
+                        if package.source == aur::SOURCE {
+                            actions.push(Action::InstallAurPackage(package_name));
+                        } else {
+                            actions.push(Action::UpdateInstallPackage(package_name));
+                        }
                     }
                 }
                 _ => {}
@@ -159,27 +285,81 @@ impl Component for PackagesTable {
         Ok(Some(actions))
     }
 
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        let mut actions = Vec::new();
+
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(index) = self.row_at(mouse_event.row) {
+                    let double_click = self.last_click.is_some_and(|(row, time)| {
+                        row == index && time.elapsed() < DOUBLE_CLICK_INTERVAL
+                    });
+                    self.state.select(Some(index));
+                    actions.extend(self.select_package_action());
+                    if double_click {
+                        self.last_click = None;
+                        actions.push(Action::Focus(Pane::PackageInfo));
+                    } else {
+                        self.last_click = Some((index, Instant::now()));
+                    }
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.next();
+                actions.extend(self.select_package_action());
+            }
+            MouseEventKind::ScrollUp => {
+                self.previous();
+                actions.extend(self.select_package_action());
+            }
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
     fn update(&mut self, event: &Event) -> eyre::Result<()> {
         match event {
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::PackagesTable;
+            }
             Event::FoundPackages(packages) => {
                 self.packages = packages.clone();
+                self.marked.clear();
                 self.reset_selection();
             }
+            Event::FoundAurPackages(packages) => {
+                self.packages.extend(packages.iter().cloned());
+            }
+            // AUR builds may finish after the search changed
             Event::PackageInstalled(package_name) => {
-                let index = self
-                    .packages
-                    .iter()
-                    .position(|p| p.name == *package_name)
-                    .unwrap();
-                self.packages[index].installed = true;
+                if let Some(package) = self.packages.iter_mut().find(|p| p.name == *package_name) {
+                    package.installed_version = Some(package.version.clone());
+                }
             }
             Event::PackageRemoved(package_name) => {
-                let index = self
-                    .packages
-                    .iter()
-                    .position(|p| p.name == *package_name)
-                    .unwrap();
-                self.packages[index].installed = false;
+                if let Some(package) = self.packages.iter_mut().find(|p| p.name == *package_name) {
+                    package.installed_version = None;
+                }
+            }
+            Event::ReasonsChanged(reasons) => {
+                for (package_name, reason) in reasons {
+                    let Some(package) = self.packages.iter_mut().find(|p| p.name == *package_name)
+                    else {
+                        continue;
+                    };
+                    match &mut package.local {
+                        Some(local) => local.reason = Some(*reason),
+                        None => package.reason = Some(*reason),
+                    }
+                }
+                self.marked.clear();
+            }
+            Event::HoldsChanged(held) => {
+                self.held = held.iter().cloned().collect();
             }
             _ => {}
         }
@@ -188,39 +368,54 @@ impl Component for PackagesTable {
     }
 
     fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
-        let horizontal_layout =
-            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                .split(*area)[0];
-        let area = Layout::vertical([Constraint::Length(3), Constraint::Percentage(100)])
-            .split(horizontal_layout)[1];
+        self.area = *area;
         let mut rows = Vec::new();
         for package in &self.packages {
-            if package.installed {
-                let installed = vec![
+            let reason = package
+                .install_reason()
+                .map_or_else(String::new, |reason| reason.to_string());
+            let row = if package.is_installed() {
+                let mut installed = vec![
                     Span::from("["),
                     Span::styled("✔", Style::default().fg(Color::from_str("#00ff00")?)),
                     Span::from("]"),
                 ];
-                rows.push(Row::new(vec![
+                // held packages are skipped by system upgrades, so they are not upgradable
+                if self.held.contains(&package.name) {
+                    installed.push(Span::from(" 🔒"));
+                } else if package.installed_version.as_ref().is_some_and(|installed| {
+                    alpm::vercmp(installed.as_str(), package.version.as_str()).is_lt()
+                }) {
+                    installed.push(Span::styled(" ↑", Style::default().fg(Color::Yellow)));
+                }
+                Row::new(vec![
                     Cell::from(package.name.clone()),
                     Cell::from(package.source.clone()),
                     Cell::from(Line::from(installed)),
-                ]));
+                    Cell::from(reason),
+                ])
             } else {
-                rows.push(Row::new(vec![
+                Row::new(vec![
                     package.name.clone(),
                     package.source.clone(),
                     "[ ]".to_string(),
-                ]));
+                    reason,
+                ])
+            };
+            if self.marked.contains(&package.name) {
+                rows.push(row.style(Style::new().fg(Color::Yellow)));
+            } else {
+                rows.push(row);
             }
         }
         let widths = [
             Constraint::Length(20),
             Constraint::Length(10),
+            Constraint::Length(10),
             Constraint::Percentage(100),
         ];
-        let header =
-            Row::new(["name", "source", "installed"]).style(Style::new().bold().fg(Color::Magenta));
+        let header = Row::new(["name", "source", "installed", "reason"])
+            .style(Style::new().bold().fg(Color::Magenta));
         let border_color = if self.active {
             self.theme.active
         } else {
@@ -228,9 +423,21 @@ impl Component for PackagesTable {
         };
         let output = Table::new(rows, widths)
             .header(header)
-            .block(Block::bordered().border_style(Style::default().fg(border_color)))
+            .block(
+                Block::bordered()
+                    .title_bottom(if self.marked.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" {} marked, [t] toggle install reason ", self.marked.len())
+                    })
+                    .border_style(Style::default().fg(border_color)),
+            )
             .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
-        frame.render_stateful_widget(output, area, &mut self.state);
+        frame.render_stateful_widget(output, *area, &mut self.state);
         Ok(())
     }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::PackagesTable)
+    }
 }
@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::highlight;
+use crate::pacnew::{ConfigFile, Resolution};
+use crate::theme::Theme;
+
+/// `.pacnew` and `.pacsave` files and their differences from the live files.
+#[derive(Default)]
+pub(crate) struct PacnewView {
+    files: Vec<ConfigFile>,
+    /// Files kept as they are for the rest of the session.
+    kept: HashSet<PathBuf>,
+    state: TableState,
+    scroll: usize,
+    side_by_side: bool,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl PacnewView {
+    fn selected(&self) -> Option<&ConfigFile> {
+        self.files.get(self.state.selected()?)
+    }
+
+    fn select(&mut self, index: usize) {
+        if !self.files.is_empty() {
+            self.state.select(Some(index.min(self.files.len() - 1)));
+            self.scroll = 0;
+        }
+    }
+
+    fn resolve_action(&self, resolution: Resolution) -> Option<Action> {
+        let file = self.selected()?.clone();
+        let message = match resolution {
+            Resolution::Replace => format!(
+                "replace {} with {}?",
+                file.live.display(),
+                file.path.display()
+            ),
+            Resolution::Remove => format!("delete {}?", file.path.display()),
+            Resolution::Merge => return Some(Action::ResolveConfigFile(file, resolution)),
+        };
+        Some(Action::Confirm(
+            message,
+            Box::new(Action::ResolveConfigFile(file, resolution)),
+        ))
+    }
+
+    fn keep(&mut self) {
+        let Some(index) = self.state.selected() else {
+            return;
+        };
+        if index < self.files.len() {
+            let file = self.files.remove(index);
+            self.kept.insert(file.path);
+            self.select(index);
+        }
+    }
+
+    fn diff_lines(&self, width: usize) -> Vec<Line<'static>> {
+        let Some(file) = self.selected() else {
+            return Vec::new();
+        };
+        match &file.diff {
+            Err(error) => vec![Line::from(format!("cannot compare the files: {error}")).red()],
+            Ok(diff) if diff.is_empty() => vec![Line::from("the files are identical")],
+            Ok(diff) if self.side_by_side => side_by_side(diff, width),
+            Ok(diff) => diff.lines().map(highlight::diff).collect(),
+        }
+    }
+}
+
+/// Pads or cuts `text` to exactly `width` characters.
+fn column(text: &str, width: usize) -> String {
+    format!("{:width$}", text.chars().take(width).collect::<String>())
+}
+
+/// Lays a unified diff out as the live file on the left and the new one on the right.
+fn side_by_side(diff: &str, width: usize) -> Vec<Line<'static>> {
+    let half = width.saturating_sub(3) / 2;
+    let mut lines = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    let flush = |lines: &mut Vec<Line<'static>>, removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        for i in 0..removed.len().max(added.len()) {
+            let left = removed.get(i).copied();
+            let right = added.get(i).copied();
+            lines.push(Line::from(vec![
+                Span::raw(column(left.unwrap_or_default(), half)).red(),
+                Span::raw(if left.is_some() && right.is_some() {
+                    " | "
+                } else if left.is_some() {
+                    " < "
+                } else {
+                    " > "
+                }),
+                Span::raw(column(right.unwrap_or_default(), half)).green(),
+            ]));
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    // the file names come before the first hunk
+    let hunks = diff.lines().skip_while(|line| !line.starts_with("@@"));
+    for line in hunks {
+        if let Some(line) = line.strip_prefix('-') {
+            removed.push(line);
+            continue;
+        }
+        if let Some(line) = line.strip_prefix('+') {
+            added.push(line);
+            continue;
+        }
+        flush(&mut lines, &mut removed, &mut added);
+        if line.starts_with("@@") {
+            lines.push(Line::from(line.to_owned()).cyan());
+        } else if let Some(line) = line.strip_prefix(' ') {
+            lines.push(Line::from(format!(
+                "{}   {}",
+                column(line, half),
+                column(line, half)
+            )));
+        }
+    }
+    flush(&mut lines, &mut removed, &mut added);
+
+    lines
+}
+
+impl Component for PacnewView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.select(self.state.selected().map_or(0, |i| i + 1));
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+                }
+                KeyCode::Char('g') | KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(usize::MAX),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::Char('s') => self.side_by_side = !self.side_by_side,
+                KeyCode::Char('c') => self.keep(),
+                KeyCode::Char('u') => actions.extend(self.resolve_action(Resolution::Replace)),
+                KeyCode::Char('m') => actions.extend(self.resolve_action(Resolution::Merge)),
+                KeyCode::Char('d') => actions.extend(self.resolve_action(Resolution::Remove)),
+                KeyCode::Char('r') => actions.push(Action::LoadConfigFiles),
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code: KeyCode::Char('G'),
+                ..
+            } => self.select(usize::MAX),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        let in_list = mouse_event.column < self.area.right();
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if in_list && mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp if in_list => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_add(1),
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::ConfigFilesLoaded(files) => {
+                self.files = files
+                    .iter()
+                    .filter(|file| !self.kept.contains(&file.path))
+                    .cloned()
+                    .collect();
+                self.select(self.state.selected().unwrap_or_default());
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::Pacnew;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        let [list_area, diff_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(*area);
+        self.area = list_area;
+
+        let rows = self.files.iter().map(|file| {
+            Row::new(vec![
+                file.live.display().to_string(),
+                file.kind.title().to_owned(),
+                file.owner.clone().unwrap_or_else(|| String::from("-")),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(7),
+            Constraint::Length(16),
+        ];
+        let title = if self.files.is_empty() {
+            String::from("no .pacnew or .pacsave files")
+        } else {
+            format!("{} files to review", self.files.len())
+        };
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["file", "kind", "package"]).style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(" [u] use new  [m] merge  [d] delete  [c] keep  [r] reload ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list_area, &mut self.state);
+
+        let block = Block::bordered()
+            .title(
+                self.selected()
+                    .map(|file| file.path.display().to_string())
+                    .unwrap_or_default(),
+            )
+            .title_bottom(" [s] side by side  [PgUp/PgDn] scroll ")
+            .border_style(Style::default().fg(border_color));
+        let inner = block.inner(diff_area);
+        let lines = self.diff_lines(usize::from(inner.width));
+        let max_scroll = lines.len().saturating_sub(usize::from(inner.height));
+        self.scroll = self.scroll.min(max_scroll);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            diff_area,
+        );
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::Pacnew)
+    }
+}
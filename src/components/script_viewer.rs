@@ -0,0 +1,291 @@
+use color_eyre::eyre;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::event::Event;
+use crate::highlight;
+use crate::makepkg::Script;
+use crate::theme::Theme;
+
+/// Highlights the case insensitive occurrences of `query` in the spans of `line`.
+fn mark_matches(spans: Vec<Span<'static>>, line: &str, query: &str) -> Vec<Span<'static>> {
+    if query.is_empty() {
+        return spans;
+    }
+    // ASCII lowercasing keeps the byte offsets of the original line
+    let matches = line
+        .to_ascii_lowercase()
+        .match_indices(&query.to_ascii_lowercase())
+        .map(|(start, found)| (start, start + found.len()))
+        .collect::<Vec<_>>();
+    if matches.is_empty() {
+        return spans;
+    }
+
+    let mut marked = Vec::new();
+    let mut offset = 0;
+    for span in spans {
+        let (start, end) = (offset, offset + span.content.len());
+        offset = end;
+        let mut cut = start;
+        for &(match_start, match_end) in &matches {
+            let (from, to) = (match_start.max(start), match_end.min(end));
+            if from >= to {
+                continue;
+            }
+            if cut < from {
+                marked.push(Span::styled(line[cut..from].to_owned(), span.style));
+            }
+            marked.push(Span::styled(
+                line[from..to].to_owned(),
+                span.style.add_modifier(Modifier::REVERSED),
+            ));
+            cut = to;
+        }
+        if cut < end {
+            marked.push(Span::styled(line[cut..end].to_owned(), span.style));
+        }
+    }
+    marked
+}
+
+/// PKGBUILDs and install scriptlets with line numbers and search.
+#[derive(Default)]
+pub(crate) struct ScriptViewer {
+    package_name: String,
+    scripts: Vec<Script>,
+    selected: usize,
+    scroll: usize,
+    /// The query being typed, `None` when not searching.
+    input: Option<String>,
+    query: String,
+    theme: Theme,
+    active: bool,
+}
+
+impl ScriptViewer {
+    fn lines(&self) -> Vec<&str> {
+        self.scripts
+            .get(self.selected)
+            .map(|script| script.content.lines().collect())
+            .unwrap_or_default()
+    }
+
+    fn select(&mut self, index: usize) {
+        if index < self.scripts.len() {
+            self.selected = index;
+            self.scroll = 0;
+        }
+    }
+
+    fn matching_lines(&self) -> Vec<usize> {
+        if self.query.is_empty() {
+            return Vec::new();
+        }
+        let query = self.query.to_ascii_lowercase();
+        self.lines()
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.to_ascii_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn next_match(&mut self) {
+        let matches = self.matching_lines();
+        let next = matches
+            .iter()
+            .find(|&&i| i > self.scroll)
+            .or(matches.first());
+        if let Some(&i) = next {
+            self.scroll = i;
+        }
+    }
+
+    fn previous_match(&mut self) {
+        let matches = self.matching_lines();
+        let previous = matches
+            .iter()
+            .rev()
+            .find(|&&i| i < self.scroll)
+            .or(matches.last());
+        if let Some(&i) = previous {
+            self.scroll = i;
+        }
+    }
+
+    fn handle_input(&mut self, key_event: &KeyEvent) {
+        let Some(input) = &mut self.input else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Enter => {
+                self.query = self.input.take().unwrap_or_default();
+                // include the current line
+                self.scroll = self.scroll.saturating_sub(1);
+                self.next_match();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(char)
+                if matches!(
+                    key_event.modifiers,
+                    KeyModifiers::NONE | KeyModifiers::SHIFT
+                ) =>
+            {
+                input.push(char);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for ScriptViewer {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+        if self.input.is_some() {
+            self.handle_input(key_event);
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        match *key_event {
+            KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Char('g') | KeyCode::Home => self.scroll = 0,
+                KeyCode::End => self.scroll = usize::MAX,
+                KeyCode::Char('l') | KeyCode::Right => self.select(self.selected + 1),
+                KeyCode::Char('h') | KeyCode::Left => {
+                    self.select(self.selected.saturating_sub(1));
+                }
+                KeyCode::Char('/') => self.input = Some(String::new()),
+                KeyCode::Char('n') => self.next_match(),
+                KeyCode::Char('q') => actions.push(Action::CloseViewer),
+                _ => {}
+            },
+            KeyEvent {
+                modifiers: KeyModifiers::SHIFT,
+                code,
+                ..
+            } => match code {
+                KeyCode::Char('G') => self.scroll = usize::MAX,
+                KeyCode::Char('N') => self.previous_match(),
+                _ => {}
+            },
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_add(1),
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::ScriptsLoaded(package_name, scripts) => {
+                self.package_name = package_name.clone();
+                self.scripts = scripts.clone();
+                self.selected = 0;
+                self.scroll = 0;
+                self.input = None;
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::ScriptViewer;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+
+        let mut title = vec![Span::raw(format!("{} ", self.package_name))];
+        for (i, script) in self.scripts.iter().enumerate() {
+            let style = if i == self.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            title.push(Span::styled(format!(" {} ", script.name), style));
+        }
+        let status = match &self.input {
+            Some(input) => format!(" /{input} "),
+            None if !self.query.is_empty() => {
+                format!(
+                    " {} matches for {} ",
+                    self.matching_lines().len(),
+                    self.query
+                )
+            }
+            None => String::from(" [h/l] file  [/] search  [q] close "),
+        };
+        let block = Block::bordered()
+            .title(Line::from(title))
+            .title_bottom(status)
+            .border_style(Style::default().fg(border_color));
+
+        let inner = block.inner(*area);
+        let max_scroll = self.lines().len().saturating_sub(usize::from(inner.height));
+        self.scroll = self.scroll.min(max_scroll);
+        let lines = self.lines();
+
+        let number_width = lines.len().to_string().len();
+        let text = lines
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(usize::from(inner.height))
+            .map(|(i, line)| {
+                let mut spans =
+                    vec![Span::raw(format!("{:>number_width$} ", i + 1)).fg(Color::DarkGray)];
+                spans.extend(mark_matches(highlight::shell(line), line, &self.query));
+                Line::from(spans)
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_widget(Paragraph::new(text).block(block), *area);
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::ScriptViewer)
+    }
+
+    fn is_modal(&self) -> bool {
+        self.input.is_some()
+    }
+}
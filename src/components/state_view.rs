@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::widgets::{Block, Cell, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::{Component, Pane};
+use crate::desired::{Difference, Drift};
+use crate::event::Event;
+use crate::theme::Theme;
+
+/// How the installed packages differ from the desired state file.
+#[derive(Default)]
+pub(crate) struct StateView {
+    path: Option<PathBuf>,
+    differences: Vec<Difference>,
+    /// Why the desired state could not be read.
+    error: Option<String>,
+    state: TableState,
+    theme: Theme,
+    active: bool,
+    area: Rect,
+}
+
+impl StateView {
+    fn select(&mut self, index: usize) {
+        if !self.differences.is_empty() {
+            self.state
+                .select(Some(index.min(self.differences.len() - 1)));
+        }
+    }
+
+    fn count(&self, kind: Drift) -> usize {
+        self.differences
+            .iter()
+            .filter(|difference| difference.kind == kind)
+            .count()
+    }
+
+    fn reconcile_action(&self) -> Option<Action> {
+        let fixable = self
+            .differences
+            .iter()
+            .filter(|difference| difference.fix.is_some())
+            .cloned()
+            .collect::<Vec<_>>();
+        if fixable.is_empty() {
+            return None;
+        }
+
+        let mut message = format!("fix {} differences from the desired state?", fixable.len());
+        let by_hand = self.differences.len() - fixable.len();
+        if by_hand > 0 {
+            message.push_str(&format!("\n{by_hand} are left to fix by hand"));
+        }
+        Some(Action::Confirm(
+            message,
+            Box::new(Action::Reconcile(fixable)),
+        ))
+    }
+
+    fn title(&self) -> String {
+        let path = self
+            .path
+            .as_ref()
+            .map_or_else(String::new, |path| path.display().to_string());
+        if let Some(error) = &self.error {
+            return format!("could not read {path}: {error}");
+        }
+        if self.differences.is_empty() {
+            return format!("the system matches {path}");
+        }
+        format!(
+            "{} missing, {} extra, {} drifted from {path}",
+            self.count(Drift::Missing),
+            self.count(Drift::Extra),
+            self.count(Drift::Drifted)
+        )
+    }
+}
+
+impl Component for StateView {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        if !self.active {
+            return Ok(None);
+        }
+
+        let mut actions = Vec::new();
+
+        // control keys belong to the app
+        if !matches!(
+            key_event.modifiers,
+            KeyModifiers::NONE | KeyModifiers::SHIFT
+        ) {
+            return Ok(Some(actions));
+        }
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Enter => actions.extend(self.reconcile_action()),
+            KeyCode::Char('r') => actions.push(Action::LoadDesiredState),
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the top border and the header
+                let first_row = self.area.y + 2;
+                if mouse_event.row >= first_row {
+                    self.select(self.state.offset() + usize::from(mouse_event.row - first_row));
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::DesiredStateCompared(path, differences) => {
+                self.path = Some(path.clone());
+                match differences {
+                    Ok(differences) => {
+                        self.differences = differences.clone();
+                        self.error = None;
+                    }
+                    Err(error) => {
+                        self.differences.clear();
+                        self.error = Some(error.clone());
+                    }
+                }
+                self.select(self.state.selected().unwrap_or_default());
+            }
+            Event::PaneFocused(pane) => {
+                self.active = *pane == Pane::State;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let border_color = if self.active {
+            self.theme.active
+        } else {
+            self.theme.inactive
+        };
+        self.area = *area;
+
+        let rows = self
+            .differences
+            .iter()
+            .map(|difference| {
+                let color = match difference.kind {
+                    Drift::Missing => Color::Green,
+                    Drift::Extra => Color::Red,
+                    Drift::Drifted => Color::Yellow,
+                };
+                let row = Row::new(vec![
+                    Cell::from(difference.kind.to_string().fg(color)),
+                    Cell::from(difference.name.clone()),
+                    Cell::from(difference.detail.clone()),
+                    Cell::from(difference.fix_label()),
+                ]);
+                if difference.fix.is_some() {
+                    row
+                } else {
+                    row.style(Style::default().add_modifier(Modifier::DIM))
+                }
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Fill(1),
+            Constraint::Fill(2),
+            Constraint::Length(24),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["", "package", "difference", "fix"])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(self.title())
+                    .title_bottom(" [Enter] reconcile  [r] compare again ")
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, *area, &mut self.state);
+        Ok(())
+    }
+
+    fn pane(&self) -> Option<Pane> {
+        Some(Pane::State)
+    }
+}
@@ -0,0 +1,204 @@
+use color_eyre::eyre;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize as _};
+use ratatui::widgets::{Block, Cell, Clear, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::Component;
+use crate::downgrade::{self, Candidate};
+use crate::event::Event;
+use crate::format;
+use crate::pacman::Signature;
+use crate::theme::Theme;
+
+/// The versions an installed package can be downgraded or reinstalled to.
+#[derive(Default)]
+pub(crate) struct VersionPicker {
+    package_name: Option<String>,
+    installed_version: String,
+    versions: Vec<Candidate>,
+    /// Lookups still running in the background.
+    pending: usize,
+    state: TableState,
+    theme: Theme,
+}
+
+impl VersionPicker {
+    fn select(&mut self, index: usize) {
+        if !self.versions.is_empty() {
+            self.state.select(Some(index.min(self.versions.len() - 1)));
+        }
+    }
+
+    fn install_action(&self, hold: bool) -> Option<Action> {
+        let package_name = self.package_name.clone()?;
+        let candidate = self.versions.get(self.state.selected()?)?.clone();
+
+        let mut message = format!(
+            "install {package_name} {} from the {}?",
+            candidate.version,
+            candidate.source()
+        );
+        if hold {
+            message.push_str("\nit will be added to IgnorePkg");
+        }
+        if candidate.signature == Signature::Invalid {
+            message.push_str("\nthe signature of the file is invalid");
+        }
+        Some(Action::Confirm(
+            message,
+            Box::new(Action::InstallVersion(package_name, candidate, hold)),
+        ))
+    }
+}
+
+impl Component for VersionPicker {
+    fn handle_key_event(&mut self, key_event: &KeyEvent) -> eyre::Result<Option<Vec<Action>>> {
+        let mut actions = Vec::new();
+
+        match key_event.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Enter => {
+                if let Some(action) = self.install_action(false) {
+                    actions.push(action);
+                    self.package_name = None;
+                }
+            }
+            KeyCode::Char('h') => {
+                if let Some(action) = self.install_action(true) {
+                    actions.push(action);
+                    self.package_name = None;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.package_name = None,
+            _ => {}
+        }
+
+        Ok(Some(actions))
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse_event: &MouseEvent,
+    ) -> eyre::Result<Option<Vec<Action>>> {
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => {
+                self.select(self.state.selected().map_or(0, |i| i + 1));
+            }
+            MouseEventKind::ScrollUp => {
+                self.select(self.state.selected().map_or(0, |i| i.saturating_sub(1)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, event: &Event) -> eyre::Result<()> {
+        match event {
+            Event::VersionsRequested(package_name, installed_version, lookups) => {
+                self.package_name = Some(package_name.clone());
+                self.installed_version = installed_version.clone();
+                self.versions.clear();
+                self.pending = *lookups;
+                self.state = TableState::default();
+            }
+            Event::VersionsFound(package_name, candidates) => {
+                if self.package_name.as_ref() != Some(package_name) {
+                    return Ok(());
+                }
+                self.pending = self.pending.saturating_sub(1);
+                downgrade::merge(&mut self.versions, candidates.clone());
+                if self.state.selected().is_none() {
+                    self.select(0);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: &Rect) -> eyre::Result<()> {
+        let Some(package_name) = &self.package_name else {
+            return Ok(());
+        };
+
+        let [_, area, _] = Layout::vertical([
+            Constraint::Percentage(20),
+            Constraint::Percentage(60),
+            Constraint::Percentage(20),
+        ])
+        .areas(*area);
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Percentage(15),
+            Constraint::Percentage(70),
+            Constraint::Percentage(15),
+        ])
+        .areas(area);
+
+        let rows = self.versions.iter().map(|candidate| {
+            let installed = if candidate.version == self.installed_version {
+                "installed"
+            } else {
+                ""
+            };
+            let signature_color = match candidate.signature {
+                Signature::Valid => Color::Green,
+                Signature::Invalid => Color::Red,
+                Signature::Unverified | Signature::Missing => Color::Yellow,
+            };
+            Row::new(vec![
+                Cell::from(candidate.version.clone()),
+                Cell::from(candidate.source()),
+                Cell::from(candidate.date.map_or(String::from("-"), format::date)),
+                Cell::from(candidate.signature.to_string().fg(signature_color)),
+                Cell::from(installed),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(9),
+        ];
+
+        let title = if self.pending > 0 {
+            format!("versions of {package_name} (searching...)")
+        } else if self.versions.is_empty() {
+            format!("no other versions of {package_name} found")
+        } else {
+            format!("versions of {package_name}")
+        };
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["version", "source", "date", "signature", ""])
+                    .style(Style::new().bold().fg(Color::Magenta)),
+            )
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(" [Enter] install  [h] install and hold  [Esc] close ")
+                    .border_style(Style::default().fg(self.theme.active)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(table, area, &mut self.state);
+        Ok(())
+    }
+
+    fn is_modal(&self) -> bool {
+        self.package_name.is_some()
+    }
+}
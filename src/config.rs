@@ -0,0 +1,177 @@
+use std::{env, fs, path::PathBuf};
+
+use color_eyre::eyre;
+use serde::Deserialize;
+
+use crate::{cache, ignore, keyring, mirrorlist, pacman_log};
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct Config {
+    pub(crate) mouse: bool,
+    pub(crate) aur: AurConfig,
+    pub(crate) log_file: PathBuf,
+    pub(crate) cache_dirs: Vec<PathBuf>,
+    /// How many versions of each package the cache view keeps by default.
+    pub(crate) cache_keep: usize,
+    pub(crate) archive: ArchiveConfig,
+    /// The `IgnorePkg` include file ptu manages.
+    pub(crate) ignore_file: PathBuf,
+    /// Whether holds are written to `ignore_file` too, so pacman skips them as well.
+    pub(crate) persist_holds: bool,
+    /// The pacman keyring, e.g. a temporary one to try things out.
+    pub(crate) gpg_dir: PathBuf,
+    /// The mirrorlist the sync databases are refreshed from and the mirror view ranks.
+    pub(crate) mirrorlist: PathBuf,
+    pub(crate) news: NewsConfig,
+    /// The packages the system should have, `$XDG_CONFIG_HOME/ptu/state.toml` by default.
+    pub(crate) desired_state: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mouse: true,
+            aur: Default::default(),
+            log_file: PathBuf::from(pacman_log::LOG_FILE),
+            cache_dirs: vec![PathBuf::from(cache::CACHE_DIR)],
+            cache_keep: cache::KEEP,
+            archive: Default::default(),
+            ignore_file: PathBuf::from(ignore::IGNORE_FILE),
+            persist_holds: false,
+            gpg_dir: PathBuf::from(keyring::GPG_DIR),
+            mirrorlist: PathBuf::from(mirrorlist::MIRRORLIST),
+            news: Default::default(),
+            desired_state: None,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct AurConfig {
+    pub(crate) enabled: bool,
+    /// Base URL of the AUR web interface, e.g. a local mock server.
+    pub(crate) url: String,
+    /// Where package bases are cloned and built, `$XDG_CACHE_HOME/ptu/aur` by default.
+    pub(crate) build_dir: Option<PathBuf>,
+}
+
+impl Default for AurConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            url: String::from("https://aur.archlinux.org"),
+            build_dir: None,
+        }
+    }
+}
+
+/// An Arch Linux Archive style mirror of old package versions.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct ArchiveConfig {
+    pub(crate) enabled: bool,
+    /// Serves `packages/<first letter>/<name>/` directory listings, e.g. a local mock server.
+    pub(crate) url: String,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::from("https://archive.archlinux.org"),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct NewsConfig {
+    pub(crate) enabled: bool,
+    /// The RSS feed, a `file://` URL or a path reads a local file.
+    pub(crate) url: String,
+    /// Whether upgrades are refused until the news since the last upgrade are read.
+    pub(crate) block_upgrades: bool,
+}
+
+impl Default for NewsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            url: String::from("https://archlinux.org/feeds/news/"),
+            block_upgrades: false,
+        }
+    }
+}
+
+impl Config {
+    pub(crate) fn load() -> eyre::Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let config = toml::from_str(&content)?;
+
+        Ok(config)
+    }
+
+    pub(crate) fn desired_state(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.desired_state {
+            return Some(path.clone());
+        }
+
+        Some(config_dir()?.join("state.toml"))
+    }
+}
+
+impl AurConfig {
+    pub(crate) fn build_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.build_dir {
+            return Some(dir.clone());
+        }
+
+        Some(cache_dir()?.join("aur"))
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// Where ptu reads its configuration from, `$XDG_CONFIG_HOME/ptu` by default.
+fn config_dir() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_dir.join("ptu"))
+}
+
+/// Where ptu keeps what it can download again, `$XDG_CACHE_HOME/ptu` by default.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    let cache_dir = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+
+    Some(cache_dir.join("ptu"))
+}
+
+/// Where ptu keeps what it remembers between runs, `$XDG_STATE_HOME/ptu` by default.
+pub(crate) fn state_dir() -> Option<PathBuf> {
+    let state_dir = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+
+    Some(state_dir.join("ptu"))
+}
@@ -0,0 +1,485 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::ExitStatus;
+
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::hold::Holds;
+use crate::ignore;
+use crate::manifest::Manifest;
+use crate::pacman::{self, Package, Pacman, Reason, Transaction};
+
+/// The packages a system should have. A TOML manifest reads as a desired state without
+/// groups, dependencies or holds.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct DesiredState {
+    /// Packages installed explicitly, at the listed versions if any.
+    #[serde(flatten)]
+    pub(crate) manifest: Manifest,
+    /// Groups whose members are installed explicitly.
+    pub(crate) groups: Vec<String>,
+    /// Packages installed as dependencies, kept even when nothing requires them.
+    pub(crate) dependencies: Vec<String>,
+    /// Packages held at their installed version.
+    pub(crate) holds: Vec<String>,
+}
+
+impl DesiredState {
+    pub(crate) fn read(path: &Path) -> eyre::Result<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Drift {
+    /// Wanted but not installed.
+    Missing,
+    /// Installed but not wanted.
+    Extra,
+    /// Installed differently than wanted.
+    Drifted,
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::Missing => f.write_str("missing"),
+            Drift::Extra => f.write_str("extra"),
+            Drift::Drifted => f.write_str("drifted"),
+        }
+    }
+}
+
+/// What reconciling does about a difference.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fix {
+    Install(Reason),
+    /// Installs the version of the sync databases.
+    Sync,
+    SetReason(Reason),
+    Remove,
+    Hold(bool),
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::Install(Reason::Explicit) => f.write_str("install"),
+            Fix::Install(Reason::Depend) => f.write_str("install as dependency"),
+            Fix::Sync => f.write_str("install synced version"),
+            Fix::SetReason(reason) => write!(f, "mark as {reason}"),
+            Fix::Remove => f.write_str("remove"),
+            Fix::Hold(true) => f.write_str("hold"),
+            Fix::Hold(false) => f.write_str("release"),
+        }
+    }
+}
+
+/// A way the system differs from the desired state.
+#[derive(Clone, Serialize)]
+pub(crate) struct Difference {
+    pub(crate) kind: Drift,
+    /// A package, or a group the sync databases lack.
+    pub(crate) name: String,
+    pub(crate) detail: String,
+    /// `None` if it has to be fixed by hand.
+    #[serde(skip)]
+    pub(crate) fix: Option<Fix>,
+}
+
+impl Difference {
+    fn new(kind: Drift, name: &str, detail: String, fix: Option<Fix>) -> Self {
+        Self {
+            kind,
+            name: name.to_owned(),
+            detail,
+            fix,
+        }
+    }
+
+    /// What reconciling does, for listings.
+    pub(crate) fn fix_label(&self) -> String {
+        self.fix
+            .map_or_else(|| String::from("by hand"), |fix| fix.to_string())
+    }
+}
+
+/// What comparing needs to know about the packages of the system.
+pub(crate) trait System {
+    /// The installed packages with the sync database each is in.
+    fn installed_packages(&self) -> Vec<(Package, Option<String>)>;
+    /// The packages of a group, `None` if no sync database has it.
+    fn group_members(&self, group: &str) -> Option<Vec<String>>;
+    fn sync_version(&self, package_name: &str) -> Option<String>;
+    fn orphans(&self) -> Vec<Package>;
+    fn is_required(&self, package_name: &str) -> bool;
+}
+
+impl System for Pacman {
+    fn installed_packages(&self) -> Vec<(Package, Option<String>)> {
+        Pacman::installed_packages(self)
+    }
+
+    fn group_members(&self, group: &str) -> Option<Vec<String>> {
+        Pacman::group_members(self, group)
+    }
+
+    fn sync_version(&self, package_name: &str) -> Option<String> {
+        Pacman::sync_version(self, package_name)
+    }
+
+    fn orphans(&self) -> Vec<Package> {
+        Pacman::orphans(self)
+    }
+
+    fn is_required(&self, package_name: &str) -> bool {
+        Pacman::is_required(self, package_name)
+    }
+}
+
+/// How the installed packages and holds differ from the desired state, missing first.
+pub(crate) fn compare(state: &DesiredState, pacman: &dyn System, holds: &Holds) -> Vec<Difference> {
+    let installed = pacman
+        .installed_packages()
+        .into_iter()
+        .map(|(package, _)| (package.name.clone(), package))
+        .collect::<HashMap<_, _>>();
+    let mut differences = Vec::new();
+
+    let mut explicit = state.manifest.names().cloned().collect::<BTreeSet<_>>();
+    for group in &state.groups {
+        match pacman.group_members(group) {
+            Some(members) => explicit.extend(members),
+            None => differences.push(Difference::new(
+                Drift::Missing,
+                group,
+                String::from("group in no sync database"),
+                None,
+            )),
+        }
+    }
+    let dependencies = state
+        .dependencies
+        .iter()
+        .filter(|name| !explicit.contains(*name))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let wanted = explicit
+        .iter()
+        .map(|name| (name, Reason::Explicit))
+        .chain(dependencies.iter().map(|name| (name, Reason::Depend)));
+    for (name, reason) in wanted {
+        let Some(package) = installed.get(name) else {
+            let available = pacman.sync_version(name).is_some();
+            let detail = if available {
+                format!("not installed, wanted as {reason}")
+            } else {
+                String::from("not installed, in no sync database")
+            };
+            differences.push(Difference::new(
+                Drift::Missing,
+                name,
+                detail,
+                available.then_some(Fix::Install(reason)),
+            ));
+            continue;
+        };
+        let version = state
+            .manifest
+            .versions
+            .get(name)
+            .filter(|wanted| **wanted != package.version);
+        if let Some(version) = version {
+            let available = pacman.sync_version(name).as_ref() == Some(version);
+            differences.push(Difference::new(
+                Drift::Drifted,
+                name,
+                format!("at {}, wanted {version}", package.version),
+                available.then_some(Fix::Sync),
+            ));
+        }
+        if let Some(installed_reason) = package.reason.filter(|r| *r != reason) {
+            differences.push(Difference::new(
+                Drift::Drifted,
+                name,
+                format!("installed as {installed_reason}, wanted as {reason}"),
+                Some(Fix::SetReason(reason)),
+            ));
+        }
+    }
+
+    let orphans = pacman
+        .orphans()
+        .into_iter()
+        .map(|package| package.name)
+        .collect::<BTreeSet<_>>();
+    for package in installed.values() {
+        if explicit.contains(&package.name) || dependencies.contains(&package.name) {
+            continue;
+        }
+        if package.reason == Some(Reason::Explicit) {
+            // removing it would break the packages requiring it
+            let fix = if pacman.is_required(&package.name) {
+                Fix::SetReason(Reason::Depend)
+            } else {
+                Fix::Remove
+            };
+            differences.push(Difference::new(
+                Drift::Extra,
+                &package.name,
+                String::from("installed explicitly"),
+                Some(fix),
+            ));
+        } else if orphans.contains(&package.name) {
+            differences.push(Difference::new(
+                Drift::Extra,
+                &package.name,
+                String::from("dependency nothing requires"),
+                Some(Fix::Remove),
+            ));
+        }
+    }
+
+    let wanted_holds = state.holds.iter().collect::<BTreeSet<_>>();
+    for name in &wanted_holds {
+        if !holds.contains(name) {
+            differences.push(Difference::new(
+                Drift::Drifted,
+                name,
+                String::from("not held"),
+                Some(Fix::Hold(true)),
+            ));
+        }
+    }
+    for name in holds.names() {
+        if !wanted_holds.contains(&name) {
+            differences.push(Difference::new(
+                Drift::Drifted,
+                &name,
+                String::from("held"),
+                Some(Fix::Hold(false)),
+            ));
+        }
+    }
+
+    differences.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+    differences
+}
+
+/// Fixes the differences that can be fixed. The packages are changed in one transaction, so
+/// nothing is removed when an install fails, and the holds only once it succeeded.
+pub(crate) fn reconcile(
+    differences: &[Difference],
+    holds: &mut Holds,
+    config: &Config,
+) -> eyre::Result<ExitStatus> {
+    let mut transaction = Transaction::default();
+    for difference in differences {
+        let name = difference.name.clone();
+        match difference.fix {
+            Some(Fix::Install(Reason::Explicit) | Fix::Sync) => transaction.install.push(name),
+            Some(Fix::Install(Reason::Depend)) => {
+                transaction.install.push(name.clone());
+                transaction.depend.push(name);
+            }
+            Some(Fix::SetReason(Reason::Explicit)) => transaction.explicit.push(name),
+            Some(Fix::SetReason(Reason::Depend)) => transaction.depend.push(name),
+            Some(Fix::Remove) => transaction.remove.push(name),
+            Some(Fix::Hold(_)) | None => {}
+        }
+    }
+
+    if !transaction.is_empty() {
+        let status = pacman::commit(&transaction, config)?;
+        if !status.success() {
+            return Ok(status);
+        }
+    }
+
+    set_holds(differences, holds, config)
+}
+
+/// Holds and releases packages like toggling them does, writing the `IgnorePkg` include
+/// file once.
+fn set_holds(
+    differences: &[Difference],
+    holds: &mut Holds,
+    config: &Config,
+) -> eyre::Result<ExitStatus> {
+    let mut listed = ignore::read(&config.ignore_file);
+    let before = listed.clone();
+    for difference in differences {
+        let Some(Fix::Hold(held)) = difference.fix else {
+            continue;
+        };
+        holds.set(&difference.name, held)?;
+        if !held {
+            listed.retain(|name| *name != difference.name);
+        } else if config.persist_holds && !listed.contains(&difference.name) {
+            listed.push(difference.name.clone());
+        }
+    }
+
+    if listed == before {
+        return Ok(ExitStatus::default());
+    }
+    ignore::write(&config.ignore_file, &listed)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A system with the listed packages, none of them in a group.
+    #[derive(Default)]
+    pub(crate) struct FakeSystem {
+        pub(crate) installed: Vec<(&'static str, &'static str, Reason)>,
+        pub(crate) synced: HashMap<&'static str, &'static str>,
+        pub(crate) groups: HashMap<&'static str, Vec<String>>,
+        pub(crate) orphans: Vec<&'static str>,
+        pub(crate) required: Vec<&'static str>,
+    }
+
+    impl System for FakeSystem {
+        fn installed_packages(&self) -> Vec<(Package, Option<String>)> {
+            self.installed
+                .iter()
+                .map(|&(name, version, reason)| {
+                    let package = Package {
+                        name: name.to_owned(),
+                        version: version.to_owned(),
+                        reason: Some(reason),
+                        ..Default::default()
+                    };
+                    (package, Some(String::from("extra")))
+                })
+                .collect()
+        }
+
+        fn group_members(&self, group: &str) -> Option<Vec<String>> {
+            self.groups.get(group).cloned()
+        }
+
+        fn sync_version(&self, package_name: &str) -> Option<String> {
+            self.synced
+                .get(package_name)
+                .map(|version| (*version).to_owned())
+        }
+
+        fn orphans(&self) -> Vec<Package> {
+            self.installed_packages()
+                .into_iter()
+                .map(|(package, _)| package)
+                .filter(|package| self.orphans.contains(&package.name.as_str()))
+                .collect()
+        }
+
+        fn is_required(&self, package_name: &str) -> bool {
+            self.required.contains(&package_name)
+        }
+    }
+
+    fn summary(differences: &[Difference]) -> Vec<(String, &str, String)> {
+        differences
+            .iter()
+            .map(|difference| {
+                (
+                    difference.kind.to_string(),
+                    difference.name.as_str(),
+                    difference.fix_label(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_missing_then_extra_then_drifted() {
+        let state = toml::from_str::<DesiredState>(
+            r#"
+            groups = ["base-devel", "kde"]
+            dependencies = ["zlib", "vim"]
+            holds = ["linux"]
+
+            [packages]
+            extra = ["vim", "git"]
+
+            [versions]
+            git = "2.45.0-1"
+            "#,
+        )
+        .unwrap();
+        let system = FakeSystem {
+            installed: vec![
+                ("vim", "9.1-1", Reason::Explicit),
+                ("git", "2.44.0-1", Reason::Depend),
+                ("zlib", "1.3-1", Reason::Explicit),
+                ("htop", "3.3-1", Reason::Explicit),
+                ("libfoo", "1.0-1", Reason::Explicit),
+                ("leftover", "1.0-1", Reason::Depend),
+                ("needed", "1.0-1", Reason::Depend),
+            ],
+            synced: HashMap::from([("git", "2.45.0-1"), ("make", "4.4-1")]),
+            groups: HashMap::from([(
+                "base-devel",
+                vec![String::from("make"), String::from("gcc")],
+            )]),
+            orphans: vec!["leftover"],
+            required: vec!["libfoo", "needed"],
+        };
+        let mut holds = Holds::default();
+        holds.set("firefox", true).unwrap();
+
+        let differences = compare(&state, &system, &holds);
+        let expected = [
+            ("missing", "gcc", "by hand"),
+            ("missing", "kde", "by hand"),
+            ("missing", "make", "install"),
+            ("extra", "htop", "remove"),
+            ("extra", "leftover", "remove"),
+            // removing it would break the packages requiring it
+            ("extra", "libfoo", "mark as dependency"),
+            ("drifted", "firefox", "release"),
+            ("drifted", "git", "install synced version"),
+            ("drifted", "git", "mark as explicit"),
+            ("drifted", "linux", "hold"),
+            ("drifted", "zlib", "mark as dependency"),
+        ];
+        assert_eq!(
+            summary(&differences),
+            expected.map(|(kind, name, fix)| (kind.to_owned(), name, fix.to_owned()))
+        );
+    }
+
+    #[test]
+    fn leaves_unavailable_versions_to_fix_by_hand() {
+        let state = toml::from_str::<DesiredState>(
+            r#"
+            [packages]
+            extra = ["git"]
+
+            [versions]
+            git = "2.40.0-1"
+            "#,
+        )
+        .unwrap();
+        let system = FakeSystem {
+            installed: vec![("git", "2.44.0-1", Reason::Explicit)],
+            synced: HashMap::from([("git", "2.45.0-1")]),
+            ..Default::default()
+        };
+
+        let differences = compare(&state, &system, &Holds::default());
+        assert_eq!(
+            summary(&differences),
+            [(String::from("drifted"), "git", String::from("by hand"))]
+        );
+        assert_eq!(differences[0].detail, "at 2.44.0-1, wanted 2.40.0-1");
+    }
+}
@@ -0,0 +1,265 @@
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use color_eyre::eyre;
+
+use crate::cache::{self, Cache};
+use crate::config::ArchiveConfig;
+use crate::format;
+use crate::pacman::{Pacman, Signature};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+pub(crate) enum Origin {
+    Cache(PathBuf),
+    /// The URL of the package file.
+    Archive(String),
+}
+
+/// A version a package can be installed at.
+#[derive(Clone)]
+pub(crate) struct Candidate {
+    pub(crate) version: String,
+    /// Unix timestamp of the build for cached files, of the upload for archived ones.
+    pub(crate) date: Option<i64>,
+    pub(crate) signature: Signature,
+    pub(crate) origin: Origin,
+}
+
+impl Candidate {
+    /// What to pass to `pacman -U`.
+    pub(crate) fn target(&self) -> String {
+        match &self.origin {
+            Origin::Cache(path) => path.display().to_string(),
+            Origin::Archive(url) => url.clone(),
+        }
+    }
+
+    pub(crate) fn source(&self) -> &'static str {
+        match self.origin {
+            Origin::Cache(_) => "cache",
+            Origin::Archive(_) => "archive",
+        }
+    }
+}
+
+/// The versions of a package in the cache, verifying their signatures.
+pub(crate) fn cached(pacman: &Pacman, cache: &Cache, package_name: &str) -> Vec<Candidate> {
+    cache
+        .packages
+        .iter()
+        .filter(|package| package.name == package_name)
+        .map(|package| Candidate {
+            version: package.version.clone(),
+            date: pacman
+                .load_file(&package.path)
+                .ok()
+                .map(|package| package.build_date),
+            signature: pacman.verify_file(&package.path),
+            origin: Origin::Cache(package.path.clone()),
+        })
+        .collect()
+}
+
+/// Adds `candidates` to `versions`, newest first, preferring cached files over downloads.
+pub(crate) fn merge(versions: &mut Vec<Candidate>, candidates: Vec<Candidate>) {
+    versions.extend(candidates);
+    versions.sort_by(|a, b| {
+        alpm::vercmp(b.version.as_str(), a.version.as_str()).then_with(|| {
+            matches!(a.origin, Origin::Archive(_)).cmp(&matches!(b.origin, Origin::Archive(_)))
+        })
+    });
+    versions.dedup_by(|a, b| a.version == b.version);
+}
+
+/// Client for the directory listings of an Arch Linux Archive style mirror.
+#[derive(Clone)]
+pub(crate) struct Archive {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl Archive {
+    pub(crate) fn new(config: &ArchiveConfig) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+        Self {
+            url: config.url.trim_end_matches('/').to_owned(),
+            agent,
+        }
+    }
+
+    /// The archived versions of a package built for this machine.
+    pub(crate) fn versions(&self, package_name: &str) -> eyre::Result<Vec<Candidate>> {
+        let Some(first) = package_name.chars().next() else {
+            return Ok(Vec::new());
+        };
+        let dir = format!("{}/packages/{first}/{package_name}/", self.url);
+        let listing = match self.agent.get(&dir).call() {
+            Ok(response) => response.into_string()?,
+            Err(ureq::Error::Status(404, _)) => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(parse_index(&listing, &dir, package_name))
+    }
+}
+
+/// The package files of `package_name` in an archive directory listing at `dir`.
+fn parse_index(listing: &str, dir: &str, package_name: &str) -> Vec<Candidate> {
+    let links = listing
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once("href=\"")?;
+            let (href, rest) = rest.split_once('"')?;
+            // nginx lists the upload time after the link, e.g. `02-Jan-2023 10:12`
+            let date = rest.split_once("</a>").and_then(|(_, rest)| {
+                let mut fields = rest.split_whitespace();
+                let date = format!("{} {}", fields.next()?, fields.next()?);
+                NaiveDateTime::parse_from_str(&date, "%d-%b-%Y %H:%M").ok()
+            });
+            Some((href, date.map(|date| date.and_utc().timestamp())))
+        })
+        .collect::<Vec<_>>();
+
+    links
+        .iter()
+        .filter_map(|&(href, date)| {
+            let file_name = percent_decode(href);
+            let (name, version, arch) = cache::parse_file_name(&file_name)?;
+            if name != package_name || (arch != env::consts::ARCH && arch != "any") {
+                return None;
+            }
+            let signed = links
+                .iter()
+                .any(|(sig, _)| sig.strip_suffix(".sig") == Some(href));
+            Some(Candidate {
+                version,
+                date,
+                // pacman verifies it once it is downloaded
+                signature: if signed {
+                    Signature::Unverified
+                } else {
+                    Signature::Missing
+                },
+                origin: Origin::Archive(format!("{dir}{href}")),
+            })
+        })
+        .collect()
+}
+
+/// Decodes the `%XX` escapes of a link, e.g. the `:` of epochs.
+fn percent_decode(href: &str) -> String {
+    String::from_utf8_lossy(&format::decode_escapes(href, "%", 2, 16)).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(version: &str, origin: Origin) -> Candidate {
+        Candidate {
+            version: version.to_owned(),
+            date: None,
+            signature: Signature::Unverified,
+            origin,
+        }
+    }
+
+    fn cached(version: &str) -> Candidate {
+        let path = format!("/var/cache/pacman/pkg/foo-{version}-x86_64.pkg.tar.zst");
+        candidate(version, Origin::Cache(PathBuf::from(path)))
+    }
+
+    fn archived(version: &str) -> Candidate {
+        let url = format!(
+            "https://archive.archlinux.org/packages/f/foo/foo-{version}-x86_64.pkg.tar.zst"
+        );
+        candidate(version, Origin::Archive(url))
+    }
+
+    #[test]
+    fn merges_newest_first_preferring_the_cache() {
+        let mut versions = vec![cached("1.10-1"), cached("1.9-2")];
+        merge(
+            &mut versions,
+            vec![archived("1.9-2"), archived("1:0.1-1"), archived("1.2-1")],
+        );
+
+        let merged = versions
+            .iter()
+            .map(|candidate| (candidate.version.as_str(), candidate.source()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            merged,
+            [
+                ("1:0.1-1", "archive"),
+                ("1.10-1", "cache"),
+                ("1.9-2", "cache"),
+                ("1.2-1", "archive"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_epochs_in_links() {
+        assert_eq!(
+            percent_decode("foo-1%3A2.0-1-x86_64.pkg.tar.zst"),
+            "foo-1:2.0-1-x86_64.pkg.tar.zst"
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn parses_archive_index_pages() {
+        let arch = env::consts::ARCH;
+        let listing = format!(
+            r#"<html>
+<head><title>Index of /packages/f/foo/</title></head>
+<body>
+<h1>Index of /packages/f/foo/</h1><hr><pre><a href="../">../</a>
+<a href="foo-1%3A2.0-1-{arch}.pkg.tar.zst">foo-1:2.0-1-{arch}.pkg.tar.zst</a>        02-Jan-2023 10:12     1048576
+<a href="foo-1%3A2.0-1-{arch}.pkg.tar.zst.sig">foo-1:2.0-1-{arch}.pkg.tar.zst.sig</a>    02-Jan-2023 10:12         310
+<a href="foo-1.9-2-any.pkg.tar.xz">foo-1.9-2-any.pkg.tar.xz</a>                    15-Mar-2022 08:00      524288
+<a href="foo-1.9-1-not-an-arch.pkg.tar.xz">foo-1.9-1-not-an-arch.pkg.tar.xz</a>    15-Mar-2022 07:00      524288
+<a href="foo-docs-1.9-2-any.pkg.tar.xz">foo-docs-1.9-2-any.pkg.tar.xz</a>          15-Mar-2022 08:00       65536
+</pre><hr></body>
+</html>
+"#
+        );
+        let dir = "https://archive.archlinux.org/packages/f/foo/";
+
+        let candidates = parse_index(&listing, dir, "foo");
+
+        let parsed = candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.version.as_str(),
+                    candidate.date,
+                    matches!(candidate.signature, Signature::Unverified),
+                    candidate.target(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            [
+                (
+                    "1:2.0-1",
+                    Some(1672654320),
+                    true,
+                    format!("{dir}foo-1%3A2.0-1-{arch}.pkg.tar.zst")
+                ),
+                (
+                    "1.9-2",
+                    Some(1647331200),
+                    false,
+                    format!("{dir}foo-1.9-2-any.pkg.tar.xz")
+                ),
+            ]
+        );
+    }
+}
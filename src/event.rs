@@ -1,8 +1,71 @@
-use crate::pacman::Package;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::action::Action;
+use crate::cache::{Cache, Group};
+use crate::components::Pane;
+use crate::desired::Difference;
+use crate::downgrade::Candidate;
+use crate::integrity::Report;
+use crate::keyring::Key;
+use crate::makepkg::{Build, Review, Script};
+use crate::manifest::Change;
+use crate::mirrorlist::{Mirror, Speed};
+use crate::news::Feed;
+use crate::package_file::Entry;
+use crate::pacman::{Optdep, Package, PackageSignature, Reason};
+use crate::pacman_log::Log;
+use crate::pacnew::ConfigFile;
 
 pub(crate) enum Event {
     FoundPackages(Vec<Package>),
+    /// Found after the sync packages and appended to them.
+    FoundAurPackages(Vec<Package>),
     PackageSelected(Package),
     PackageInstalled(String),
     PackageRemoved(String),
+    PaneFocused(Pane),
+    ConfirmationRequested(String, Action),
+    InputRequested(String, String, Action),
+    Notified(String),
+    BuildStarted(String),
+    BuildLogged(String),
+    BuildReviewRequested(Build, Review),
+    BuildFinished(String),
+    ScriptsLoaded(String, Vec<Script>),
+    LogLoaded(Log),
+    CacheLoaded(Cache),
+    /// The cached files grouped by package.
+    CacheGrouped(Vec<Group>),
+    /// A package, its installed version and how many lookups for its versions are running.
+    VersionsRequested(String, String, usize),
+    VersionsFound(String, Vec<Candidate>),
+    ConfigFilesLoaded(Vec<ConfigFile>),
+    /// How many packages are going to be checked.
+    IntegrityCheckStarted(usize),
+    IntegrityChecked(Report),
+    IntegrityCheckCancelled,
+    ReasonsChanged(Vec<(String, Reason)>),
+    HoldsChanged(Vec<String>),
+    OptdependsLoaded(Package, Vec<Optdep>),
+    /// Installed packages only optional dependencies of others, with those others.
+    OptionalOnlyLoaded(Vec<(Package, Vec<String>)>),
+    /// Opens the file picker with the entries of a directory.
+    PackageFilesListed(PathBuf, Vec<Entry>),
+    PackageFileLoaded(PathBuf, Package),
+    SignatureLoaded(String, PackageSignature),
+    /// The keys in the pacman keyring and how many installed packages each packager built.
+    KeyringLoaded(Vec<Key>, HashMap<String, usize>),
+    MirrorsLoaded(Vec<Mirror>),
+    /// How many servers are going to be measured.
+    MirrorMeasurementStarted(usize),
+    MirrorMeasured(String, Result<Speed, String>),
+    MirrorMeasurementCancelled,
+    /// The news feed and the ids of the items read.
+    NewsLoaded(Feed, Vec<String>),
+    NewsRead(Vec<String>),
+    /// What importing the manifest at the path would change.
+    ManifestCompared(PathBuf, Vec<Change>),
+    /// How the system differs from the desired state file at the path.
+    DesiredStateCompared(PathBuf, Result<Vec<Difference>, String>),
 }
@@ -0,0 +1,135 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+use color_eyre::eyre;
+use serde::Serialize;
+
+use crate::pacman::Package;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    /// Aligned columns for reading in a terminal
+    #[default]
+    Table,
+    /// A JSON array of records
+    Json,
+    /// One JSON record per line
+    Jsonl,
+    /// Comma separated values
+    Csv,
+    /// Tab separated values
+    Tsv,
+}
+
+impl Format {
+    /// Picks the format matching the extension of `path`.
+    pub(crate) fn from_path(path: &Path) -> eyre::Result<Self> {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some("json") => Ok(Format::Json),
+            Some("jsonl") => Ok(Format::Jsonl),
+            Some("csv") => Ok(Format::Csv),
+            Some("tsv") => Ok(Format::Tsv),
+            _ => eyre::bail!(
+                "unsupported export file '{}', expected a .json, .jsonl, .csv or .tsv extension",
+                path.display()
+            ),
+        }
+    }
+}
+
+pub(crate) const PACKAGE_HEADER: [&str; 5] =
+    ["name", "source", "version", "installed", "description"];
+
+pub(crate) fn package_row(package: &Package) -> Vec<String> {
+    vec![
+        package.name.clone(),
+        package.source.clone(),
+        package.version.clone(),
+        package.installed_version.clone().unwrap_or_default(),
+        package.description.clone().unwrap_or_default(),
+    ]
+}
+
+/// Writes `records` in `format`, tabular formats use `header` and `row` for their columns
+/// while JSON formats serialize the whole records.
+pub(crate) fn write<T: Serialize>(
+    writer: impl Write,
+    format: Format,
+    records: &[T],
+    header: &[&str],
+    row: impl Fn(&T) -> Vec<String>,
+) -> eyre::Result<()> {
+    let mut writer = BufWriter::new(writer);
+
+    match format {
+        Format::Table => {
+            let rows = records.iter().map(row).collect::<Vec<_>>();
+            write_table(&mut writer, header, &rows)?;
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut writer, records)?;
+            writeln!(writer)?;
+        }
+        Format::Jsonl => {
+            for record in records {
+                serde_json::to_writer(&mut writer, record)?;
+                writeln!(writer)?;
+            }
+        }
+        Format::Csv | Format::Tsv => {
+            let delimiter = if format == Format::Csv { b',' } else { b'\t' };
+            let mut csv = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(&mut writer);
+            csv.write_record(header)?;
+            for record in records {
+                csv.write_record(row(record))?;
+            }
+            csv.flush()?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn write_stdout<T: Serialize>(
+    format: Format,
+    records: &[T],
+    header: &[&str],
+    row: impl Fn(&T) -> Vec<String>,
+) -> eyre::Result<()> {
+    write(io::stdout().lock(), format, records, header, row)
+}
+
+/// Exports `packages` to `path` in the format matching its extension.
+pub(crate) fn write_packages(path: &Path, packages: &[Package]) -> eyre::Result<()> {
+    let format = Format::from_path(path)?;
+    let file = File::create(path)?;
+    write(file, format, packages, &PACKAGE_HEADER, package_row)
+}
+
+fn write_table(writer: &mut impl Write, header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths = header.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = header.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    for row in [&header].into_iter().chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Local};
+
+const SIZE_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+pub(crate) fn size(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < SIZE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} {}", SIZE_UNITS[unit])
+    } else {
+        format!("{value:.2} {}", SIZE_UNITS[unit])
+    }
+}
+
+pub(crate) fn date(timestamp: i64) -> String {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(date) => date
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => String::from("-"),
+    }
+}
+
+/// Decodes the escapes made of `prefix` and a byte written with `width` digits in `radix`,
+/// keeping anything that does not decode as it is.
+pub(crate) fn decode_escapes(value: &str, prefix: &str, width: usize, radix: u32) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while !rest.is_empty() {
+        let escaped = rest
+            .strip_prefix(prefix.as_bytes())
+            .and_then(|tail| Some((tail.get(..width)?, &tail[width..])))
+            .and_then(|(digits, tail)| {
+                let digits = std::str::from_utf8(digits).ok()?;
+                Some((u8::from_str_radix(digits, radix).ok()?, tail))
+            });
+        match escaped {
+            Some((decoded, tail)) => {
+                bytes.push(decoded);
+                rest = tail;
+            }
+            None => {
+                bytes.push(rest[0]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    bytes
+}
@@ -0,0 +1,125 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+const KEYWORDS: [&str; 19] = [
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+    "in", "function", "return", "local", "export", "declare", "select",
+];
+
+const COMMENT: Style = Style::new().fg(Color::DarkGray);
+const STRING: Style = Style::new().fg(Color::Green);
+const VARIABLE: Style = Style::new().fg(Color::Magenta);
+const KEYWORD: Style = Style::new().fg(Color::Yellow);
+const ASSIGNMENT: Style = Style::new().fg(Color::Cyan);
+
+fn is_word(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_'
+}
+
+/// Highlights a line of shell such as a PKGBUILD or an install scriptlet.
+///
+/// Lines are highlighted on their own, so strings spanning lines are only coloured on their
+/// first line.
+pub(crate) fn shell(line: &str) -> Vec<Span<'static>> {
+    let chars = line.char_indices().collect::<Vec<_>>();
+    let mut spans = Vec::new();
+    let mut plain = 0;
+    let mut i = 0;
+
+    let push = |spans: &mut Vec<Span<'static>>, plain: &mut usize, start, end, style| {
+        if *plain < start {
+            spans.push(Span::raw(line[*plain..start].to_owned()));
+        }
+        spans.push(Span::styled(line[start..end].to_owned(), style));
+        *plain = end;
+    };
+    let offset = |i: usize| chars.get(i).map_or(line.len(), |(offset, _)| *offset);
+
+    while i < chars.len() {
+        let (start, char) = chars[i];
+        let at_word_start = i == 0 || !is_word(chars[i - 1].1);
+
+        match char {
+            '#' if i == 0 || chars[i - 1].1.is_whitespace() => {
+                push(&mut spans, &mut plain, start, line.len(), COMMENT);
+                i = chars.len();
+            }
+            '\'' | '"' => {
+                let mut end = i + 1;
+                while end < chars.len() && chars[end].1 != char {
+                    // only double quotes have escapes
+                    if char == '"' && chars[end].1 == '\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                let end = (end + 1).min(chars.len());
+                push(&mut spans, &mut plain, start, offset(end), STRING);
+                i = end;
+            }
+            '$' if i + 1 < chars.len() => {
+                let next = chars[i + 1].1;
+                let end = if next == '{' {
+                    let close = (i + 2..chars.len()).find(|&j| chars[j].1 == '}');
+                    close.map_or(chars.len(), |close| close + 1)
+                } else if is_word(next) {
+                    (i + 1..chars.len())
+                        .find(|&j| !is_word(chars[j].1))
+                        .unwrap_or(chars.len())
+                } else if "@*#?!$-".contains(next) {
+                    i + 2
+                } else {
+                    i + 1
+                };
+                if end > i + 1 {
+                    push(&mut spans, &mut plain, start, offset(end), VARIABLE);
+                }
+                i = end;
+            }
+            _ if at_word_start && is_word(char) => {
+                let end = (i..chars.len())
+                    .find(|&j| !is_word(chars[j].1))
+                    .unwrap_or(chars.len());
+                let word = &line[start..offset(end)];
+                let first_word = line[..start].trim().is_empty();
+                if KEYWORDS.contains(&word) {
+                    push(&mut spans, &mut plain, start, offset(end), KEYWORD);
+                } else if first_word && chars.get(end).is_some_and(|(_, c)| *c == '=') {
+                    push(&mut spans, &mut plain, start, offset(end), ASSIGNMENT);
+                } else if first_word && line[offset(end)..].trim_start().starts_with("()") {
+                    push(
+                        &mut spans,
+                        &mut plain,
+                        start,
+                        offset(end),
+                        Style::new().add_modifier(Modifier::BOLD),
+                    );
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if plain < line.len() {
+        spans.push(Span::raw(line[plain..].to_owned()));
+    }
+
+    spans
+}
+
+/// Colours a line of a unified diff.
+pub(crate) fn diff(line: &str) -> Line<'static> {
+    let color = if line.starts_with("+++") || line.starts_with("---") {
+        Color::Reset
+    } else if line.starts_with('+') {
+        Color::Green
+    } else if line.starts_with('-') {
+        Color::Red
+    } else if line.starts_with("@@") {
+        Color::Cyan
+    } else {
+        Color::Reset
+    };
+    Line::styled(line.to_owned(), Style::new().fg(color))
+}
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use crate::cache::Cache;
+use crate::pacman_log::{Log, Operation};
+
+/// How a single operation of a transaction is undone.
+#[derive(Clone)]
+pub(crate) enum Step {
+    /// Install the version the package had before from the cache.
+    Install(String, PathBuf),
+    /// Install a package the transaction removed from the cache. The log does not record
+    /// why it was installed, so it comes back as explicitly installed.
+    Reinstall(String, PathBuf),
+    /// Remove a package the transaction installed.
+    Remove,
+    /// The version the package had before is not in the cache.
+    Missing(String),
+}
+
+/// Undoing a transaction, in the order the operations were logged.
+#[derive(Clone, Default)]
+pub(crate) struct Revert {
+    pub(crate) transaction: usize,
+    pub(crate) steps: Vec<(String, Step)>,
+}
+
+impl Revert {
+    pub(crate) fn new(log: &Log, transaction: usize, cache: &Cache) -> Self {
+        let mut steps = Vec::new();
+        let previous =
+            |package: &str, version: &str, removed: bool| match cache.find(package, version) {
+                Some(cached) if removed => Step::Reinstall(version.to_owned(), cached.path.clone()),
+                Some(cached) => Step::Install(version.to_owned(), cached.path.clone()),
+                None => Step::Missing(version.to_owned()),
+            };
+
+        for &i in &log.transactions[transaction].entries {
+            let step = match &log.entries[i].operation {
+                Operation::Installed { package, .. } => (package.clone(), Step::Remove),
+                Operation::Upgraded { package, from, .. }
+                | Operation::Downgraded { package, from, .. } => {
+                    (package.clone(), previous(package, from, false))
+                }
+                Operation::Removed { package, version } => {
+                    (package.clone(), previous(package, version, true))
+                }
+                _ => continue,
+            };
+            steps.push(step);
+        }
+
+        Self { transaction, steps }
+    }
+
+    pub(crate) fn files(&self) -> Vec<PathBuf> {
+        self.steps
+            .iter()
+            .filter_map(|(_, step)| match step {
+                Step::Install(_, path) | Step::Reinstall(_, path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn removals(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter(|(_, step)| matches!(step, Step::Remove))
+            .map(|(package, _)| package.clone())
+            .collect()
+    }
+
+    pub(crate) fn reinstalls(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|(_, step)| matches!(step, Step::Reinstall(..)))
+            .count()
+    }
+
+    pub(crate) fn missing(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|(_, step)| matches!(step, Step::Missing(_)))
+            .count()
+    }
+}
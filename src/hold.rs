@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+
+use crate::{config, ignore};
+
+const HOLD_FILE: &str = "holds";
+
+/// Packages kept at their installed version, left out of upgrades.
+#[derive(Default)]
+pub(crate) struct Holds {
+    packages: BTreeSet<String>,
+    /// One package per line, `$XDG_STATE_HOME/ptu/holds` unless there is no home.
+    path: Option<PathBuf>,
+}
+
+impl Holds {
+    /// Reads the hold list along with the packages held through the `IgnorePkg` include
+    /// file.
+    pub(crate) fn load(ignore_file: &Path) -> Self {
+        let path = config::state_dir().map(|dir| dir.join(HOLD_FILE));
+        let mut packages = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_owned)
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+        packages.extend(ignore::read(ignore_file));
+
+        Self { packages, path }
+    }
+
+    pub(crate) fn contains(&self, package_name: &str) -> bool {
+        self.packages.contains(package_name)
+    }
+
+    pub(crate) fn names(&self) -> Vec<String> {
+        self.packages.iter().cloned().collect()
+    }
+
+    /// Holds or releases a package and saves the list.
+    pub(crate) fn set(&mut self, package_name: &str, held: bool) -> eyre::Result<()> {
+        if held {
+            self.packages.insert(package_name.to_owned());
+        } else {
+            self.packages.remove(package_name);
+        }
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut content = self.names().join("\n");
+        content.push('\n');
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+
+use color_eyre::eyre;
+
+pub(crate) const IGNORE_FILE: &str = "/etc/pacman.d/ptu-ignore.conf";
+pub(crate) const PACMAN_CONF: &str = "/etc/pacman.conf";
+
+/// The packages listed in the managed `IgnorePkg` include file.
+pub(crate) fn read(path: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "IgnorePkg").then_some(value)
+        })
+        .flat_map(str::split_whitespace)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Rewrites the include file with `packages`, it is owned by root.
+pub(crate) fn write(path: &Path, packages: &[String]) -> eyre::Result<ExitStatus> {
+    let mut content = format!(
+        "# managed by ptu, include it in the [options] section of {PACMAN_CONF}:\n\
+         # Include = {}\n",
+        path.display()
+    );
+    if !packages.is_empty() {
+        content.push_str(&format!("IgnorePkg = {}\n", packages.join(" ")));
+    }
+
+    let mut child = Command::new("sudo")
+        .arg("tee")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    // closes stdin before waiting, so tee is not left running when the write fails
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(content.as_bytes()),
+        None => Ok(()),
+    };
+    let status = child.wait()?;
+    written?;
+    Ok(status)
+}
+
+/// Adds a package to the include file.
+pub(crate) fn add(path: &Path, package_name: &str) -> eyre::Result<ExitStatus> {
+    let mut packages = read(path);
+    if !packages.iter().any(|package| package == package_name) {
+        packages.push(package_name.to_owned());
+    }
+    write(path, &packages)
+}
+
+/// Removes a package from the include file.
+pub(crate) fn remove(path: &Path, package_name: &str) -> eyre::Result<ExitStatus> {
+    let mut packages = read(path);
+    packages.retain(|package| package != package_name);
+    write(path, &packages)
+}
+
+/// Whether pacman.conf includes the file, otherwise pacman does not know about it.
+pub(crate) fn is_included(path: &Path) -> bool {
+    let Ok(content) = fs::read_to_string(PACMAN_CONF) else {
+        return false;
+    };
+
+    content.lines().any(|line| {
+        line.split_once('=')
+            .is_some_and(|(key, value)| key.trim() == "Include" && Path::new(value.trim()) == path)
+    })
+}
+
+/// How to make pacman use the include file.
+pub(crate) fn include_hint(path: &Path) -> String {
+    format!(
+        "add 'Include = {}' to the [options] section of {PACMAN_CONF}",
+        path.display()
+    )
+}
@@ -0,0 +1,330 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read as _};
+use std::os::unix::ffi::OsStringExt as _;
+use std::os::unix::fs::{MetadataExt as _, PermissionsExt as _};
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+use flate2::read::GzDecoder;
+use sha2::{Digest as _, Sha256};
+
+use crate::format;
+use crate::pacman::Pacman;
+
+/// How a file differs from what its package installed.
+#[derive(Clone)]
+pub(crate) enum Problem {
+    Missing,
+    Type,
+    /// Expected and actual permissions.
+    Mode(u32, u32),
+    Mtime,
+    /// Expected and actual size in bytes.
+    Size(u64, u64),
+    Checksum,
+    Link,
+    Unreadable(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Missing => f.write_str("missing"),
+            Problem::Type => f.write_str("type mismatch"),
+            Problem::Mode(expected, actual) => {
+                write!(f, "permissions {actual:o}, expected {expected:o}")
+            }
+            Problem::Mtime => f.write_str("modification time mismatch"),
+            Problem::Size(expected, actual) => write!(f, "size {actual}, expected {expected}"),
+            Problem::Checksum => f.write_str("checksum mismatch"),
+            Problem::Link => f.write_str("symlink target mismatch"),
+            Problem::Unreadable(error) => write!(f, "cannot read: {error}"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FileReport {
+    pub(crate) path: PathBuf,
+    pub(crate) problems: Vec<Problem>,
+}
+
+impl FileReport {
+    pub(crate) fn is_missing(&self) -> bool {
+        matches!(self.problems[..], [Problem::Missing])
+    }
+}
+
+/// The files of a package that differ from what it installed, like `pacman -Qkk`.
+#[derive(Clone)]
+pub(crate) struct Report {
+    pub(crate) package: String,
+    /// How many files were checked.
+    pub(crate) checked: usize,
+    /// How many files only root can read, left unchecked.
+    pub(crate) unchecked: usize,
+    pub(crate) files: Vec<FileReport>,
+}
+
+impl Report {
+    /// A package whose files could not be checked at all.
+    pub(crate) fn failed(package: String, error: eyre::Report) -> Self {
+        Self {
+            package,
+            checked: 0,
+            unchecked: 0,
+            files: vec![FileReport {
+                path: PathBuf::from("mtree"),
+                problems: vec![Problem::Unreadable(error.to_string())],
+            }],
+        }
+    }
+}
+
+/// A file as recorded in the mtree of a package.
+#[derive(Default)]
+struct Entry {
+    path: PathBuf,
+    kind: String,
+    mode: Option<u32>,
+    size: Option<u64>,
+    /// Seconds, pacman ignores the fraction.
+    time: Option<i64>,
+    sha256: Option<String>,
+    link: Option<PathBuf>,
+}
+
+/// Decodes the `\ooo` octal escapes mtree uses for spaces and other special characters.
+fn unescape(value: &str) -> OsString {
+    OsString::from_vec(format::decode_escapes(value, "\\", 3, 8))
+}
+
+fn parse_mtree(content: &str, root: &Path) -> Vec<Entry> {
+    let mut defaults = HashMap::new();
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(first) = fields.next() else {
+            continue;
+        };
+        match first {
+            "/set" => {
+                defaults.extend(fields.filter_map(|field| field.split_once('=')));
+                continue;
+            }
+            "/unset" => {
+                for key in fields {
+                    defaults.remove(key);
+                }
+                continue;
+            }
+            _ if first.starts_with('#') => continue,
+            _ => {}
+        }
+
+        // `.PKGINFO`, `.INSTALL` and the like are not installed
+        let Some(relative) = first.strip_prefix("./") else {
+            continue;
+        };
+        if relative.starts_with('.') {
+            continue;
+        }
+
+        let mut keywords = defaults.clone();
+        keywords.extend(fields.filter_map(|field| field.split_once('=')));
+        entries.push(Entry {
+            path: root.join(unescape(relative)),
+            kind: keywords.get("type").copied().unwrap_or("file").to_owned(),
+            mode: keywords
+                .get("mode")
+                .and_then(|mode| u32::from_str_radix(mode, 8).ok()),
+            size: keywords.get("size").and_then(|size| size.parse().ok()),
+            time: keywords
+                .get("time")
+                .and_then(|time| time.split('.').next())
+                .and_then(|time| time.parse().ok()),
+            sha256: keywords
+                .get("sha256digest")
+                .map(|digest| digest.to_string()),
+            link: keywords
+                .get("link")
+                .map(|link| PathBuf::from(unescape(link))),
+        });
+    }
+
+    entries
+}
+
+/// Compares a file with its mtree entry, leaving the checksum for later.
+fn check_entry(entry: &Entry, backup: bool) -> io::Result<Vec<Problem>> {
+    let metadata = match fs::symlink_metadata(&entry.path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![Problem::Missing]),
+        Err(error) => return Err(error),
+    };
+
+    let file_type = metadata.file_type();
+    let type_matches = match entry.kind.as_str() {
+        "dir" => file_type.is_dir(),
+        "link" => file_type.is_symlink(),
+        _ => file_type.is_file(),
+    };
+    if !type_matches {
+        return Ok(vec![Problem::Type]);
+    }
+
+    let mut problems = Vec::new();
+    let mode = metadata.permissions().mode() & 0o7777;
+    if !file_type.is_symlink() && entry.mode.is_some_and(|expected| expected != mode) {
+        problems.push(Problem::Mode(entry.mode.unwrap_or_default(), mode));
+    }
+    if let Some(expected) = &entry.link {
+        if fs::read_link(&entry.path).ok().as_ref() != Some(expected) {
+            problems.push(Problem::Link);
+        }
+    }
+    // configuration files are expected to change
+    if file_type.is_file() && !backup {
+        if entry.time.is_some_and(|time| time != metadata.mtime()) {
+            problems.push(Problem::Mtime);
+        }
+        if let Some(size) = entry.size.filter(|&size| size != metadata.len()) {
+            problems.push(Problem::Size(size, metadata.len()));
+        }
+    }
+    Ok(problems)
+}
+
+/// The hex encoded SHA-256 digest of a file.
+fn sha256sum(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Verifies the installed files of a package against the mtree in the local database.
+pub(crate) fn check(
+    pacman: &Pacman,
+    package: &str,
+    backup: &HashSet<PathBuf>,
+) -> eyre::Result<Report> {
+    let dir = pacman
+        .local_dir(package)
+        .ok_or_else(|| eyre::eyre!("{package} is not installed"))?;
+    let mut content = String::new();
+    GzDecoder::new(File::open(dir.join("mtree"))?).read_to_string(&mut content)?;
+    let entries = parse_mtree(&content, Path::new(pacman.root()));
+
+    let mut files = Vec::new();
+    let mut unchanged = Vec::new();
+    let mut unchecked = 0;
+    for entry in &entries {
+        let problems = match check_entry(entry, backup.contains(&entry.path)) {
+            Ok(problems) => problems,
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                unchecked += 1;
+                continue;
+            }
+            Err(error) => vec![Problem::Unreadable(error.to_string())],
+        };
+        if !problems.is_empty() {
+            files.push(FileReport {
+                path: entry.path.clone(),
+                problems,
+            });
+        } else if entry.sha256.is_some() && !backup.contains(&entry.path) {
+            unchanged.push(entry);
+        }
+    }
+
+    // only files that look unchanged are worth reading
+    for entry in unchanged {
+        let problem = match sha256sum(&entry.path) {
+            Ok(digest) if Some(&digest) == entry.sha256.as_ref() => continue,
+            Ok(_) => Problem::Checksum,
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                unchecked += 1;
+                continue;
+            }
+            Err(error) => Problem::Unreadable(error.to_string()),
+        };
+        files.push(FileReport {
+            path: entry.path.clone(),
+            problems: vec![problem],
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(Report {
+        package: package.to_owned(),
+        checked: entries.len() - unchecked,
+        unchecked,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MTREE: &str = r"#mtree
+/set type=file uid=0 gid=0 mode=644
+./.BUILDINFO time=1717171717.0 size=4981 sha256digest=aaaa
+./.PKGINFO time=1717171717.0 size=1024 sha256digest=bbbb
+./etc time=1717171717.0 mode=755 type=dir
+./etc/foo.conf time=1717171717.123456789 size=42 sha256digest=5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03
+./usr/bin/foo time=1717171717.0 mode=755 size=1024 sha256digest=cccc
+./usr/lib/libfoo.so time=1717171717.0 mode=777 type=link link=libfoo.so.1
+/unset mode
+./usr/share/foo/with\040space\134back time=1717171717.0 size=7 sha256digest=dddd
+";
+
+    #[test]
+    fn parses_mtree_entries() {
+        let entries = parse_mtree(MTREE, Path::new("/"));
+
+        let paths = entries
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/etc"),
+                PathBuf::from("/etc/foo.conf"),
+                PathBuf::from("/usr/bin/foo"),
+                PathBuf::from("/usr/lib/libfoo.so"),
+                PathBuf::from("/usr/share/foo/with space\\back"),
+            ]
+        );
+
+        let dir = &entries[0];
+        assert_eq!(dir.kind, "dir");
+        assert_eq!(dir.mode, Some(0o755));
+        assert_eq!(dir.size, None);
+
+        let config = &entries[1];
+        assert_eq!(config.kind, "file");
+        assert_eq!(config.mode, Some(0o644));
+        assert_eq!(config.size, Some(42));
+        assert_eq!(config.time, Some(1717171717));
+        assert_eq!(
+            config.sha256.as_deref(),
+            Some("5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03")
+        );
+
+        let link = &entries[3];
+        assert_eq!(link.kind, "link");
+        assert_eq!(link.link, Some(PathBuf::from("libfoo.so.1")));
+        assert_eq!(link.sha256, None);
+
+        // unset after the link, so only the defaults without it are left
+        let escaped = &entries[4];
+        assert_eq!(escaped.mode, None);
+        assert_eq!(escaped.size, Some(7));
+    }
+}
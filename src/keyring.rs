@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use alpm::SigLevel;
+use chrono::Utc;
+use color_eyre::eyre;
+
+use crate::format;
+
+/// The keyring pacman verifies signatures with.
+pub(crate) const GPG_DIR: &str = "/etc/pacman.d/gnupg";
+
+/// How far a key is trusted, the validity and owner trust codes of gpg.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trust {
+    Unknown,
+    Invalid,
+    Disabled,
+    Revoked,
+    Expired,
+    Undefined,
+    Never,
+    Marginal,
+    Full,
+    Ultimate,
+}
+
+impl Trust {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "i" => Trust::Invalid,
+            "d" => Trust::Disabled,
+            "r" => Trust::Revoked,
+            "e" => Trust::Expired,
+            "q" => Trust::Undefined,
+            "n" => Trust::Never,
+            "m" => Trust::Marginal,
+            "f" => Trust::Full,
+            "u" => Trust::Ultimate,
+            _ => Trust::Unknown,
+        }
+    }
+
+    /// Whether pacman accepts signatures made with the key.
+    pub(crate) fn is_trusted(self) -> bool {
+        matches!(self, Trust::Marginal | Trust::Full | Trust::Ultimate)
+    }
+}
+
+impl fmt::Display for Trust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trust::Unknown => f.write_str("unknown"),
+            Trust::Invalid => f.write_str("invalid"),
+            Trust::Disabled => f.write_str("disabled"),
+            Trust::Revoked => f.write_str("revoked"),
+            Trust::Expired => f.write_str("expired"),
+            Trust::Undefined => f.write_str("undefined"),
+            Trust::Never => f.write_str("never"),
+            Trust::Marginal => f.write_str("marginal"),
+            Trust::Full => f.write_str("full"),
+            Trust::Ultimate => f.write_str("ultimate"),
+        }
+    }
+}
+
+/// A public key in the keyring.
+#[derive(Clone)]
+pub(crate) struct Key {
+    pub(crate) fingerprint: String,
+    /// User ids like `Name <email>`, the primary one first.
+    pub(crate) uids: Vec<String>,
+    pub(crate) validity: Trust,
+    pub(crate) owner_trust: Trust,
+    /// Unix timestamp.
+    pub(crate) created: Option<i64>,
+    /// Unix timestamp, `None` if the key never expires.
+    pub(crate) expires: Option<i64>,
+}
+
+impl Key {
+    /// The long key id signatures refer to.
+    pub(crate) fn key_id(&self) -> &str {
+        let start = self.fingerprint.len().saturating_sub(16);
+        &self.fingerprint[start..]
+    }
+
+    /// Whether `key_id`, long or a full fingerprint, names this key.
+    pub(crate) fn matches(&self, key_id: &str) -> bool {
+        !key_id.is_empty()
+            && self
+                .fingerprint
+                .to_ascii_uppercase()
+                .ends_with(&key_id.to_ascii_uppercase())
+    }
+
+    pub(crate) fn is_expired(&self) -> bool {
+        self.validity == Trust::Expired
+            || self
+                .expires
+                .is_some_and(|expires| expires < Utc::now().timestamp())
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        self.uids.first().map_or("", String::as_str)
+    }
+
+    pub(crate) fn emails(&self) -> impl Iterator<Item = &str> {
+        self.uids.iter().filter_map(|uid| email(uid))
+    }
+
+    /// The packagers among `packagers` whose email is on the key.
+    pub(crate) fn packagers<'a>(
+        &self,
+        packagers: &'a HashMap<String, usize>,
+    ) -> Vec<(&'a str, usize)> {
+        let mut matching = packagers
+            .iter()
+            .filter(|(packager, _)| {
+                email(packager).is_some_and(|packager| {
+                    self.emails()
+                        .any(|email| email.eq_ignore_ascii_case(packager))
+                })
+            })
+            .map(|(packager, count)| (packager.as_str(), *count))
+            .collect::<Vec<_>>();
+        matching.sort();
+        matching
+    }
+}
+
+/// The email address in a `Name <email>` user id or packager field.
+pub(crate) fn email(user: &str) -> Option<&str> {
+    let (_, rest) = user.split_once('<')?;
+    let (email, _) = rest.split_once('>')?;
+    Some(email)
+}
+
+/// Decodes the `\xNN` escapes gpg uses for colons and other special characters.
+fn unescape(value: &str) -> String {
+    String::from_utf8_lossy(&format::decode_escapes(value, "\\x", 2, 16)).into_owned()
+}
+
+fn timestamp(field: Option<&str>) -> Option<i64> {
+    field
+        .filter(|field| !field.is_empty())
+        .and_then(|field| field.parse().ok())
+}
+
+/// Parses `gpg --with-colons --fixed-list-mode --list-keys`.
+fn parse(output: &str) -> Vec<Key> {
+    let mut keys: Vec<Key> = Vec::new();
+    // fingerprints and user ids after a subkey belong to the subkey
+    let mut in_subkey = false;
+
+    for line in output.lines() {
+        let fields = line.split(':').collect::<Vec<_>>();
+        match fields[0] {
+            "pub" => {
+                in_subkey = false;
+                keys.push(Key {
+                    fingerprint: String::new(),
+                    uids: Vec::new(),
+                    validity: Trust::from_code(fields.get(1).copied().unwrap_or_default()),
+                    owner_trust: Trust::from_code(fields.get(8).copied().unwrap_or_default()),
+                    created: timestamp(fields.get(5).copied()),
+                    expires: timestamp(fields.get(6).copied()),
+                });
+            }
+            "sub" => in_subkey = true,
+            "fpr" if !in_subkey => {
+                if let (Some(key), Some(fingerprint)) = (keys.last_mut(), fields.get(9)) {
+                    key.fingerprint = (*fingerprint).to_owned();
+                }
+            }
+            "uid" => {
+                if let (Some(key), Some(uid)) = (keys.last_mut(), fields.get(9)) {
+                    key.uids.push(unescape(uid));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    keys
+}
+
+/// Lists the keys in the keyring at `gpg_dir`, which is usually only writable by root.
+pub(crate) fn list(gpg_dir: &Path) -> eyre::Result<Vec<Key>> {
+    let output = Command::new("gpg")
+        .arg("--homedir")
+        .arg(gpg_dir)
+        .args([
+            "--no-permission-warning",
+            "--lock-never",
+            "--no-auto-check-trustdb",
+            "--with-colons",
+            "--fixed-list-mode",
+            "--list-keys",
+        ])
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        eyre::bail!("gpg could not list the keys in {}", gpg_dir.display());
+    }
+
+    Ok(parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// The signature checks pacman.conf asks for, by default and for each repository.
+pub(crate) struct SigLevels {
+    default: SigLevel,
+    repos: HashMap<String, SigLevel>,
+}
+
+impl SigLevels {
+    /// Reads `SigLevel` from pacman.conf and the files it includes. Includes are followed
+    /// like pacman does, but glob patterns in them are not expanded.
+    pub(crate) fn read(path: &Path) -> Self {
+        // what pacman uses when pacman.conf does not say
+        let mut levels = Self {
+            default: SigLevel::PACKAGE
+                | SigLevel::PACKAGE_OPTIONAL
+                | SigLevel::DATABASE
+                | SigLevel::DATABASE_OPTIONAL,
+            repos: HashMap::new(),
+        };
+        levels.read_file(path, &mut String::new(), 0);
+        levels
+    }
+
+    /// Reads one file, an included one continuing in the section it is included from.
+    fn read_file(&mut self, path: &Path, section: &mut String, depth: usize) {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        for line in content.lines().map(str::trim) {
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                name.clone_into(section);
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                // pacman gives up on deeper includes as well
+                "Include" if depth < MAX_INCLUDE_DEPTH => {
+                    self.read_file(Path::new(value.trim()), section, depth + 1);
+                }
+                "SigLevel" if section == "options" => {
+                    self.default = apply(self.default, value);
+                }
+                "SigLevel" => {
+                    let level = self.repos.get(section).copied().unwrap_or(self.default);
+                    self.repos.insert(section.clone(), apply(level, value));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn default(&self) -> SigLevel {
+        self.default
+    }
+
+    pub(crate) fn repo(&self, name: &str) -> SigLevel {
+        self.repos
+            .get(name)
+            .copied()
+            .unwrap_or(SigLevel::USE_DEFAULT)
+    }
+}
+
+/// Applies `SigLevel` options like `Required DatabaseOptional` to `level`.
+fn apply(mut level: SigLevel, options: &str) -> SigLevel {
+    let package = [
+        SigLevel::PACKAGE,
+        SigLevel::PACKAGE_OPTIONAL,
+        SigLevel::PACKAGE_MARGINAL_OK | SigLevel::PACKAGE_UNKNOWN_OK,
+    ];
+    let database = [
+        SigLevel::DATABASE,
+        SigLevel::DATABASE_OPTIONAL,
+        SigLevel::DATABASE_MARGINAL_OK | SigLevel::DATABASE_UNKNOWN_OK,
+    ];
+
+    for option in options.split_whitespace() {
+        let (targets, option) = if let Some(option) = option.strip_prefix("Package") {
+            (vec![package], option)
+        } else if let Some(option) = option.strip_prefix("Database") {
+            (vec![database], option)
+        } else {
+            (vec![package, database], option)
+        };
+        for [required, optional, untrusted] in targets {
+            match option {
+                "Never" => level.remove(required),
+                "Optional" => level.insert(required | optional),
+                "Required" => {
+                    level.insert(required);
+                    level.remove(optional);
+                }
+                "TrustedOnly" => level.remove(untrusted),
+                "TrustAll" => level.insert(untrusted),
+                _ => {}
+            }
+        }
+    }
+
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: &str = "\
+tru::1:1700000000:0:3:1:5
+pub:e:4096:1:1111111111111111:1500000000:1600000000::f:::scSC::::::23::0:
+fpr:::::::::AAAAAAAAAAAAAAAAAAAAAAAA1111111111111111:
+uid:e::::1500000000::0000::Jane Doe <jane@archlinux.org>::::::::::0:
+sub:e:4096:1:2222222222222222:1500000000:1600000000:::::e::::::23:
+fpr:::::::::BBBBBBBBBBBBBBBBBBBBBBBB2222222222222222:
+pub:m:4096:1:3333333333333333:1500000000:::m:::scSC::::::23::0:
+fpr:::::::::CCCCCCCCCCCCCCCCCCCCCCCC3333333333333333:
+uid:m::::1500000000::0000::John Roe \\x3a builds <john@archlinux.org>::::::::::0:
+uid:m::::1500000000::0000::John Roe <john@example.org>::::::::::0:
+";
+
+    #[test]
+    fn parses_keys_with_their_trust_and_expiry() {
+        let keys = parse(KEYS);
+        assert_eq!(keys.len(), 2);
+
+        let jane = &keys[0];
+        assert_eq!(jane.fingerprint, "AAAAAAAAAAAAAAAAAAAAAAAA1111111111111111");
+        assert_eq!(jane.key_id(), "1111111111111111");
+        assert!(jane.validity == Trust::Expired);
+        assert!(jane.owner_trust == Trust::Full);
+        assert_eq!(jane.expires, Some(1_600_000_000));
+        assert!(jane.is_expired());
+
+        let john = &keys[1];
+        assert!(john.validity == Trust::Marginal && john.validity.is_trusted());
+        assert_eq!(john.expires, None);
+        assert!(!john.is_expired());
+        assert_eq!(john.name(), "John Roe : builds <john@archlinux.org>");
+        assert!(john.matches("cccccccccccccccccccccccc3333333333333333"));
+        assert!(!john.matches("2222222222222222"));
+    }
+
+    #[test]
+    fn matches_packagers_by_email() {
+        let keys = parse(KEYS);
+        let packagers = HashMap::from([
+            (String::from("John Roe <JOHN@archlinux.org>"), 3),
+            (String::from("John Roe <john@example.org>"), 1),
+            (String::from("Jane Doe <jane@archlinux.org>"), 2),
+            (String::from("nobody"), 5),
+        ]);
+        assert_eq!(
+            keys[1].packagers(&packagers),
+            [
+                ("John Roe <JOHN@archlinux.org>", 3),
+                ("John Roe <john@example.org>", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_sig_levels_through_includes() {
+        let dir = std::env::temp_dir().join(format!("ptu-siglevels-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let included = dir.join("custom.conf");
+        fs::write(
+            &included,
+            "SigLevel = Optional TrustAll\nServer = file:///repo\n",
+        )
+        .unwrap();
+        // an include without a SigLevel, like a mirrorlist
+        let mirrorlist = dir.join("mirrorlist");
+        fs::write(&mirrorlist, "Server = file:///mirror/$repo\n").unwrap();
+        let conf = dir.join("pacman.conf");
+        fs::write(
+            &conf,
+            format!(
+                "[options]\nSigLevel = Required DatabaseOptional\n\n\
+                 [core]\nInclude = {}\n\n\
+                 [custom]\nInclude = {}\n",
+                mirrorlist.display(),
+                included.display()
+            ),
+        )
+        .unwrap();
+
+        let levels = SigLevels::read(&conf);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            levels.default(),
+            SigLevel::PACKAGE | SigLevel::DATABASE | SigLevel::DATABASE_OPTIONAL
+        );
+        assert_eq!(levels.repo("core"), SigLevel::USE_DEFAULT);
+        assert_eq!(
+            levels.repo("custom"),
+            levels.default()
+                | SigLevel::PACKAGE_OPTIONAL
+                | SigLevel::PACKAGE_MARGINAL_OK
+                | SigLevel::PACKAGE_UNKNOWN_OK
+                | SigLevel::DATABASE_MARGINAL_OK
+                | SigLevel::DATABASE_UNKNOWN_OK
+        );
+    }
+}
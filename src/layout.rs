@@ -0,0 +1,397 @@
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Tabs;
+
+use crate::components::Pane;
+use crate::theme::Theme;
+
+/// Terminals at least this wide show the packages and the details side by side.
+const WIDE_MIN_WIDTH: u16 = 100;
+/// Narrow terminals at least this tall stack the packages above the details.
+const STACKED_MIN_HEIGHT: u16 = 30;
+const INPUT_HEIGHT: u16 = 3;
+const SPLIT_STEP: u16 = 5;
+const SPLIT_MIN: u16 = 20;
+const SPLIT_MAX: u16 = 80;
+
+/// Screens switched between with the function keys.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum View {
+    Packages,
+    Log,
+    History,
+    Cache,
+    Pacnew,
+    Integrity,
+    Optdepends,
+    Keyring,
+    Mirrors,
+    News,
+    State,
+}
+
+impl View {
+    pub(crate) const ALL: [View; 11] = [
+        View::Packages,
+        View::Log,
+        View::History,
+        View::Cache,
+        View::Pacnew,
+        View::Integrity,
+        View::Optdepends,
+        View::Keyring,
+        View::Mirrors,
+        View::News,
+        View::State,
+    ];
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            View::Packages => "packages",
+            View::Log => "log",
+            View::History => "history",
+            View::Cache => "cache",
+            View::Pacnew => "pacnew",
+            View::Integrity => "integrity",
+            View::Optdepends => "optdepends",
+            View::Keyring => "keyring",
+            View::Mirrors => "mirrors",
+            View::News => "news",
+            View::State => "state",
+        }
+    }
+
+    /// The pane focused when the view is shown.
+    pub(crate) fn pane(self) -> Pane {
+        match self {
+            View::Packages => Pane::PackageInput,
+            View::Log => Pane::Log,
+            View::History => Pane::History,
+            View::Cache => Pane::Cache,
+            View::Pacnew => Pane::Pacnew,
+            View::Integrity => Pane::Integrity,
+            View::Optdepends => Pane::Optdepends,
+            View::Keyring => Pane::Keyring,
+            View::Mirrors => Pane::Mirrors,
+            View::News => Pane::News,
+            View::State => Pane::State,
+        }
+    }
+
+    /// The view a pane belongs to.
+    fn of(pane: Pane) -> Self {
+        match pane {
+            Pane::PackageInput | Pane::PackagesTable | Pane::PackageInfo | Pane::ScriptViewer => {
+                View::Packages
+            }
+            Pane::Log => View::Log,
+            Pane::History => View::History,
+            Pane::Cache => View::Cache,
+            Pane::Pacnew => View::Pacnew,
+            Pane::Integrity => View::Integrity,
+            Pane::Optdepends => View::Optdepends,
+            Pane::Keyring => View::Keyring,
+            Pane::Mirrors => View::Mirrors,
+            Pane::News => View::News,
+            Pane::State => View::State,
+        }
+    }
+
+    /// The view shown by pressing `F<n>`.
+    pub(crate) fn from_key(n: u8) -> Option<Self> {
+        View::ALL.get(usize::from(n).checked_sub(1)?).copied()
+    }
+
+    fn label(self) -> String {
+        let n = View::ALL
+            .iter()
+            .position(|view| *view == self)
+            .unwrap_or_default()
+            + 1;
+        format!("F{n} {}", self.title())
+    }
+}
+
+enum LayoutMode {
+    Wide,
+    Stacked,
+    Tabbed,
+}
+
+impl LayoutMode {
+    fn new(area: Rect) -> Self {
+        if area.width >= WIDE_MIN_WIDTH {
+            LayoutMode::Wide
+        } else if area.height >= STACKED_MIN_HEIGHT {
+            LayoutMode::Stacked
+        } else {
+            LayoutMode::Tabbed
+        }
+    }
+}
+
+/// Areas computed for a single frame.
+#[derive(Default)]
+pub(crate) struct Areas {
+    header: Rect,
+    panes: Vec<(Pane, Rect)>,
+    tabs: Option<(Rect, Vec<Pane>)>,
+}
+
+impl Areas {
+    pub(crate) fn pane(&self, pane: Pane) -> Option<Rect> {
+        self.panes
+            .iter()
+            .find(|(p, _)| *p == pane)
+            .map(|(_, area)| *area)
+    }
+
+    pub(crate) fn header(&self) -> Rect {
+        self.header
+    }
+
+    pub(crate) fn tabs(&self) -> Option<Rect> {
+        self.tabs.as_ref().map(|(area, _)| *area)
+    }
+
+    pub(crate) fn pane_at(&self, position: Position) -> Option<Pane> {
+        self.panes
+            .iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(pane, _)| *pane)
+    }
+
+    pub(crate) fn view_at(&self, position: Position) -> Option<View> {
+        if !self.header.contains(position) {
+            return None;
+        }
+        let mut x = self.header.x;
+        for view in View::ALL {
+            let width = view.label().len() as u16 + 2;
+            if position.x < x + width {
+                return Some(view);
+            }
+            x += width + 1;
+        }
+        None
+    }
+
+    pub(crate) fn tab_at(&self, position: Position) -> Option<Pane> {
+        let (area, panes) = self.tabs.as_ref()?;
+        if !area.contains(position) {
+            return None;
+        }
+        // each title is padded by a space on both sides and followed by a divider
+        let mut x = area.x;
+        for &pane in panes {
+            let width = pane.title().len() as u16 + 2;
+            if position.x < x + width {
+                return Some(pane);
+            }
+            x += width + 1;
+        }
+        None
+    }
+}
+
+pub(crate) struct LayoutManager {
+    /// Percentage of the screen given to the packages pane.
+    split: u16,
+    view: View,
+    tab: Pane,
+    /// Whether the script viewer is open.
+    viewer: bool,
+    theme: Theme,
+}
+
+impl Default for LayoutManager {
+    fn default() -> Self {
+        Self {
+            split: 50,
+            view: View::Packages,
+            tab: Pane::PackagesTable,
+            viewer: false,
+            theme: Default::default(),
+        }
+    }
+}
+
+impl LayoutManager {
+    pub(crate) fn grow(&mut self) {
+        self.split = (self.split + SPLIT_STEP).min(SPLIT_MAX);
+    }
+
+    pub(crate) fn shrink(&mut self) {
+        self.split = (self.split - SPLIT_STEP).max(SPLIT_MIN);
+    }
+
+    /// Shows the view of `pane` and, in tabbed mode, its tab.
+    pub(crate) fn focus(&mut self, pane: Pane) {
+        self.view = View::of(pane);
+        if self.tab_panes().contains(&pane) {
+            self.tab = pane;
+        }
+    }
+
+    pub(crate) fn open_viewer(&mut self) {
+        self.viewer = true;
+    }
+
+    pub(crate) fn close_viewer(&mut self) {
+        self.viewer = false;
+        if self.tab == Pane::ScriptViewer {
+            self.tab = Pane::PackageInfo;
+        }
+    }
+
+    /// The panes of the current view.
+    fn panes(&self) -> Vec<Pane> {
+        match self.view {
+            View::Packages => {
+                let mut panes = vec![Pane::PackageInput, Pane::PackagesTable, Pane::PackageInfo];
+                if self.viewer {
+                    panes.push(Pane::ScriptViewer);
+                }
+                panes
+            }
+            View::Log => vec![Pane::Log],
+            View::History => vec![Pane::History],
+            View::Cache => vec![Pane::Cache],
+            View::Pacnew => vec![Pane::Pacnew],
+            View::Integrity => vec![Pane::Integrity],
+            View::Optdepends => vec![Pane::Optdepends],
+            View::Keyring => vec![Pane::Keyring],
+            View::Mirrors => vec![Pane::Mirrors],
+            View::News => vec![Pane::News],
+            View::State => vec![Pane::State],
+        }
+    }
+
+    /// The pane of the current view after `pane`.
+    pub(crate) fn next(&self, pane: Pane) -> Pane {
+        let panes = self.panes();
+        let mut next = pane.next();
+        while !panes.contains(&next) && next != pane {
+            next = next.next();
+        }
+        next
+    }
+
+    fn tab_panes(&self) -> Vec<Pane> {
+        let mut panes = vec![Pane::PackagesTable, Pane::PackageInfo];
+        if self.viewer {
+            panes.push(Pane::ScriptViewer);
+        }
+        panes
+    }
+
+    /// Splits the details area between the package info and the script viewer.
+    fn details(&self, area: Rect, panes: &mut Vec<(Pane, Rect)>) {
+        if !self.viewer {
+            panes.push((Pane::PackageInfo, area));
+            return;
+        }
+        let [info, viewer] =
+            Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(area);
+        panes.push((Pane::PackageInfo, info));
+        panes.push((Pane::ScriptViewer, viewer));
+    }
+
+    pub(crate) fn areas(&self, area: Rect) -> Areas {
+        let [header, area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+        let mut areas = match self.view {
+            View::Packages => self.packages_areas(area),
+            View::Log
+            | View::History
+            | View::Cache
+            | View::Pacnew
+            | View::Integrity
+            | View::Optdepends
+            | View::Keyring
+            | View::Mirrors
+            | View::News
+            | View::State => Areas {
+                panes: vec![(self.view.pane(), area)],
+                ..Default::default()
+            },
+        };
+        areas.header = header;
+        areas
+    }
+
+    fn packages_areas(&self, area: Rect) -> Areas {
+        match LayoutMode::new(area) {
+            LayoutMode::Wide => {
+                let [left, info] = Layout::horizontal([
+                    Constraint::Percentage(self.split),
+                    Constraint::Percentage(100 - self.split),
+                ])
+                .areas(area);
+                let [input, table] =
+                    Layout::vertical([Constraint::Length(INPUT_HEIGHT), Constraint::Fill(1)])
+                        .areas(left);
+                let mut panes = vec![(Pane::PackageInput, input), (Pane::PackagesTable, table)];
+                self.details(info, &mut panes);
+                Areas {
+                    panes,
+                    ..Default::default()
+                }
+            }
+            LayoutMode::Stacked => {
+                let [input, rest] =
+                    Layout::vertical([Constraint::Length(INPUT_HEIGHT), Constraint::Fill(1)])
+                        .areas(area);
+                let [table, info] = Layout::vertical([
+                    Constraint::Percentage(self.split),
+                    Constraint::Percentage(100 - self.split),
+                ])
+                .areas(rest);
+                let mut panes = vec![(Pane::PackageInput, input), (Pane::PackagesTable, table)];
+                self.details(info, &mut panes);
+                Areas {
+                    panes,
+                    ..Default::default()
+                }
+            }
+            LayoutMode::Tabbed => {
+                let [input, tabs, pane] = Layout::vertical([
+                    Constraint::Length(INPUT_HEIGHT),
+                    Constraint::Length(1),
+                    Constraint::Fill(1),
+                ])
+                .areas(area);
+                Areas {
+                    panes: vec![(Pane::PackageInput, input), (self.tab, pane)],
+                    tabs: Some((tabs, self.tab_panes())),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    pub(crate) fn header(&self) -> Tabs<'static> {
+        let selected = View::ALL.iter().position(|view| *view == self.view);
+        Tabs::new(View::ALL.map(View::label))
+            .select(selected.unwrap_or_default())
+            .style(Style::default().fg(self.theme.inactive))
+            .highlight_style(
+                Style::default()
+                    .fg(self.theme.active)
+                    .add_modifier(Modifier::BOLD),
+            )
+    }
+
+    pub(crate) fn tabs(&self) -> Tabs<'static> {
+        let panes = self.tab_panes();
+        let selected = panes.iter().position(|pane| *pane == self.tab);
+        Tabs::new(panes.into_iter().map(Pane::title))
+            .select(selected.unwrap_or_default())
+            .style(Style::default().fg(self.theme.inactive))
+            .highlight_style(
+                Style::default()
+                    .fg(self.theme.active)
+                    .add_modifier(Modifier::REVERSED),
+            )
+    }
+}
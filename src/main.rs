@@ -1,17 +1,49 @@
 mod action;
 mod app;
+mod aur;
+mod cache;
+mod cli;
 mod components;
+mod config;
+mod desired;
+mod downgrade;
 mod event;
+mod export;
+mod format;
+mod highlight;
+mod history;
+mod hold;
+mod ignore;
+mod integrity;
+mod keyring;
+mod layout;
+mod makepkg;
+mod manifest;
+mod mirrorlist;
+mod news;
+mod package_file;
 mod pacman;
+mod pacman_log;
+mod pacnew;
 mod panic_hook;
+mod sync;
 mod theme;
 mod tui;
 
 use crate::app::App;
+use crate::cli::Cli;
 
+use clap::Parser as _;
 use color_eyre::eyre;
 
 fn main() -> eyre::Result<()> {
+    let cli = Cli::parse();
+
+    if let Some(command) = cli.command {
+        color_eyre::install()?;
+        return cli::run(command, &cli.options);
+    }
+
     panic_hook::init()?;
 
     let mut app = App::new()?;
@@ -0,0 +1,536 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use color_eyre::eyre;
+
+use crate::aur::Aur;
+use crate::pacman::{Dep, Package, Pacman};
+
+/// Revision of the last successful build, kept out of the work tree.
+const LAST_BUILD_FILE: &str = ".git/ptu-last-build";
+
+/// A package base to clone, build and install.
+#[derive(Clone)]
+pub(crate) struct Build {
+    pub(crate) base: String,
+    /// Packages of the base to install, a base may build more.
+    pub(crate) packages: Vec<String>,
+    /// Whether the base is only built for another package.
+    pub(crate) as_deps: bool,
+    pub(crate) dir: PathBuf,
+}
+
+/// Everything needed to install an AUR package.
+#[derive(Clone, Default)]
+pub(crate) struct Plan {
+    /// Dependencies available from the sync databases.
+    pub(crate) repo_deps: Vec<String>,
+    /// Package bases in the order they have to be built.
+    pub(crate) builds: Vec<Build>,
+}
+
+/// What to read before building a package base.
+#[derive(Clone)]
+pub(crate) struct Review {
+    pub(crate) pkgbuild: String,
+    /// Changes since the last build, `None` if it was never built.
+    pub(crate) diff: Option<String>,
+    pub(crate) revision: String,
+}
+
+/// A build or install script to read.
+#[derive(Clone)]
+pub(crate) struct Script {
+    pub(crate) name: String,
+    pub(crate) content: String,
+}
+
+impl Script {
+    pub(crate) fn read(name: impl Into<String>, path: &Path) -> eyre::Result<Self> {
+        Ok(Self {
+            name: name.into(),
+            content: fs::read_to_string(path)?,
+        })
+    }
+}
+
+/// What resolving needs to know about the installed packages and the sync databases.
+pub(crate) trait SyncLookup {
+    fn is_satisfied(&self, dep: &Dep) -> bool;
+    /// The sync package satisfying `dep` by name or by what it provides.
+    fn sync_satisfier(&self, dep: &Dep) -> Option<String>;
+}
+
+impl SyncLookup for Pacman {
+    fn is_satisfied(&self, dep: &Dep) -> bool {
+        Pacman::is_satisfied(self, dep)
+    }
+
+    fn sync_satisfier(&self, dep: &Dep) -> Option<String> {
+        Pacman::sync_satisfier(self, dep)
+    }
+}
+
+/// What resolving needs to look up in the AUR.
+pub(crate) trait AurLookup {
+    fn info(&self, names: &[&str]) -> eyre::Result<Vec<Package>>;
+    fn providers(&self, name: &str) -> eyre::Result<Vec<Package>>;
+}
+
+impl AurLookup for Aur {
+    fn info(&self, names: &[&str]) -> eyre::Result<Vec<Package>> {
+        Aur::info(self, names)
+    }
+
+    fn providers(&self, name: &str) -> eyre::Result<Vec<Package>> {
+        Aur::providers(self, name)
+    }
+}
+
+struct Resolver<'a> {
+    pacman: &'a dyn SyncLookup,
+    aur: &'a dyn AurLookup,
+    visited: HashSet<String>,
+    order: Vec<Package>,
+    repo_deps: Vec<String>,
+}
+
+impl Resolver<'_> {
+    /// Adds `package` after the AUR packages it depends on.
+    fn visit(&mut self, package: Package) -> eyre::Result<()> {
+        if !self.visited.insert(package.name.clone()) {
+            return Ok(());
+        }
+
+        let mut missing = Vec::new();
+        let deps = package
+            .depends
+            .iter()
+            .chain(&package.makedepends)
+            .chain(&package.checkdepends);
+        for dep in deps {
+            if self.pacman.is_satisfied(dep) {
+                continue;
+            }
+            // finds providers and checks versions, unlike the AUR lookup below
+            if let Some(name) = self.pacman.sync_satisfier(dep) {
+                if !self.repo_deps.contains(&name) {
+                    self.repo_deps.push(name);
+                }
+                continue;
+            }
+            if !missing
+                .iter()
+                .any(|missing: &&Dep| missing.name == dep.name)
+            {
+                missing.push(dep);
+            }
+        }
+        if missing.is_empty() {
+            self.order.push(package);
+            return Ok(());
+        }
+
+        let names = missing
+            .iter()
+            .map(|dep| dep.name.as_str())
+            .collect::<Vec<_>>();
+        let found = self.aur.info(&names)?;
+        for dep in missing {
+            // an AUR package planned already may provide it
+            if self
+                .order
+                .iter()
+                .any(|planned| dep.is_satisfied_by(planned))
+            {
+                continue;
+            }
+            let provider = match found.iter().find(|p| dep.is_satisfied_by(p)) {
+                Some(provider) => provider.clone(),
+                None => self
+                    .aur
+                    .providers(&dep.name)?
+                    .into_iter()
+                    .find(|p| dep.is_satisfied_by(p))
+                    .ok_or_else(|| {
+                        eyre::eyre!(
+                            "{} depends on {}, which is neither in the sync databases nor in the AUR",
+                            package.name,
+                            dep.to_depstring()
+                        )
+                    })?,
+            };
+            self.visit(provider)?;
+        }
+
+        self.order.push(package);
+        Ok(())
+    }
+}
+
+/// Resolves the AUR dependencies of `name`, looking them up in the sync databases first.
+pub(crate) fn plan(
+    pacman: &dyn SyncLookup,
+    aur: &dyn AurLookup,
+    build_dir: &Path,
+    name: &str,
+) -> eyre::Result<Plan> {
+    let package = aur
+        .info(&[name])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| eyre::eyre!("package '{name}' was not found in the AUR"))?;
+
+    let mut resolver = Resolver {
+        pacman,
+        aur,
+        visited: HashSet::new(),
+        order: Vec::new(),
+        repo_deps: Vec::new(),
+    };
+    resolver.visit(package)?;
+
+    let mut builds: Vec<Build> = Vec::new();
+    for package in resolver.order {
+        let base = package.base.unwrap_or_else(|| package.name.clone());
+        let as_deps = package.name != name;
+        match builds.iter_mut().find(|build| build.base == base) {
+            Some(build) => {
+                build.packages.push(package.name);
+                build.as_deps &= as_deps;
+            }
+            None => builds.push(Build {
+                dir: build_dir.join(&base),
+                base,
+                packages: vec![package.name],
+                as_deps,
+            }),
+        }
+    }
+
+    Ok(Plan {
+        repo_deps: resolver.repo_deps,
+        builds,
+    })
+}
+
+/// Clones the package base or pulls it if it was cloned before.
+pub(crate) fn fetch(build: &Build, aur: &Aur, log: &(dyn Fn(&str) + Sync)) -> eyre::Result<Review> {
+    if build.dir.join(".git").exists() {
+        run(
+            Command::new("git")
+                .arg("pull")
+                .arg("--ff-only")
+                .current_dir(&build.dir),
+            log,
+        )?;
+    } else {
+        fs::create_dir_all(&build.dir)?;
+        run(
+            Command::new("git")
+                .arg("clone")
+                .arg(aur.clone_url(&build.base))
+                .arg(&build.dir),
+            log,
+        )?;
+    }
+
+    let pkgbuild = fs::read_to_string(build.dir.join("PKGBUILD"))?;
+    let revision = output(
+        Command::new("git")
+            .arg("rev-parse")
+            .arg("HEAD")
+            .current_dir(&build.dir),
+    )?
+    .trim()
+    .to_owned();
+    let diff = match fs::read_to_string(build.dir.join(LAST_BUILD_FILE)) {
+        Ok(last) => Some(output(
+            Command::new("git")
+                .arg("diff")
+                .arg(last.trim())
+                .arg(&revision)
+                .current_dir(&build.dir),
+        )?),
+        Err(_) => None,
+    };
+
+    Ok(Review {
+        pkgbuild,
+        diff,
+        revision,
+    })
+}
+
+/// Runs `makepkg` and returns the package files to install.
+pub(crate) fn build(build: &Build, log: &(dyn Fn(&str) + Sync)) -> eyre::Result<Vec<PathBuf>> {
+    run(
+        Command::new("makepkg")
+            .arg("--force")
+            .arg("--noconfirm")
+            .current_dir(&build.dir),
+        log,
+    )?;
+
+    let packagelist = output(
+        Command::new("makepkg")
+            .arg("--packagelist")
+            .current_dir(&build.dir),
+    )?;
+    let files = package_files(&packagelist, &build.packages);
+    if files.is_empty() {
+        eyre::bail!("makepkg did not build {}", build.packages.join(", "));
+    }
+
+    Ok(files)
+}
+
+/// The PKGBUILD and install scripts of a cloned package base.
+pub(crate) fn scripts(dir: &Path) -> eyre::Result<Vec<Script>> {
+    let mut scripts = vec![Script::read("PKGBUILD", &dir.join("PKGBUILD"))?];
+
+    let mut install_files = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "install")
+        })
+        .collect::<Vec<_>>();
+    install_files.sort();
+    for path in install_files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        scripts.push(Script::read(name, &path)?);
+    }
+
+    Ok(scripts)
+}
+
+/// Remembers `revision` so the next review only shows what changed since.
+pub(crate) fn mark_built(build: &Build, revision: &str) -> eyre::Result<()> {
+    fs::write(build.dir.join(LAST_BUILD_FILE), revision)?;
+    Ok(())
+}
+
+/// The files of `makepkg --packagelist` that belong to `packages`, leaving out the other
+/// packages of the base like debug packages.
+fn package_files(packagelist: &str, packages: &[String]) -> Vec<PathBuf> {
+    packagelist
+        .lines()
+        .map(PathBuf::from)
+        .filter(|path| packages.iter().any(|name| is_package_file(path, name)))
+        .collect()
+}
+
+/// Whether `path` is named `<name>-<pkgver>-<pkgrel>-<arch>.pkg.tar.*`.
+fn is_package_file(path: &Path, name: &str) -> bool {
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    // package names may contain dashes, versions and architectures may not
+    file_name
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|rest| rest.matches('-').count() == 2)
+}
+
+/// Runs `command`, passing every line it prints to `log`.
+fn run(command: &mut Command, log: &(dyn Fn(&str) + Sync)) -> eyre::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    thread::scope(|scope| {
+        scope.spawn(|| stdout.map(|stdout| log_lines(stdout, log)));
+        scope.spawn(|| stderr.map(|stderr| log_lines(stderr, log)));
+    });
+
+    let status = child.wait()?;
+    if !status.success() {
+        eyre::bail!("{:?} failed with {status}", command.get_program());
+    }
+
+    Ok(())
+}
+
+fn log_lines(reader: impl Read, log: &(dyn Fn(&str) + Sync)) {
+    for line in BufReader::new(reader).lines().map_while(Result::ok) {
+        log(&line);
+    }
+}
+
+fn output(command: &mut Command) -> eyre::Result<String> {
+    let output = command.stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        eyre::bail!("{:?} failed with {}", command.get_program(), output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Installed packages and sync packages by the names they satisfy.
+    struct FakeSync {
+        installed: Vec<&'static str>,
+        synced: Vec<&'static str>,
+    }
+
+    impl SyncLookup for FakeSync {
+        fn is_satisfied(&self, dep: &Dep) -> bool {
+            self.installed.contains(&dep.name.as_str())
+        }
+
+        fn sync_satisfier(&self, dep: &Dep) -> Option<String> {
+            self.synced
+                .contains(&dep.name.as_str())
+                .then(|| dep.name.clone())
+        }
+    }
+
+    struct FakeAur(Vec<Package>);
+
+    impl AurLookup for FakeAur {
+        fn info(&self, names: &[&str]) -> eyre::Result<Vec<Package>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|package| names.contains(&package.name.as_str()))
+                .cloned()
+                .collect())
+        }
+
+        fn providers(&self, name: &str) -> eyre::Result<Vec<Package>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|package| {
+                    package.name == name || package.provides.iter().any(|dep| dep.name == name)
+                })
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn package(
+        name: &str,
+        base: &str,
+        version: &str,
+        depends: &[&str],
+        provides: &[&str],
+    ) -> Package {
+        let deps = |deps: &[&str]| deps.iter().map(|dep| Dep::parse(dep)).collect();
+        Package {
+            name: name.to_owned(),
+            base: Some(base.to_owned()),
+            version: version.to_owned(),
+            depends: deps(depends),
+            provides: deps(provides),
+            ..Default::default()
+        }
+    }
+
+    fn aur() -> FakeAur {
+        let mut app = package(
+            "app",
+            "app",
+            "1.0-1",
+            &["glibc", "libfoo>=2", "libbar", "app-common"],
+            &[],
+        );
+        app.makedepends = vec![Dep::parse("cmake")];
+        FakeAur(vec![
+            app,
+            package("app-common", "app", "1.0-1", &[], &[]),
+            // too old, the git package provides a newer version
+            package("libfoo", "libfoo", "1.0-1", &[], &[]),
+            package(
+                "libfoo-git",
+                "libfoo-git",
+                "2.1.r5-1",
+                &["libbar"],
+                &["libfoo=2.1"],
+            ),
+            package("libbar-bin", "libbar-bin", "3.0-1", &[], &["libbar"]),
+        ])
+    }
+
+    #[test]
+    fn plans_builds_by_version_constraint_and_provides() {
+        let sync = FakeSync {
+            installed: vec!["glibc"],
+            synced: vec!["cmake"],
+        };
+
+        let plan = plan(&sync, &aur(), Path::new("/build"), "app").unwrap();
+
+        assert_eq!(plan.repo_deps, ["cmake"]);
+        let builds = plan
+            .builds
+            .iter()
+            .map(|build| (build.base.as_str(), build.packages.clone(), build.as_deps))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            builds,
+            [
+                ("libbar-bin", vec![String::from("libbar-bin")], true),
+                ("libfoo-git", vec![String::from("libfoo-git")], true),
+                // built once for both packages, installed explicitly for the target
+                (
+                    "app",
+                    vec![String::from("app-common"), String::from("app")],
+                    false
+                ),
+            ]
+        );
+        assert_eq!(plan.builds[0].dir, Path::new("/build/libbar-bin"));
+    }
+
+    #[test]
+    fn fails_on_dependencies_nothing_satisfies() {
+        let sync = FakeSync {
+            installed: vec!["glibc", "libbar"],
+            synced: vec!["cmake"],
+        };
+        let mut aur = aur();
+        aur.0.retain(|package| !package.name.starts_with("libfoo"));
+
+        let error = plan(&sync, &aur, Path::new("/build"), "app").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "app depends on libfoo>=2, which is neither in the sync databases nor in the AUR"
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_package_files_asked_for() {
+        let packagelist = "\
+/build/app/app-1:1.0-1-x86_64.pkg.tar.zst
+/build/app/app-common-1:1.0-1-any.pkg.tar.zst
+/build/app/app-debug-1:1.0-1-x86_64.pkg.tar.zst
+";
+
+        assert_eq!(
+            package_files(packagelist, &[String::from("app")]),
+            [PathBuf::from("/build/app/app-1:1.0-1-x86_64.pkg.tar.zst")]
+        );
+        assert_eq!(
+            package_files(
+                packagelist,
+                &[String::from("app-common"), String::from("app")]
+            )
+            .len(),
+            2
+        );
+        assert!(package_files(packagelist, &[String::from("ap")]).is_empty());
+    }
+}
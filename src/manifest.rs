@@ -0,0 +1,363 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::ExitStatus;
+
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::desired::System;
+use crate::pacman::{self, Pacman, Reason, Transaction};
+
+/// The repository of packages found in no sync database.
+pub(crate) const FOREIGN: &str = "foreign";
+
+/// The explicitly installed packages of a system, to set another system up the same way.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    /// Package names by repository, [`FOREIGN`] for the ones in no sync database.
+    #[serde(default)]
+    pub(crate) packages: BTreeMap<String, Vec<String>>,
+    /// Versions by package, empty unless exported with them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) versions: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// The explicitly installed packages of this system.
+    pub(crate) fn from_system(pacman: &Pacman, with_versions: bool) -> Self {
+        let mut manifest = Self::default();
+        for (package, repo) in pacman.installed_packages() {
+            if package.reason != Some(Reason::Explicit) {
+                continue;
+            }
+            if with_versions {
+                manifest
+                    .versions
+                    .insert(package.name.clone(), package.version.clone());
+            }
+            manifest
+                .packages
+                .entry(repo.unwrap_or_else(|| FOREIGN.to_owned()))
+                .or_default()
+                .push(package.name);
+        }
+        manifest
+            .packages
+            .values_mut()
+            .for_each(|names| names.sort());
+        manifest
+    }
+
+    /// Reads a manifest, TOML for `.toml` files and text otherwise.
+    pub(crate) fn read(path: &Path) -> eyre::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        if is_toml(path) {
+            Ok(toml::from_str(&content)?)
+        } else {
+            Ok(Self::parse(&content))
+        }
+    }
+
+    /// Writes the manifest, TOML for `.toml` files and text otherwise.
+    pub(crate) fn write(&self, path: &Path) -> eyre::Result<()> {
+        let content = if is_toml(path) {
+            toml::to_string(self)?
+        } else {
+            self.to_text()
+        };
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.packages.values().map(Vec::len).sum()
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &String> {
+        self.packages.values().flatten()
+    }
+
+    /// A `[repo]` heading per repository followed by a `name [version]` line per package.
+    /// Names before any heading belong to no repository, so a plain `pacman -Qqe` list
+    /// reads as well.
+    pub(crate) fn parse(content: &str) -> Self {
+        let mut manifest = Self::default();
+        let mut repo = String::new();
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some(heading) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                heading.trim().clone_into(&mut repo);
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else {
+                continue;
+            };
+            if let Some(version) = fields.next() {
+                manifest
+                    .versions
+                    .insert(name.to_owned(), version.to_owned());
+            }
+            manifest
+                .packages
+                .entry(repo.clone())
+                .or_default()
+                .push(name.to_owned());
+        }
+        manifest
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        let mut content = String::new();
+        for (repo, names) in &self.packages {
+            if !repo.is_empty() {
+                if !content.is_empty() {
+                    content.push('\n');
+                }
+                content.push_str(&format!("[{repo}]\n"));
+            }
+            for name in names {
+                match self.versions.get(name) {
+                    Some(version) => content.push_str(&format!("{name} {version}\n")),
+                    None => content.push_str(&format!("{name}\n")),
+                }
+            }
+        }
+        content
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "toml")
+}
+
+/// What it takes to bring the system in line with a manifest.
+#[derive(Clone)]
+pub(crate) enum Change {
+    /// Not installed, `available` if the sync databases have it.
+    Install { name: String, available: bool },
+    /// Installed explicitly, but not in the manifest.
+    Remove(String),
+    /// Installed explicitly and not in the manifest, but other packages require it.
+    Depend(String),
+    /// Installed at another version, `available` if the sync databases have the wanted one.
+    Version {
+        name: String,
+        installed: String,
+        wanted: String,
+        available: bool,
+    },
+    /// Installed as a dependency, but explicitly in the manifest.
+    Explicit(String),
+}
+
+impl Change {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Change::Install { name, .. }
+            | Change::Remove(name)
+            | Change::Depend(name)
+            | Change::Version { name, .. }
+            | Change::Explicit(name) => name,
+        }
+    }
+
+    /// Whether pacman can make the change, packages outside the sync databases and old
+    /// versions are left to be installed by hand.
+    pub(crate) fn is_applicable(&self) -> bool {
+        match self {
+            Change::Install { available, .. } | Change::Version { available, .. } => *available,
+            Change::Remove(_) | Change::Depend(_) | Change::Explicit(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Install { name, available } => {
+                write!(f, "install {name}")?;
+                if !available {
+                    write!(f, " (not in the sync databases)")?;
+                }
+                Ok(())
+            }
+            Change::Remove(name) => write!(f, "remove {name}"),
+            Change::Depend(name) => write!(f, "mark {name} as a dependency, others require it"),
+            Change::Version {
+                name,
+                installed,
+                wanted,
+                available,
+            } => {
+                write!(f, "change {name} from {installed} to {wanted}")?;
+                if !available {
+                    write!(f, " (not in the sync databases)")?;
+                }
+                Ok(())
+            }
+            Change::Explicit(name) => write!(f, "mark {name} as explicitly installed"),
+        }
+    }
+}
+
+/// The changes that would make the explicitly installed packages those of the manifest.
+pub(crate) fn compare(manifest: &Manifest, pacman: &dyn System) -> Vec<Change> {
+    let installed = pacman
+        .installed_packages()
+        .into_iter()
+        .map(|(package, _)| (package.name.clone(), package))
+        .collect::<HashMap<_, _>>();
+    let mut changes = Vec::new();
+
+    for name in manifest.names() {
+        let Some(package) = installed.get(name) else {
+            changes.push(Change::Install {
+                name: name.clone(),
+                available: pacman.sync_version(name).is_some(),
+            });
+            continue;
+        };
+        if let Some(wanted) = manifest.versions.get(name) {
+            if *wanted != package.version {
+                changes.push(Change::Version {
+                    name: name.clone(),
+                    installed: package.version.clone(),
+                    wanted: wanted.clone(),
+                    available: pacman.sync_version(name).as_ref() == Some(wanted),
+                });
+            }
+        }
+        if package.reason == Some(Reason::Depend) {
+            changes.push(Change::Explicit(name.clone()));
+        }
+    }
+
+    let wanted = manifest.names().collect::<Vec<_>>();
+    let mut removals = installed
+        .values()
+        .filter(|package| package.reason == Some(Reason::Explicit))
+        .filter(|package| !wanted.contains(&&package.name))
+        .map(|package| {
+            // removing it would break the packages requiring it
+            if pacman.is_required(&package.name) {
+                Change::Depend(package.name.clone())
+            } else {
+                Change::Remove(package.name.clone())
+            }
+        })
+        .collect::<Vec<_>>();
+    removals.sort_by(|a, b| a.name().cmp(b.name()));
+    changes.extend(removals);
+
+    changes
+}
+
+/// Applies the applicable changes in one transaction, so a failure leaves nothing changed.
+pub(crate) fn apply(changes: &[Change], config: &Config) -> eyre::Result<ExitStatus> {
+    let changes = changes
+        .iter()
+        .filter(|change| change.is_applicable())
+        .collect::<Vec<_>>();
+    let names = |matches: fn(&Change) -> bool| {
+        changes
+            .iter()
+            .filter(|change| matches(change))
+            .map(|change| change.name().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let transaction = Transaction {
+        install: names(|change| matches!(change, Change::Install { .. } | Change::Version { .. })),
+        remove: names(|change| matches!(change, Change::Remove(_))),
+        explicit: names(|change| matches!(change, Change::Explicit(_))),
+        depend: names(|change| matches!(change, Change::Depend(_))),
+        ..Default::default()
+    };
+    if transaction.is_empty() {
+        return Ok(ExitStatus::default());
+    }
+    pacman::commit(&transaction, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desired::tests::FakeSystem;
+
+    const TEXT: &str = "\
+[core]
+linux 6.9.2.arch1-1
+pacman
+
+[extra]
+git
+vim 9.1.0-1
+
+[foreign]
+yay
+";
+
+    #[test]
+    fn round_trips_text() {
+        let manifest = Manifest::parse(TEXT);
+        assert_eq!(manifest.len(), 5);
+        assert_eq!(manifest.packages["core"], ["linux", "pacman"]);
+        assert_eq!(manifest.packages[FOREIGN], ["yay"]);
+        assert_eq!(
+            manifest.versions,
+            BTreeMap::from([
+                (String::from("linux"), String::from("6.9.2.arch1-1")),
+                (String::from("vim"), String::from("9.1.0-1")),
+            ])
+        );
+        assert_eq!(manifest.to_text(), TEXT);
+    }
+
+    #[test]
+    fn reads_plain_package_lists() {
+        let manifest = Manifest::parse("# pacman -Qqe\nbase\n\n  linux  # the kernel\n");
+        assert_eq!(manifest.packages[""], ["base", "linux"]);
+        assert!(manifest.versions.is_empty());
+        assert_eq!(manifest.to_text(), "base\nlinux\n");
+    }
+
+    #[test]
+    fn marks_required_packages_missing_from_the_manifest_as_dependencies() {
+        let system = FakeSystem {
+            installed: vec![
+                ("git", "2.45.1-1", Reason::Explicit),
+                ("perl", "5.38.2-2", Reason::Explicit),
+                ("nano", "8.0-1", Reason::Explicit),
+                ("vim", "9.1.0-1", Reason::Depend),
+            ],
+            synced: HashMap::from([("htop", "3.3.0-3")]),
+            required: vec!["perl"],
+            ..Default::default()
+        };
+        let manifest = Manifest::parse(
+            "git
+vim
+htop
+",
+        );
+
+        let changes = compare(&manifest, &system)
+            .iter()
+            .map(Change::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "mark vim as explicitly installed",
+                "install htop",
+                "remove nano",
+                "mark perl as a dependency, others require it",
+            ]
+        );
+    }
+}
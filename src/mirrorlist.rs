@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, Read, Write as _};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use color_eyre::eyre;
+
+pub(crate) const MIRRORLIST: &str = "/etc/pacman.d/mirrorlist";
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(10);
+/// The repository whose database is downloaded to measure a mirror, every mirror has it.
+const TEST_REPO: &str = "core";
+
+/// The enabled `Server` URLs of a mirrorlist, in the order pacman tries them.
+pub(crate) fn servers(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "Server").then(|| value.trim().to_owned())
+        })
+        .collect()
+}
+
+/// Fills in the `$repo` and `$arch` variables of a server URL.
+pub(crate) fn server_url(server: &str, repo: &str) -> String {
+    server
+        .replace("$repo", repo)
+        .replace("$arch", env::consts::ARCH)
+}
+
+/// A server of a mirrorlist, commented out or not.
+#[derive(Clone)]
+pub(crate) struct Mirror {
+    pub(crate) server: String,
+    pub(crate) enabled: bool,
+    /// The `## Country` heading above it.
+    pub(crate) country: Option<String>,
+}
+
+/// Every server of a mirrorlist, including the commented out ones.
+pub(crate) fn parse(content: &str) -> Vec<Mirror> {
+    let mut mirrors = Vec::new();
+    let mut country = None;
+
+    for line in content.lines().map(str::trim) {
+        let uncommented = line.trim_start_matches('#').trim();
+        match uncommented.split_once('=') {
+            Some((key, value)) if key.trim() == "Server" => mirrors.push(Mirror {
+                server: value.trim().to_owned(),
+                enabled: !line.starts_with('#'),
+                country: country.clone(),
+            }),
+            Some(_) => {}
+            None if line.starts_with("##") && !uncommented.is_empty() => {
+                country = Some(uncommented.to_owned());
+            }
+            None => {}
+        }
+    }
+
+    mirrors
+}
+
+/// A mirrorlist with `mirrors` in their order, keeping the country headings.
+pub(crate) fn render(mirrors: &[Mirror]) -> String {
+    let mut content = format!(
+        "## ranked by ptu on {}\n",
+        Local::now().format("%Y-%m-%d %H:%M")
+    );
+    let mut country = None;
+    for mirror in mirrors {
+        if mirror.country.is_some() && mirror.country != country {
+            country.clone_from(&mirror.country);
+            content.push_str(&format!(
+                "\n## {}\n",
+                mirror.country.as_deref().unwrap_or_default()
+            ));
+        }
+        let comment = if mirror.enabled { "" } else { "#" };
+        content.push_str(&format!("{comment}Server = {}\n", mirror.server));
+    }
+    content
+}
+
+/// Replaces the mirrorlist, which is owned by root, keeping the old one next to it.
+pub(crate) fn write(path: &Path, content: &str) -> eyre::Result<ExitStatus> {
+    replace(path, content, true)
+}
+
+/// Replaces `path` with `content` after copying it to a `.bak` file, through sudo if `sudo`.
+fn replace(path: &Path, content: &str, sudo: bool) -> eyre::Result<ExitStatus> {
+    let command = |program: &str| {
+        if sudo {
+            let mut command = Command::new("sudo");
+            command.arg(program);
+            command
+        } else {
+            Command::new(program)
+        }
+    };
+
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let status = command("cp")
+        .arg("--preserve=mode,ownership")
+        .arg(path)
+        .arg(&backup)
+        .status()?;
+    if !status.success() {
+        return Ok(status);
+    }
+
+    let mut child = command("tee")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    // closes stdin before waiting, so tee is not left running when the write fails
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(content.as_bytes()),
+        None => Ok(()),
+    };
+    let status = child.wait()?;
+    written?;
+    Ok(status)
+}
+
+/// Orders mirrors fastest first, then the failed ones, then the unmeasured ones.
+pub(crate) fn rank(mirrors: &mut [Mirror], speeds: &HashMap<String, Result<Speed, String>>) {
+    let throughput = |mirror: &Mirror| match speeds.get(&mirror.server) {
+        Some(Ok(speed)) => speed.throughput,
+        Some(Err(_)) => -1.0,
+        None => -2.0,
+    };
+    mirrors.sort_by(|a, b| {
+        throughput(b)
+            .partial_cmp(&throughput(a))
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Downloads the files mirrors are measured with.
+pub(crate) trait Fetch: Send + Sync {
+    /// Starts downloading `url`, returning once the response begins.
+    fn fetch(&self, url: &str) -> eyre::Result<Box<dyn Read + Send>>;
+}
+
+impl Fetch for ureq::Agent {
+    fn fetch(&self, url: &str) -> eyre::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(self.get(url).call()?.into_reader()))
+    }
+}
+
+/// How fast a mirror served its copy of the test database.
+#[derive(Clone, Copy)]
+pub(crate) struct Speed {
+    /// Until the response began.
+    pub(crate) latency: Duration,
+    /// Bytes per second once it did.
+    pub(crate) throughput: f64,
+}
+
+/// Measures a server by downloading its database of the test repository.
+pub(crate) fn measure(fetcher: &dyn Fetch, server: &str) -> eyre::Result<Speed> {
+    let url = format!(
+        "{}/{TEST_REPO}.db",
+        server_url(server, TEST_REPO).trim_end_matches('/')
+    );
+    let start = Instant::now();
+    let mut reader = fetcher.fetch(&url)?;
+    let latency = start.elapsed();
+    let bytes = io::copy(&mut reader, &mut io::sink())?;
+    let transfer = start.elapsed() - latency;
+
+    Ok(Speed {
+        latency,
+        throughput: bytes as f64 / transfer.as_secs_f64().max(0.001),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Cursor;
+    use std::thread;
+
+    use super::*;
+
+    const MIRRORLIST: &str = "\
+## Germany
+Server = https://fast.example.org/$repo/os/$arch
+#Server = https://slow.example.org/$repo/os/$arch
+
+## Sweden
+# Server = https://down.example.org/$repo/os/$arch
+Server = https://unmeasured.example.org/$repo/os/$arch
+";
+
+    /// Serves a small database, slowly for the slow mirror and not at all for the down one.
+    struct StubFetch;
+
+    struct SlowReader(Cursor<Vec<u8>>);
+
+    impl Read for SlowReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            thread::sleep(Duration::from_millis(20));
+            self.0.read(buf)
+        }
+    }
+
+    impl Fetch for StubFetch {
+        fn fetch(&self, url: &str) -> eyre::Result<Box<dyn Read + Send>> {
+            let database = Cursor::new(vec![0; 1000]);
+            if url.contains("down.") {
+                eyre::bail!("connection refused");
+            } else if url.contains("slow.") {
+                Ok(Box::new(SlowReader(database)))
+            } else {
+                Ok(Box::new(database))
+            }
+        }
+    }
+
+    #[test]
+    fn parses_enabled_and_commented_servers() {
+        let mirrors = parse(MIRRORLIST);
+        let summary = mirrors
+            .iter()
+            .map(|mirror| {
+                (
+                    mirror.server.as_str(),
+                    mirror.enabled,
+                    mirror.country.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "https://fast.example.org/$repo/os/$arch",
+                    true,
+                    Some("Germany")
+                ),
+                (
+                    "https://slow.example.org/$repo/os/$arch",
+                    false,
+                    Some("Germany")
+                ),
+                (
+                    "https://down.example.org/$repo/os/$arch",
+                    false,
+                    Some("Sweden")
+                ),
+                (
+                    "https://unmeasured.example.org/$repo/os/$arch",
+                    true,
+                    Some("Sweden")
+                ),
+            ]
+        );
+        assert_eq!(
+            servers(MIRRORLIST),
+            [
+                "https://fast.example.org/$repo/os/$arch",
+                "https://unmeasured.example.org/$repo/os/$arch"
+            ]
+        );
+    }
+
+    #[test]
+    fn ranks_measured_mirrors_by_speed() {
+        let mut mirrors = parse(MIRRORLIST);
+        let speeds = mirrors
+            .iter()
+            .filter(|mirror| !mirror.server.contains("unmeasured."))
+            .map(|mirror| {
+                let speed = measure(&StubFetch, &mirror.server).map_err(|e| e.to_string());
+                (mirror.server.clone(), speed)
+            })
+            .collect::<HashMap<_, _>>();
+        rank(&mut mirrors, &speeds);
+
+        let ranked = mirrors
+            .iter()
+            .map(|mirror| mirror.server.split('/').nth(2).unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            [
+                "fast.example.org",
+                "slow.example.org",
+                "down.example.org",
+                "unmeasured.example.org"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_a_backup_when_writing() {
+        let path = env::temp_dir().join(format!("ptu-mirrorlist-{}", std::process::id()));
+        fs::write(&path, MIRRORLIST).unwrap();
+
+        let mirrors = parse(MIRRORLIST);
+        let content = render(&mirrors[..1]);
+        let status = replace(&path, &content, false).unwrap();
+
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        let written = fs::read_to_string(&path).unwrap();
+        let kept = fs::read_to_string(&backup).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&backup).unwrap();
+
+        assert!(status.success());
+        assert_eq!(written, content);
+        assert_eq!(kept, MIRRORLIST);
+        assert_eq!(servers(&written), [mirrors[0].server.as_str()]);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::DateTime;
+use color_eyre::eyre;
+
+use crate::config::{self, NewsConfig};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const CACHE_FILE: &str = "news.xml";
+const READ_FILE: &str = "news-read";
+
+/// An entry of the news feed.
+#[derive(Clone)]
+pub(crate) struct Item {
+    /// The guid of the item, or its link.
+    pub(crate) id: String,
+    pub(crate) title: String,
+    pub(crate) link: String,
+    /// Unix timestamp.
+    pub(crate) published: Option<i64>,
+    /// The text of the item without its markup.
+    pub(crate) description: String,
+}
+
+/// The items of the news feed, newest first as the feed lists them.
+#[derive(Clone, Default)]
+pub(crate) struct Feed {
+    pub(crate) items: Vec<Item>,
+    /// When the cached copy shown was fetched, `None` if the feed was just fetched.
+    pub(crate) cached: Option<SystemTime>,
+}
+
+/// Fetches the feed, falling back to the copy cached by the last successful fetch.
+pub(crate) fn load(config: &NewsConfig) -> eyre::Result<Feed> {
+    let cache = config::cache_dir().map(|dir| dir.join(CACHE_FILE));
+
+    match fetch(&config.url) {
+        Ok(xml) => {
+            // without a cache the feed is only missing when offline
+            if let Some(cache) = &cache {
+                let _ = cache
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| fs::write(cache, &xml));
+            }
+            Ok(Feed {
+                items: parse(&xml),
+                cached: None,
+            })
+        }
+        Err(error) => {
+            let Some(cache) = cache.filter(|cache| cache.exists()) else {
+                return Err(error);
+            };
+            Ok(Feed {
+                items: parse(&fs::read_to_string(&cache)?),
+                cached: Some(fs::metadata(&cache)?.modified()?),
+            })
+        }
+    }
+}
+
+/// Reads a feed from a URL, or from a file for `file://` URLs and paths.
+fn fetch(url: &str) -> eyre::Result<String> {
+    if let Some(path) = url
+        .strip_prefix("file://")
+        .or(url.starts_with('/').then_some(url))
+    {
+        return Ok(fs::read_to_string(path)?);
+    }
+
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    Ok(agent.get(url).call()?.into_string()?)
+}
+
+/// The content of the first `tag` element in `xml`.
+fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    // the tag may have attributes, like `<guid isPermaLink="false">`
+    let start = xml.find(&format!("<{tag}"))?;
+    let rest = &xml[start..];
+    let content = rest.find('>')? + 1;
+    let end = rest.find(&format!("</{tag}>"))?;
+    rest.get(content..end)
+}
+
+/// The content of a CDATA section.
+fn cdata(raw: &str) -> Option<&str> {
+    raw.strip_prefix("<![CDATA[")?.strip_suffix("]]>")
+}
+
+/// Decodes the character data of an element, either CDATA or escaped text.
+fn text(raw: &str) -> String {
+    let raw = raw.trim();
+    match cdata(raw) {
+        Some(data) => data.to_owned(),
+        None => unescape(raw, false),
+    }
+}
+
+/// The character an entity like `amp;` or `#x26;` stands for, and the length of the entity.
+fn entity(rest: &str) -> Option<(char, usize)> {
+    let end = rest.find(';')?;
+    let character = match &rest[..end] {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        name => {
+            let code = match name.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+    Some((character, end + 1))
+}
+
+/// Decodes the entities of `text` in one pass. In `escaped` HTML an entity of the HTML is
+/// escaped once more, like `&amp;lt;`, and decodes to the character it stands for.
+fn unescape(text: &str, escaped: bool) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let nested = after
+            .strip_prefix("amp;")
+            .filter(|_| escaped)
+            .and_then(|nested| entity(nested).map(|(c, len)| (c, "amp;".len() + len)));
+        match nested.or_else(|| entity(after)) {
+            Some((character, len)) => {
+                decoded.push(character);
+                rest = &after[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = after;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Turns the HTML of a description, in CDATA or escaped, into plain text, keeping paragraphs
+/// and list items. The markup is removed before decoding, so nothing is unescaped twice.
+fn plain_text(raw: &str) -> String {
+    let raw = raw.trim();
+    let (html, escaped) = match cdata(raw) {
+        Some(data) => (data, false),
+        None => (raw, true),
+    };
+    let (open, close) = if escaped {
+        ("&lt;", "&gt;")
+    } else {
+        ("<", ">")
+    };
+
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find(open) {
+        text.push_str(&unescape(&rest[..start], escaped));
+        let Some(end) = rest[start..].find(close) else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + open.len()..start + end]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match tag.as_str() {
+            "/p" | "br" | "br/" | "/li" | "/pre" | "/h1" | "/h2" | "/h3" => text.push('\n'),
+            "li" => text.push_str("- "),
+            _ => {}
+        }
+        rest = &rest[start + end + close.len()..];
+    }
+    text.push_str(&unescape(rest, escaped));
+
+    let mut lines = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        // keep single blank lines between paragraphs
+        if line.is_empty() && lines.last().is_none_or(|last: &&str| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_owned()
+}
+
+/// Parses the items of an RSS feed.
+pub(crate) fn parse(xml: &str) -> Vec<Item> {
+    xml.split("<item>")
+        .skip(1)
+        .filter_map(|item| {
+            let item = &item[..item.find("</item>")?];
+            let title = text(element(item, "title")?);
+            let link = element(item, "link").map(text).unwrap_or_default();
+            let id = element(item, "guid")
+                .map(text)
+                .unwrap_or_else(|| link.clone());
+            Some(Item {
+                id,
+                title,
+                link,
+                published: element(item, "pubDate")
+                    .and_then(|date| DateTime::parse_from_rfc2822(text(date).as_str()).ok())
+                    .map(|date| date.timestamp()),
+                description: element(item, "description")
+                    .map(plain_text)
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// The ids of the news items that were read.
+#[derive(Default)]
+pub(crate) struct ReadItems {
+    ids: BTreeSet<String>,
+    /// One id per line, `$XDG_STATE_HOME/ptu/news-read` unless there is no home.
+    path: Option<PathBuf>,
+}
+
+impl ReadItems {
+    pub(crate) fn load() -> Self {
+        let path = config::state_dir().map(|dir| dir.join(READ_FILE));
+        let ids = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { ids, path }
+    }
+
+    pub(crate) fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub(crate) fn ids(&self) -> Vec<String> {
+        self.ids.iter().cloned().collect()
+    }
+
+    /// Marks items as read and saves the list.
+    pub(crate) fn mark(&mut self, ids: &[String]) -> eyre::Result<()> {
+        self.ids.extend(ids.iter().cloned());
+        match &self.path {
+            Some(path) => save(path, &self.ids()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn save(path: &Path, ids: &[String]) -> eyre::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = ids.join("\n");
+    content.push('\n');
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// The unread items published after `since`, all unread items if it is `None`.
+pub(crate) fn unread_since<'a>(
+    items: &'a [Item],
+    read: &ReadItems,
+    since: Option<i64>,
+) -> Vec<&'a Item> {
+    items
+        .iter()
+        .filter(|item| !read.contains(&item.id))
+        .filter(|item| match (since, item.published) {
+            (Some(since), Some(published)) => published > since,
+            _ => true,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0"><channel><title>Arch Linux: Recent news updates</title>
+<item><title>&lt;pkg&gt; needs manual intervention</title><link>https://archlinux.org/news/two/</link><description>&lt;p&gt;Run &lt;code&gt;pacman -Syu --overwrite '*'&lt;/code&gt; &amp;amp; reboot.&lt;/p&gt;&lt;p&gt;Tom &amp;amp;amp; Jerry&lt;/p&gt;</description><pubDate>Tue, 02 Jan 2024 12:00:00 +0000</pubDate><guid isPermaLink="false">tag:archlinux.org,2024:/news/two/</guid></item>
+<item><title><![CDATA[Ships & boats]]></title><link>https://archlinux.org/news/one/</link><description><![CDATA[<ul><li>one &lt; two</li><li>three</li></ul>]]></description><pubDate>Mon, 01 Jan 2024 12:00:00 +0000</pubDate></item>
+</channel></rss>"#;
+
+    fn read(ids: &[&str]) -> ReadItems {
+        ReadItems {
+            ids: ids.iter().map(|id| (*id).to_owned()).collect(),
+            path: None,
+        }
+    }
+
+    #[test]
+    fn parses_items() {
+        let items = parse(FEED);
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].id, "tag:archlinux.org,2024:/news/two/");
+        assert_eq!(items[0].title, "<pkg> needs manual intervention");
+        assert_eq!(items[0].link, "https://archlinux.org/news/two/");
+        assert_eq!(items[0].published, Some(1_704_196_800));
+
+        // without a guid the link identifies the item
+        assert_eq!(items[1].id, "https://archlinux.org/news/one/");
+        assert_eq!(items[1].title, "Ships & boats");
+        assert_eq!(items[1].published, Some(1_704_110_400));
+    }
+
+    #[test]
+    fn decodes_descriptions_once() {
+        let items = parse(FEED);
+        assert_eq!(
+            items[0].description,
+            "Run pacman -Syu --overwrite '*' & reboot.\nTom &amp; Jerry"
+        );
+        assert_eq!(items[1].description, "- one < two\n- three");
+    }
+
+    #[test]
+    fn plain_text_keeps_paragraphs() {
+        assert_eq!(
+            plain_text("<![CDATA[<p>first</p>\n\n\n<p>second<br>line</p>]]>"),
+            "first\n\nsecond\nline"
+        );
+        assert_eq!(plain_text("AT&amp;amp;T &amp;lt;3"), "AT&T <3");
+    }
+
+    #[test]
+    fn lists_unread_items_after_a_time() {
+        let items = parse(FEED);
+        let unread = |read: &ReadItems, since| {
+            unread_since(&items, read, since)
+                .into_iter()
+                .map(|item| item.link.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            unread(&read(&[]), None),
+            [
+                "https://archlinux.org/news/two/",
+                "https://archlinux.org/news/one/"
+            ]
+        );
+        assert_eq!(
+            unread(&read(&[]), Some(1_704_150_000)),
+            ["https://archlinux.org/news/two/"]
+        );
+        assert_eq!(
+            unread(&read(&["tag:archlinux.org,2024:/news/two/"]), None),
+            ["https://archlinux.org/news/one/"]
+        );
+    }
+}
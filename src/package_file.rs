@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+
+use crate::pacman::Package;
+
+/// A directory or package file shown in the file picker.
+#[derive(Clone)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) is_dir: bool,
+    /// Size in bytes, zero for directories.
+    pub(crate) size: u64,
+}
+
+/// Whether a file is a package archive such as `.pkg.tar.zst`, leaving out signatures.
+pub(crate) fn is_package_file(file_name: &str) -> bool {
+    file_name.contains(".pkg.tar") && !file_name.ends_with(".sig")
+}
+
+/// The subdirectories and package files of `dir`, directories first, hidden ones left out.
+pub(crate) fn list(dir: &Path) -> eyre::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(dir)?.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        // follows symlinks, so linked directories can be entered
+        let Ok(metadata) = fs::metadata(entry.path()) else {
+            continue;
+        };
+        if !metadata.is_dir() && !is_package_file(&name) {
+            continue;
+        }
+        entries.push(Entry {
+            name,
+            path: entry.path(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+        });
+    }
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// What installing a package file does to the installed version.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) enum Change {
+    Install,
+    /// Upgrades from the given version.
+    Upgrade(String),
+    /// Downgrades from the given version.
+    Downgrade(String),
+    Reinstall,
+}
+
+impl Change {
+    pub(crate) fn new(package: &Package) -> Self {
+        let Some(installed) = &package.installed_version else {
+            return Change::Install;
+        };
+        match alpm::vercmp(package.version.as_str(), installed.as_str()) {
+            Ordering::Greater => Change::Upgrade(installed.clone()),
+            Ordering::Less => Change::Downgrade(installed.clone()),
+            Ordering::Equal => Change::Reinstall,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Install => f.write_str("new install"),
+            Change::Upgrade(installed) => write!(f, "upgrade from {installed}"),
+            Change::Downgrade(installed) => write!(f, "downgrade from {installed}"),
+            Change::Reinstall => f.write_str("reinstall of the installed version"),
+        }
+    }
+}
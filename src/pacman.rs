@@ -1,49 +1,761 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
 
-use alpm::{Alpm, SigLevel};
+use alpm::{
+    Alpm, AnyEvent, DownloadEvent, PackageFrom, PackageOperation, PackageReason, PackageValidation,
+    PrepareData, PrepareError, SigLevel, TransFlag,
+};
+use clap::Args;
 use color_eyre::eyre;
+use serde::Serialize;
+
+use crate::aur::{Aur, AurInfo};
+use crate::config::Config;
+use crate::hold::Holds;
+use crate::ignore;
+use crate::keyring::SigLevels;
+use crate::mirrorlist;
+use crate::sync::DbStatus;
 
 pub(crate) struct Pacman {
     handle: Alpm,
+    aur: Option<Aur>,
+    mirrorlist: PathBuf,
 }
 
 impl Pacman {
-    pub(crate) fn new() -> eyre::Result<Self> {
-        let handle = Alpm::new("/", "/var/lib/pacman")?;
+    pub(crate) fn new(config: &Config) -> eyre::Result<Self> {
+        let mut handle = Alpm::new("/", "/var/lib/pacman")?;
+        handle.set_gpgdir(config.gpg_dir.as_os_str().as_encoded_bytes())?;
+
+        let levels = SigLevels::read(Path::new(ignore::PACMAN_CONF));
+        handle.set_default_siglevel(levels.default())?;
+        for name in ["core", "extra", "community"] {
+            handle.register_syncdb(name, levels.repo(name))?;
+        }
 
-        handle.register_syncdb("core", SigLevel::USE_DEFAULT)?;
-        handle.register_syncdb("extra", SigLevel::USE_DEFAULT)?;
-        handle.register_syncdb("community", SigLevel::USE_DEFAULT)?;
+        let aur = config.aur.enabled.then(|| Aur::new(&config.aur));
+
+        Ok(Self {
+            handle,
+            aur,
+            mirrorlist: config.mirrorlist.clone(),
+        })
+    }
 
-        Ok(Self { handle })
+    pub(crate) fn aur(&self) -> Option<&Aur> {
+        self.aur.as_ref()
     }
 
-    pub(crate) fn search_package(&self, package_name: &str) -> eyre::Result<Vec<Package>> {
+    pub(crate) fn search_syncdbs(&self, package_name: &str) -> eyre::Result<Vec<Package>> {
         let mut packages = Vec::new();
 
         for db in self.handle.syncdbs() {
             for pkg in db.search([package_name].iter())? {
                 let localdb = self.handle.localdb();
                 let localpkg = localdb.pkg(pkg.name());
-                packages.push(Package {
-                    name: pkg.name().to_owned(),
-                    source: db.name().to_owned(),
-                    installed: localpkg.is_ok(),
-                    description: pkg.desc().unwrap_or("-").to_owned(),
-                    version: pkg.version().to_string(),
-                    filename: pkg.filename().unwrap_or("-").to_owned(),
-                    base: pkg.base().unwrap_or("-").to_owned(),
-                    url: pkg.url().unwrap_or("-").to_owned(),
-                    packager: pkg.packager().unwrap_or("-").to_owned(),
-                    md5sum: pkg.md5sum().unwrap_or("-").to_owned(),
-                    sha256sum: pkg.sha256sum().unwrap_or("-").to_owned(),
-                    arch: pkg.arch().unwrap_or("-").to_owned(),
-                });
+                let mut package = Package::new(pkg, db.name());
+                if let Ok(localpkg) = localpkg {
+                    let local = Package::new(localpkg, localdb.name());
+                    package.installed_version = local.installed_version.clone();
+                    package.local = Some(Box::new(local));
+                }
+                packages.push(package);
             }
         }
 
         Ok(packages)
     }
+
+    /// Looks a package up in the sync databases, with its installed counterpart attached.
+    pub(crate) fn sync_package(&self, package_name: &str) -> Option<Package> {
+        self.handle.syncdbs().iter().find_map(|db| {
+            let pkg = db.pkg(package_name).ok()?;
+            let package = Package::new(pkg, db.name());
+            Some(self.with_local(vec![package]).remove(0))
+        })
+    }
+
+    /// Looks an installed package up in the local database.
+    pub(crate) fn local_package(&self, package_name: &str) -> Option<Package> {
+        let localdb = self.handle.localdb();
+        localdb
+            .pkg(package_name)
+            .ok()
+            .map(|localpkg| Package::new(localpkg, localdb.name()))
+    }
+
+    /// Attaches the installed counterparts to packages found outside the sync databases.
+    pub(crate) fn with_local(&self, mut packages: Vec<Package>) -> Vec<Package> {
+        let localdb = self.handle.localdb();
+        for package in &mut packages {
+            if let Ok(localpkg) = localdb.pkg(package.name.as_str()) {
+                let local = Package::new(localpkg, localdb.name());
+                package.installed_version = local.installed_version.clone();
+                package.local = Some(Box::new(local));
+            }
+        }
+        packages
+    }
+
+    /// Packages installed as dependencies that no installed package requires or optionally
+    /// requires, like `pacman -Qdt`.
+    pub(crate) fn orphans(&self) -> Vec<Package> {
+        self.orphans_after(&HashMap::new())
+    }
+
+    /// The orphans there would be once the install reasons of some packages are changed.
+    pub(crate) fn orphans_after(&self, reasons: &HashMap<String, Reason>) -> Vec<Package> {
+        let localdb = self.handle.localdb();
+        localdb
+            .pkgs()
+            .iter()
+            .filter(|pkg| {
+                reasons
+                    .get(pkg.name())
+                    .map_or(pkg.reason() == PackageReason::Depend, |reason| {
+                        *reason == Reason::Depend
+                    })
+            })
+            .filter(|pkg| pkg.required_by().is_empty() && pkg.optional_for().is_empty())
+            .map(|pkg| Package::new(pkg, localdb.name()))
+            .collect()
+    }
+
+    /// Whether other installed packages depend on an installed package.
+    pub(crate) fn is_required(&self, package_name: &str) -> bool {
+        self.handle
+            .localdb()
+            .pkg(package_name)
+            .is_ok_and(|pkg| !pkg.required_by().is_empty())
+    }
+
+    /// Changes the install reasons of installed packages in the local database. Takes the
+    /// database lock like `pacman -D`, so it fails with [`alpm::Error::HandleLock`] without
+    /// write access to the database directory or while pacman runs.
+    pub(crate) fn set_reasons(&mut self, reasons: &[(String, Reason)]) -> alpm::Result<()> {
+        self.handle.trans_init(TransFlag::NONE)?;
+        let result = self.write_reasons(reasons);
+        let released = self.handle.trans_release();
+        result.and(released)
+    }
+
+    /// Writes install reasons to the local database, the lock has to be held.
+    fn write_reasons(&self, reasons: &[(String, Reason)]) -> alpm::Result<()> {
+        let localdb = self.handle.localdb();
+        for (package_name, reason) in reasons {
+            localdb
+                .pkg(package_name.as_str())?
+                .set_reason((*reason).into())?;
+        }
+        Ok(())
+    }
+
+    /// Installs and removes packages in one transaction, then writes the install reasons
+    /// before releasing the database lock. Fails with [`alpm::Error::HandleLock`] like
+    /// [`Pacman::set_reasons`], nothing is changed then.
+    pub(crate) fn commit(
+        &mut self,
+        transaction: &Transaction,
+        config: &Config,
+    ) -> eyre::Result<()> {
+        let servers = fs::read_to_string(&self.mirrorlist)
+            .map(|content| mirrorlist::servers(&content))
+            .unwrap_or_default();
+        for db in self.handle.syncdbs_mut() {
+            for server in &servers {
+                db.add_server(mirrorlist::server_url(server, db.name()))?;
+            }
+        }
+        for dir in &config.cache_dirs {
+            self.handle
+                .add_cachedir(dir.as_os_str().as_encoded_bytes())?;
+        }
+        self.handle
+            .set_logfile(config.log_file.as_os_str().as_encoded_bytes())?;
+        // libalpm only adds the hook directory packages install to
+        self.handle.add_hookdir(HOOK_DIR)?;
+        self.handle.set_event_cb((), print_event);
+
+        self.handle.trans_init(TransFlag::NEEDED)?;
+        let result = self.add_targets(transaction).and_then(|()| {
+            self.handle.trans_prepare().map_err(prepare_error)?;
+            self.handle
+                .trans_commit()
+                .map_err(|error| eyre::eyre!("could not commit the transaction: {error}"))?;
+            let reasons = transaction
+                .explicit
+                .iter()
+                .map(|name| (name.clone(), Reason::Explicit))
+                .chain(
+                    transaction
+                        .depend
+                        .iter()
+                        .map(|name| (name.clone(), Reason::Depend)),
+                )
+                .collect::<Vec<_>>();
+            Ok(self.write_reasons(&reasons)?)
+        });
+        let released = self.handle.trans_release();
+        result?;
+        Ok(released?)
+    }
+
+    fn add_targets(&self, transaction: &Transaction) -> eyre::Result<()> {
+        for name in &transaction.install {
+            let pkg = self
+                .handle
+                .syncdbs()
+                .iter()
+                .find_map(|db| db.pkg(name.as_str()).ok())
+                .ok_or_else(|| eyre::eyre!("{name} is in no sync database"))?;
+            self.handle
+                .trans_add_pkg(pkg)
+                .map_err(|error| error.error)?;
+        }
+        // what pacman uses for package files when pacman.conf does not say
+        let level = SigLevel::PACKAGE | SigLevel::PACKAGE_OPTIONAL;
+        for path in &transaction.files {
+            let pkg = self
+                .handle
+                .pkg_load(path.as_os_str().as_encoded_bytes(), true, level)
+                .map_err(|error| eyre::eyre!("could not load {}: {error}", path.display()))?;
+            self.handle
+                .trans_add_pkg(pkg)
+                .map_err(|error| error.error)?;
+        }
+        for name in &transaction.remove {
+            let pkg = self.handle.localdb().pkg(name.as_str())?;
+            self.handle.trans_remove_pkg(pkg)?;
+        }
+        Ok(())
+    }
+
+    /// Installed packages present only because others optionally depend on them.
+    pub(crate) fn optional_only(&self) -> Vec<(Package, Vec<String>)> {
+        let localdb = self.handle.localdb();
+        localdb
+            .pkgs()
+            .iter()
+            .filter(|pkg| pkg.reason() == PackageReason::Depend && pkg.required_by().is_empty())
+            .filter_map(|pkg| {
+                let optional_for = pkg.optional_for().into_iter().collect::<Vec<_>>();
+                (!optional_for.is_empty())
+                    .then(|| (Package::new(pkg, localdb.name()), optional_for))
+            })
+            .collect()
+    }
+
+    /// The optional dependencies of a package with how they are satisfied.
+    pub(crate) fn optdepends(&self, package: &Package) -> Vec<Optdep> {
+        let localdb = self.handle.localdb();
+        package
+            .optdepends
+            .iter()
+            .map(|dep| Optdep {
+                installed: localdb
+                    .pkgs()
+                    .find_satisfier(dep.to_depstring())
+                    .map(|pkg| pkg.name().to_owned()),
+                available: self.sync_satisfier(dep),
+                dep: dep.clone(),
+            })
+            .collect()
+    }
+
+    /// The installed packages with the sync database each is found in, `None` for foreign
+    /// packages.
+    pub(crate) fn installed_packages(&self) -> Vec<(Package, Option<String>)> {
+        let localdb = self.handle.localdb();
+        localdb
+            .pkgs()
+            .iter()
+            .map(|pkg| {
+                let repo = self
+                    .handle
+                    .syncdbs()
+                    .iter()
+                    .find(|db| db.pkg(pkg.name()).is_ok())
+                    .map(|db| db.name().to_owned());
+                (Package::new(pkg, localdb.name()), repo)
+            })
+            .collect()
+    }
+
+    /// The members of a group across the sync databases, `None` if none has the group.
+    pub(crate) fn group_members(&self, group: &str) -> Option<Vec<String>> {
+        let mut members = None::<Vec<String>>;
+        for db in self.handle.syncdbs() {
+            if let Ok(group) = db.group(group) {
+                members
+                    .get_or_insert_with(Vec::new)
+                    .extend(group.packages().iter().map(|pkg| pkg.name().to_owned()));
+            }
+        }
+        members
+    }
+
+    /// The version of a package in the first sync database that has it.
+    pub(crate) fn sync_version(&self, package_name: &str) -> Option<String> {
+        self.handle
+            .syncdbs()
+            .iter()
+            .find_map(|db| db.pkg(package_name).ok())
+            .map(|pkg| pkg.version().to_string())
+    }
+
+    /// The versions of all installed packages by name.
+    pub(crate) fn installed_versions(&self) -> HashMap<String, String> {
+        self.handle
+            .localdb()
+            .pkgs()
+            .iter()
+            .map(|pkg| (pkg.name().to_owned(), pkg.version().to_string()))
+            .collect()
+    }
+
+    /// The files installed packages back up when they are modified, with their owners.
+    pub(crate) fn backup_files(&self) -> HashMap<PathBuf, String> {
+        let root = Path::new(self.handle.root());
+        let mut files = HashMap::new();
+        for pkg in self.handle.localdb().pkgs() {
+            for backup in pkg.backup() {
+                files.insert(root.join(backup.name()), pkg.name().to_owned());
+            }
+        }
+        files
+    }
+
+    /// Installed packages with a newer version in the sync databases.
+    pub(crate) fn upgrades(&self) -> Vec<Package> {
+        let localdb = self.handle.localdb();
+        let mut packages = Vec::new();
+
+        for localpkg in localdb.pkgs() {
+            let Some(pkg) = localpkg.sync_new_version(self.handle.syncdbs()) else {
+                continue;
+            };
+            let source = pkg.db().map_or("-", |db| db.name());
+            let local = Package::new(localpkg, localdb.name());
+            let mut package = Package::new(pkg, source);
+            package.installed_version = local.installed_version.clone();
+            package.local = Some(Box::new(local));
+            packages.push(package);
+        }
+
+        packages
+    }
+
+    /// The installed packages owning `path`, like `pacman -Qo`.
+    pub(crate) fn owners(&self, path: &str) -> Vec<Package> {
+        // the file lists store paths relative to the root
+        let path = path.strip_prefix(self.handle.root()).unwrap_or(path);
+        let localdb = self.handle.localdb();
+        localdb
+            .pkgs()
+            .iter()
+            .filter(|pkg| pkg.files().contains(path).is_some())
+            .map(|pkg| Package::new(pkg, localdb.name()))
+            .collect()
+    }
+
+    /// Whether an installed package satisfies `dep`.
+    pub(crate) fn is_satisfied(&self, dep: &Dep) -> bool {
+        self.handle
+            .localdb()
+            .pkgs()
+            .find_satisfier(dep.to_depstring())
+            .is_some()
+    }
+
+    /// When each sync database was last downloaded.
+    pub(crate) fn sync_statuses(&self) -> Vec<DbStatus> {
+        let sync_dir = Path::new(self.handle.dbpath()).join("sync");
+        self.handle
+            .syncdbs()
+            .iter()
+            .map(|db| DbStatus {
+                name: db.name().to_owned(),
+                modified: fs::metadata(sync_dir.join(format!("{}.db", db.name())))
+                    .and_then(|metadata| metadata.modified())
+                    .ok(),
+            })
+            .collect()
+    }
+
+    /// Downloads the sync databases that changed from the mirrors in the mirrorlist, passing
+    /// the file name, downloaded and total bytes of each download to `progress`.
+    ///
+    /// Takes the database lock, so it fails with [`alpm::Error::HandleLock`] without write
+    /// access to the database directory.
+    pub(crate) fn update_syncdbs(
+        &mut self,
+        progress: impl FnMut(&str, i64, i64) + 'static,
+    ) -> alpm::Result<bool> {
+        let servers = fs::read_to_string(&self.mirrorlist)
+            .map(|content| mirrorlist::servers(&content))
+            .unwrap_or_default();
+        for db in self.handle.syncdbs_mut() {
+            for server in &servers {
+                db.add_server(mirrorlist::server_url(server, db.name()))?;
+            }
+        }
+
+        self.handle
+            .set_dl_cb(progress, |file_name, event, progress| {
+                if let DownloadEvent::Progress(event) = event.event() {
+                    progress(file_name, event.downloaded, event.total);
+                }
+            });
+        self.handle.syncdbs_mut().update(false)
+    }
+
+    pub(crate) fn root(&self) -> &str {
+        self.handle.root()
+    }
+
+    /// The directory of an installed package in the local database.
+    pub(crate) fn local_dir(&self, package_name: &str) -> Option<PathBuf> {
+        let pkg = self.handle.localdb().pkg(package_name).ok()?;
+        let dir = format!("{}-{}", pkg.name(), pkg.version());
+        Some(PathBuf::from(self.handle.dbpath()).join("local").join(dir))
+    }
+
+    /// The install scriptlet of an installed package.
+    pub(crate) fn install_script(&self, package_name: &str) -> Option<PathBuf> {
+        let pkg = self.handle.localdb().pkg(package_name).ok()?;
+        if !pkg.has_scriptlet() {
+            return None;
+        }
+        Some(self.local_dir(package_name)?.join("install"))
+    }
+
+    /// Reads the metadata of a package file without verifying it.
+    pub(crate) fn load_file(&self, path: &Path) -> eyre::Result<Package> {
+        let pkg =
+            self.handle
+                .pkg_load(path.as_os_str().as_encoded_bytes(), false, SigLevel::NONE)?;
+        Ok(Package::from_pkg(&pkg, "file"))
+    }
+
+    /// Verifies the signature of a package file, embedded or in a `.sig` file next to it.
+    pub(crate) fn verify_file(&self, path: &Path) -> Signature {
+        let result = self.handle.pkg_load(
+            path.as_os_str().as_encoded_bytes(),
+            false,
+            SigLevel::PACKAGE,
+        );
+        match result {
+            Ok(_) => Signature::Valid,
+            Err(alpm::Error::PkgMissingSig | alpm::Error::SigMissing) => Signature::Missing,
+            Err(alpm::Error::PkgInvalidSig | alpm::Error::SigInvalid) => Signature::Invalid,
+            Err(_) => Signature::Unverified,
+        }
+    }
+
+    /// How a package is signed and the ids of the keys that signed it, of the installed
+    /// version if there is one.
+    pub(crate) fn signature(&self, package: &Package) -> PackageSignature {
+        // the status and the keys of different versions would not belong together
+        let package = package.local.as_deref().unwrap_or(package);
+        // the local database does not keep signatures, the sync one does for the same version
+        let signature = package.signature.clone().or_else(|| {
+            let pkg = self
+                .handle
+                .syncdbs()
+                .iter()
+                .find_map(|db| db.pkg(package.name.as_str()).ok())?;
+            (pkg.version().as_str() == package.version)
+                .then(|| pkg.base64_sig().map(str::to_owned))
+                .flatten()
+        });
+
+        let status = if package.validation.contains(&Validation::Signature) {
+            Signature::Valid
+        } else if signature.is_some() {
+            Signature::Unverified
+        } else {
+            Signature::Missing
+        };
+        let key_ids = signature
+            .and_then(|signature| alpm::decode_signature(signature).ok())
+            .and_then(|signature| {
+                self.handle
+                    .extract_keyid(package.name.as_str(), &signature)
+                    .ok()
+            })
+            .map(|key_ids| key_ids.iter().map(str::to_owned).collect())
+            .unwrap_or_default();
+
+        PackageSignature {
+            version: package.version.clone(),
+            status,
+            key_ids,
+        }
+    }
+
+    /// How many installed packages each packager built.
+    pub(crate) fn packagers(&self) -> HashMap<String, usize> {
+        let mut packagers = HashMap::new();
+        for pkg in self.handle.localdb().pkgs() {
+            if let Some(packager) = pkg.packager() {
+                *packagers.entry(packager.to_owned()).or_default() += 1;
+            }
+        }
+        packagers
+    }
+
+    /// The name of a sync package satisfying `dep`.
+    pub(crate) fn sync_satisfier(&self, dep: &Dep) -> Option<String> {
+        self.handle
+            .syncdbs()
+            .find_satisfier(dep.to_depstring())
+            .map(|pkg| pkg.name().to_owned())
+    }
+}
+
+/// The hooks of the system, besides the ones packages install.
+const HOOK_DIR: &str = "/etc/pacman.d/hooks/";
+
+/// Package changes made in one transaction, so a failure leaves nothing half done.
+#[derive(Args, Default)]
+pub(crate) struct Transaction {
+    /// Packages to install or upgrade from the sync databases
+    #[arg(long)]
+    pub(crate) install: Vec<String>,
+    /// Package files to install, upgrade or downgrade to
+    #[arg(long)]
+    pub(crate) files: Vec<PathBuf>,
+    /// Installed packages to remove
+    #[arg(long)]
+    pub(crate) remove: Vec<String>,
+    /// Packages to mark as explicitly installed once the others are changed
+    #[arg(long)]
+    pub(crate) explicit: Vec<String>,
+    /// Packages to mark as installed as dependencies once the others are changed
+    #[arg(long)]
+    pub(crate) depend: Vec<String>,
+}
+
+impl Transaction {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.install.is_empty()
+            && self.files.is_empty()
+            && self.remove.is_empty()
+            && self.explicit.is_empty()
+            && self.depend.is_empty()
+    }
+
+    /// The arguments of the hidden `commit` command that commits the transaction.
+    fn args(&self) -> Vec<OsString> {
+        let mut args = vec![OsString::from("commit")];
+        let names = [
+            ("--install", &self.install),
+            ("--remove", &self.remove),
+            ("--explicit", &self.explicit),
+            ("--depend", &self.depend),
+        ];
+        for (flag, names) in names {
+            for name in names {
+                args.extend([OsString::from(flag), OsString::from(name)]);
+            }
+        }
+        for path in &self.files {
+            args.extend([OsString::from("--files"), path.clone().into_os_string()]);
+        }
+        args
+    }
+}
+
+/// The paths of the config a transaction is committed with, passed along when it is
+/// committed through sudo since root would load its own config.
+#[derive(Args, Default)]
+pub(crate) struct ConfigPaths {
+    /// Log the transaction to this file instead of the configured one
+    #[arg(long)]
+    log_file: Option<PathBuf>,
+    /// Download packages from the servers of this mirrorlist
+    #[arg(long)]
+    mirrorlist: Option<PathBuf>,
+    /// Verify signatures with this keyring
+    #[arg(long)]
+    gpg_dir: Option<PathBuf>,
+    /// Look for and download packages to this cache directory, may be repeated
+    #[arg(long = "cache-dir")]
+    cache_dirs: Vec<PathBuf>,
+}
+
+impl ConfigPaths {
+    fn new(config: &Config) -> Self {
+        Self {
+            log_file: Some(config.log_file.clone()),
+            mirrorlist: Some(config.mirrorlist.clone()),
+            gpg_dir: Some(config.gpg_dir.clone()),
+            cache_dirs: config.cache_dirs.clone(),
+        }
+    }
+
+    /// `config` with the paths given in place of its own.
+    pub(crate) fn apply(self, config: &Config) -> Config {
+        let mut config = config.clone();
+        if let Some(log_file) = self.log_file {
+            config.log_file = log_file;
+        }
+        if let Some(mirrorlist) = self.mirrorlist {
+            config.mirrorlist = mirrorlist;
+        }
+        if let Some(gpg_dir) = self.gpg_dir {
+            config.gpg_dir = gpg_dir;
+        }
+        if !self.cache_dirs.is_empty() {
+            config.cache_dirs = self.cache_dirs;
+        }
+        config
+    }
+
+    fn args(&self) -> Vec<OsString> {
+        let paths = [
+            ("--log-file", &self.log_file),
+            ("--mirrorlist", &self.mirrorlist),
+            ("--gpg-dir", &self.gpg_dir),
+        ];
+        let mut args = Vec::new();
+        for (flag, path) in paths {
+            if let Some(path) = path {
+                args.extend([OsString::from(flag), path.clone().into_os_string()]);
+            }
+        }
+        for dir in &self.cache_dirs {
+            args.extend([OsString::from("--cache-dir"), dir.clone().into_os_string()]);
+        }
+        args
+    }
+}
+
+/// Commits a transaction through ptu run with sudo, for when the database lock cannot be
+/// taken.
+fn commit_with_sudo(transaction: &Transaction, config: &Config) -> eyre::Result<ExitStatus> {
+    let status = Command::new("sudo")
+        .arg(env::current_exe()?)
+        .args(transaction.args())
+        .args(ConfigPaths::new(config).args())
+        .status()?;
+    Ok(status)
+}
+
+/// Commits a transaction, through sudo if the database lock cannot be taken.
+///
+/// Installing from the sync databases is refused while packages that are not held have
+/// upgrades, as that would be a partial upgrade. No upgrade is added to the transaction since
+/// it would undo the downgrades of a revert.
+pub(crate) fn commit(transaction: &Transaction, config: &Config) -> eyre::Result<ExitStatus> {
+    let mut pacman = Pacman::new(config)?;
+    if !transaction.install.is_empty() {
+        let holds = Holds::load(&config.ignore_file);
+        let upgrades = pacman
+            .upgrades()
+            .into_iter()
+            .filter(|package| !holds.contains(&package.name))
+            .count();
+        if upgrades > 0 {
+            eyre::bail!(
+                "{upgrades} packages have upgrades, upgrade the system first since installing from the sync databases before that would be a partial upgrade"
+            );
+        }
+    }
+    match pacman.commit(transaction, config) {
+        Ok(()) => Ok(ExitStatus::default()),
+        Err(error) if matches!(error.downcast_ref(), Some(alpm::Error::HandleLock)) => {
+            commit_with_sudo(transaction, config)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Prints the progress of a transaction like pacman does.
+fn print_event(event: AnyEvent, _: &mut ()) {
+    match event.event() {
+        alpm::Event::PackageOperationStart(event) => match event.operation() {
+            PackageOperation::Install(new) => {
+                println!("installing {} ({})", new.name(), new.version());
+            }
+            PackageOperation::Upgrade(new, old) => {
+                println!(
+                    "upgrading {} ({} -> {})",
+                    new.name(),
+                    old.version(),
+                    new.version()
+                );
+            }
+            PackageOperation::Downgrade(new, old) => {
+                println!(
+                    "downgrading {} ({} -> {})",
+                    new.name(),
+                    old.version(),
+                    new.version()
+                );
+            }
+            PackageOperation::Reinstall(new, _) => {
+                println!("reinstalling {} ({})", new.name(), new.version());
+            }
+            PackageOperation::Remove(old) => {
+                println!("removing {} ({})", old.name(), old.version());
+            }
+        },
+        alpm::Event::RetrieveStart => println!("downloading packages..."),
+        alpm::Event::ScriptletInfo(info) => print!("{}", info.line()),
+        alpm::Event::HookRunStart(hook) => println!(
+            "({}/{}) {}",
+            hook.position(),
+            hook.total(),
+            hook.desc().unwrap_or(hook.name())
+        ),
+        alpm::Event::PacnewCreated(pacnew) => {
+            println!("{} installed as {}.pacnew", pacnew.file(), pacnew.file());
+        }
+        _ => {}
+    }
+}
+
+/// Explains why a transaction could not be prepared.
+fn prepare_error(error: PrepareError) -> eyre::Report {
+    // the data is only there for these errors
+    let details = match error.error() {
+        alpm::Error::PkgInvalidArch
+        | alpm::Error::UnsatisfiedDeps
+        | alpm::Error::ConflictingDeps => match error.data() {
+            PrepareData::PkgInvalidArch(pkgs) => pkgs
+                .iter()
+                .map(|pkg| format!("{} is built for another architecture", pkg.name()))
+                .collect(),
+            PrepareData::UnsatisfiedDeps(missing) => missing
+                .iter()
+                .map(|missing| format!("{} requires {}", missing.target(), missing.depend()))
+                .collect(),
+            PrepareData::ConflictingDeps(conflicts) => conflicts
+                .iter()
+                .map(|conflict| {
+                    format!(
+                        "{} and {} are in conflict",
+                        conflict.package1().name(),
+                        conflict.package2().name()
+                    )
+                })
+                .collect(),
+        },
+        _ => Vec::new(),
+    };
+    let mut message = format!("could not prepare the transaction: {error}");
+    for detail in details {
+        message.push_str(&format!("\n  {detail}"));
+    }
+    eyre::eyre!(message)
 }
 
 pub(crate) fn install_package(package_name: &str) -> eyre::Result<ExitStatus> {
@@ -55,36 +767,493 @@ pub(crate) fn install_package(package_name: &str) -> eyre::Result<ExitStatus> {
     Ok(status)
 }
 
-pub(crate) fn remove_package(package_name: &str) -> eyre::Result<ExitStatus> {
+/// Installs missing dependencies ahead of a build.
+pub(crate) fn install_dependencies(package_names: &[String]) -> eyre::Result<ExitStatus> {
     let status = Command::new("sudo")
         .arg("pacman")
-        .arg("-R")
-        .arg(package_name)
+        .arg("-S")
+        .arg("--needed")
+        .arg("--asdeps")
+        .args(package_names)
         .status()?;
     Ok(status)
 }
 
-pub(crate) fn update_install_package(package_name: &str) -> eyre::Result<ExitStatus> {
+/// Installs built package files.
+pub(crate) fn install_files(paths: &[PathBuf], as_deps: bool) -> eyre::Result<ExitStatus> {
+    let mut command = Command::new("sudo");
+    command.arg("pacman").arg("-U");
+    if as_deps {
+        command.arg("--asdeps");
+    }
+    let status = command.args(paths).status()?;
+    Ok(status)
+}
+
+/// Installs a package file or URL, replacing the installed version.
+pub(crate) fn install_target(target: &str) -> eyre::Result<ExitStatus> {
     let status = Command::new("sudo")
         .arg("pacman")
-        .arg("-Syu")
+        .arg("-U")
+        .arg(target)
+        .status()?;
+    Ok(status)
+}
+
+/// Changes install reasons like [`Pacman::set_reasons`] through `pacman -D` and sudo.
+pub(crate) fn set_reasons(reasons: &[(String, Reason)]) -> eyre::Result<ExitStatus> {
+    for reason in [Reason::Explicit, Reason::Depend] {
+        let package_names = reasons
+            .iter()
+            .filter(|(_, r)| *r == reason)
+            .map(|(package_name, _)| package_name)
+            .collect::<Vec<_>>();
+        if package_names.is_empty() {
+            continue;
+        }
+        let flag = match reason {
+            Reason::Explicit => "--asexplicit",
+            Reason::Depend => "--asdeps",
+        };
+        let status = Command::new("sudo")
+            .arg("pacman")
+            .arg("-D")
+            .arg(flag)
+            .args(package_names)
+            .status()?;
+        if !status.success() {
+            return Ok(status);
+        }
+    }
+    Ok(ExitStatus::default())
+}
+
+pub(crate) fn remove_package(package_name: &str) -> eyre::Result<ExitStatus> {
+    let status = Command::new("sudo")
+        .arg("pacman")
+        .arg("-R")
         .arg(package_name)
         .status()?;
     Ok(status)
 }
 
-#[derive(Clone, Default)]
+/// Upgrades the system along with installing a package, leaving the held packages as they
+/// are.
+pub(crate) fn update_install_package(
+    package_name: &str,
+    held: &[String],
+) -> eyre::Result<ExitStatus> {
+    let mut command = Command::new("sudo");
+    command.arg("pacman").arg("-Syu");
+    if !held.is_empty() {
+        command.arg("--ignore").arg(held.join(","));
+    }
+    let status = command.arg(package_name).status()?;
+    Ok(status)
+}
+
+#[derive(Clone, Default, Serialize)]
 pub(crate) struct Package {
     pub(crate) name: String,
     pub(crate) source: String,
-    pub(crate) installed: bool,
-    pub(crate) description: String,
     pub(crate) version: String,
-    pub(crate) filename: String,
-    pub(crate) base: String,
-    pub(crate) url: String,
-    pub(crate) packager: String,
-    pub(crate) md5sum: String,
-    pub(crate) sha256sum: String,
-    pub(crate) arch: String,
+    pub(crate) installed_version: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) filename: Option<String>,
+    pub(crate) base: Option<String>,
+    pub(crate) url: Option<String>,
+    pub(crate) packager: Option<String>,
+    pub(crate) md5sum: Option<String>,
+    pub(crate) sha256sum: Option<String>,
+    pub(crate) arch: Option<String>,
+    /// Download size in bytes, zero for local packages.
+    pub(crate) download_size: u64,
+    /// Installed size in bytes.
+    pub(crate) installed_size: u64,
+    /// Unix timestamp.
+    pub(crate) build_date: i64,
+    /// Unix timestamp, only known for local packages.
+    pub(crate) install_date: Option<i64>,
+    pub(crate) licenses: Vec<String>,
+    pub(crate) groups: Vec<String>,
+    pub(crate) depends: Vec<Dep>,
+    pub(crate) optdepends: Vec<Dep>,
+    pub(crate) makedepends: Vec<Dep>,
+    pub(crate) checkdepends: Vec<Dep>,
+    pub(crate) provides: Vec<Dep>,
+    pub(crate) conflicts: Vec<Dep>,
+    pub(crate) replaces: Vec<Dep>,
+    /// Only known for local packages.
+    pub(crate) reason: Option<Reason>,
+    /// Only known for local packages.
+    pub(crate) validation: Vec<Validation>,
+    /// Base64 encoded PGP signature.
+    pub(crate) signature: Option<String>,
+    /// Only known for AUR packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) aur: Option<AurInfo>,
+    /// The installed counterpart of a sync package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) local: Option<Box<Package>>,
+}
+
+impl Package {
+    fn new(pkg: &alpm::Package, source: &str) -> Self {
+        Self {
+            download_size: u64::try_from(pkg.download_size()).unwrap_or_default(),
+            ..Self::from_pkg(pkg, source)
+        }
+    }
+
+    /// Package files have no download size.
+    fn from_pkg(pkg: &alpm::Pkg, source: &str) -> Self {
+        let is_local = pkg.origin() == PackageFrom::LocalDb;
+
+        Self {
+            name: pkg.name().to_owned(),
+            source: source.to_owned(),
+            version: pkg.version().to_string(),
+            installed_version: is_local.then(|| pkg.version().to_string()),
+            description: pkg.desc().map(str::to_owned),
+            filename: pkg.filename().map(str::to_owned),
+            base: pkg.base().map(str::to_owned),
+            url: pkg.url().map(str::to_owned),
+            packager: pkg.packager().map(str::to_owned),
+            md5sum: pkg.md5sum().map(str::to_owned),
+            sha256sum: pkg.sha256sum().map(str::to_owned),
+            arch: pkg.arch().map(str::to_owned),
+            download_size: 0,
+            installed_size: u64::try_from(pkg.isize()).unwrap_or_default(),
+            build_date: pkg.build_date(),
+            install_date: pkg.install_date(),
+            licenses: pkg.licenses().iter().map(str::to_owned).collect(),
+            groups: pkg.groups().iter().map(str::to_owned).collect(),
+            depends: pkg.depends().iter().map(Dep::new).collect(),
+            optdepends: pkg.optdepends().iter().map(Dep::new).collect(),
+            makedepends: pkg.makedepends().iter().map(Dep::new).collect(),
+            checkdepends: pkg.checkdepends().iter().map(Dep::new).collect(),
+            provides: pkg.provides().iter().map(Dep::new).collect(),
+            conflicts: pkg.conflicts().iter().map(Dep::new).collect(),
+            replaces: pkg.replaces().iter().map(Dep::new).collect(),
+            reason: is_local.then(|| pkg.reason().into()),
+            validation: if is_local {
+                Validation::from_flags(pkg.validation())
+            } else {
+                Vec::new()
+            },
+            signature: pkg.base64_sig().map(str::to_owned),
+            aur: None,
+            local: None,
+        }
+    }
+
+    pub(crate) fn is_installed(&self) -> bool {
+        self.installed_version.is_some()
+    }
+
+    /// The install reason of the package or of its installed counterpart.
+    pub(crate) fn install_reason(&self) -> Option<Reason> {
+        self.reason
+            .or_else(|| self.local.as_ref().and_then(|local| local.reason))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DepMod {
+    Any,
+    Eq,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+impl From<alpm::DepMod> for DepMod {
+    fn from(depmod: alpm::DepMod) -> Self {
+        match depmod {
+            alpm::DepMod::Any => DepMod::Any,
+            alpm::DepMod::Eq => DepMod::Eq,
+            alpm::DepMod::Ge => DepMod::Ge,
+            alpm::DepMod::Le => DepMod::Le,
+            alpm::DepMod::Gt => DepMod::Gt,
+            alpm::DepMod::Lt => DepMod::Lt,
+        }
+    }
+}
+
+impl fmt::Display for DepMod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self {
+            DepMod::Any => "",
+            DepMod::Eq => "=",
+            DepMod::Ge => ">=",
+            DepMod::Le => "<=",
+            DepMod::Gt => ">",
+            DepMod::Lt => "<",
+        };
+        f.write_str(operator)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Dep {
+    pub(crate) name: String,
+    pub(crate) depmod: DepMod,
+    pub(crate) version: Option<String>,
+    pub(crate) description: Option<String>,
+}
+
+impl Dep {
+    fn new(dep: &alpm::Dep) -> Self {
+        Self {
+            name: dep.name().to_owned(),
+            depmod: dep.depmod().into(),
+            version: dep.version().map(|version| version.to_string()),
+            description: dep.desc().map(str::to_owned),
+        }
+    }
+
+    /// Parses a dependency string such as `python>=3.12: for the bindings`.
+    pub(crate) fn parse(dep: &str) -> Self {
+        Self::new(&alpm::Depend::new(dep))
+    }
+
+    /// Whether a package satisfies the dependency by its name or by what it provides.
+    pub(crate) fn is_satisfied_by(&self, package: &Package) -> bool {
+        (package.name == self.name && self.accepts(Some(&package.version)))
+            || package
+                .provides
+                .iter()
+                .filter(|provide| provide.name == self.name)
+                .any(|provide| self.accepts(provide.version.as_deref()))
+    }
+
+    /// Whether a version meets the constraint, a provision without a version only meets
+    /// a dependency without one.
+    fn accepts(&self, version: Option<&str>) -> bool {
+        let Some(wanted) = self.version.as_deref() else {
+            return true;
+        };
+        let Some(mut version) = version else {
+            return false;
+        };
+        // `foo>=2` accepts any pkgrel of version 2
+        if !wanted.contains('-') {
+            version = version
+                .rsplit_once('-')
+                .map_or(version, |(version, _)| version);
+        }
+
+        let ordering = alpm::vercmp(version, wanted);
+        match self.depmod {
+            DepMod::Any => true,
+            DepMod::Eq => ordering == Ordering::Equal,
+            DepMod::Ge => ordering != Ordering::Less,
+            DepMod::Le => ordering != Ordering::Greater,
+            DepMod::Gt => ordering == Ordering::Greater,
+            DepMod::Lt => ordering == Ordering::Less,
+        }
+    }
+
+    /// The dependency without its description, e.g. `glibc>=2.39`.
+    pub(crate) fn to_depstring(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{}{}{}", self.name, self.depmod, version),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Dep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_depstring())?;
+        if let Some(description) = &self.description {
+            write!(f, ": {}", description)?;
+        }
+        Ok(())
+    }
+}
+
+/// An optional dependency and the packages that satisfy it.
+#[derive(Clone)]
+pub(crate) struct Optdep {
+    pub(crate) dep: Dep,
+    /// The installed package satisfying it.
+    pub(crate) installed: Option<String>,
+    /// The sync package that would be installed for it.
+    pub(crate) available: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Reason {
+    Explicit,
+    Depend,
+}
+
+impl From<PackageReason> for Reason {
+    fn from(reason: PackageReason) -> Self {
+        match reason {
+            PackageReason::Explicit => Reason::Explicit,
+            PackageReason::Depend => Reason::Depend,
+        }
+    }
+}
+
+impl From<Reason> for PackageReason {
+    fn from(reason: Reason) -> Self {
+        match reason {
+            Reason::Explicit => PackageReason::Explicit,
+            Reason::Depend => PackageReason::Depend,
+        }
+    }
+}
+
+impl Reason {
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            Reason::Explicit => Reason::Depend,
+            Reason::Depend => Reason::Explicit,
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Explicit => f.write_str("explicit"),
+            Reason::Depend => f.write_str("dependency"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Signature {
+    Valid,
+    Invalid,
+    /// Signed, but the signature was not or could not be checked against the keyring.
+    Unverified,
+    Missing,
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signature::Valid => f.write_str("valid"),
+            Signature::Invalid => f.write_str("invalid"),
+            Signature::Unverified => f.write_str("unverified"),
+            Signature::Missing => f.write_str("missing"),
+        }
+    }
+}
+
+/// The signature of a package as shown in its details.
+#[derive(Clone)]
+pub(crate) struct PackageSignature {
+    /// The version the status and the keys are of.
+    pub(crate) version: String,
+    /// Valid if pacman checked it when installing the package.
+    pub(crate) status: Signature,
+    pub(crate) key_ids: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Validation {
+    None,
+    Md5sum,
+    Sha256sum,
+    Signature,
+}
+
+impl Validation {
+    fn from_flags(flags: PackageValidation) -> Vec<Self> {
+        [
+            (PackageValidation::NONE, Validation::None),
+            (PackageValidation::MD5SUM, Validation::Md5sum),
+            (PackageValidation::SHA256SUM, Validation::Sha256sum),
+            (PackageValidation::SIGNATURE, Validation::Signature),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, validation)| validation)
+        .collect()
+    }
+}
+
+impl fmt::Display for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Validation::None => f.write_str("none"),
+            Validation::Md5sum => f.write_str("md5sum"),
+            Validation::Sha256sum => f.write_str("sha256sum"),
+            Validation::Signature => f.write_str("signature"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser as _;
+
+    use super::*;
+    use crate::cli::{Cli, Command};
+
+    #[test]
+    fn transaction_survives_the_sudo_command_line() {
+        let transaction = Transaction {
+            install: vec![String::from("vim"), String::from("lib32-glibc")],
+            files: vec![PathBuf::from(
+                "/var/cache/pacman/pkg/foo bar-1:2.0-1-x86_64.pkg.tar.zst",
+            )],
+            remove: vec![String::from("nano")],
+            explicit: Vec::new(),
+            depend: vec![String::from("lib32-glibc")],
+        };
+        let args = [OsString::from("ptu")]
+            .into_iter()
+            .chain(transaction.args());
+
+        let Some(Command::Commit {
+            transaction: parsed,
+            ..
+        }) = Cli::try_parse_from(args).unwrap().command
+        else {
+            panic!("not parsed as a commit");
+        };
+        assert_eq!(parsed.install, transaction.install);
+        assert_eq!(parsed.files, transaction.files);
+        assert_eq!(parsed.remove, transaction.remove);
+        assert!(parsed.explicit.is_empty());
+        assert_eq!(parsed.depend, transaction.depend);
+    }
+
+    #[test]
+    fn config_paths_survive_the_sudo_command_line() {
+        let config = Config {
+            log_file: PathBuf::from("/tmp/ptu/pacman.log"),
+            mirrorlist: PathBuf::from("/tmp/ptu/mirror list"),
+            gpg_dir: PathBuf::from("/tmp/ptu/gnupg"),
+            cache_dirs: vec![
+                PathBuf::from("/tmp/ptu/pkg"),
+                PathBuf::from("/var/cache/pacman/pkg"),
+            ],
+            ..Default::default()
+        };
+        let args = [OsString::from("ptu")]
+            .into_iter()
+            .chain(Transaction::default().args())
+            .chain(ConfigPaths::new(&config).args());
+
+        let Some(Command::Commit { paths, .. }) = Cli::try_parse_from(args).unwrap().command else {
+            panic!("not parsed as a commit");
+        };
+        let parsed = paths.apply(&Config::default());
+        assert_eq!(parsed.log_file, config.log_file);
+        assert_eq!(parsed.mirrorlist, config.mirrorlist);
+        assert_eq!(parsed.gpg_dir, config.gpg_dir);
+        assert_eq!(parsed.cache_dirs, config.cache_dirs);
+    }
 }
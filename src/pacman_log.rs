@@ -0,0 +1,407 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone as _};
+use color_eyre::eyre;
+
+pub(crate) const LOG_FILE: &str = "/var/log/pacman.log";
+
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Installed {
+        package: String,
+        version: String,
+    },
+    Upgraded {
+        package: String,
+        from: String,
+        to: String,
+    },
+    Downgraded {
+        package: String,
+        from: String,
+        to: String,
+    },
+    Reinstalled {
+        package: String,
+        version: String,
+    },
+    Removed {
+        package: String,
+        version: String,
+    },
+    TransactionStarted,
+    TransactionCompleted,
+    TransactionFailed,
+    /// A command line pacman was run with.
+    Command(String),
+    Hook(String),
+    Warning(String),
+    Error(String),
+    Other(String),
+}
+
+impl Operation {
+    fn parse(source: &str, message: &str) -> Self {
+        if source == "PACMAN" {
+            if let Some(command) = message
+                .strip_prefix("Running '")
+                .and_then(|command| command.strip_suffix('\''))
+            {
+                return Operation::Command(command.to_owned());
+            }
+        }
+
+        if let Some(warning) = message.strip_prefix("warning: ") {
+            return Operation::Warning(warning.to_owned());
+        }
+        if let Some(error) = message.strip_prefix("error: ") {
+            return Operation::Error(error.to_owned());
+        }
+        if let Some(hook) = message
+            .strip_prefix("running '")
+            .and_then(|hook| hook.strip_suffix("'..."))
+        {
+            return Operation::Hook(hook.to_owned());
+        }
+
+        match message {
+            "transaction started" => return Operation::TransactionStarted,
+            "transaction completed" => return Operation::TransactionCompleted,
+            "transaction failed" | "transaction interrupted" => {
+                return Operation::TransactionFailed
+            }
+            _ => {}
+        }
+
+        // e.g. `upgraded linux (6.9.1.arch1-1 -> 6.9.2.arch1-1)`
+        let parsed = message.split_once(' ').and_then(|(verb, rest)| {
+            let (package, versions) = rest.split_once(" (")?;
+            let versions = versions.strip_suffix(')')?;
+            let package = package.to_owned();
+            let single = || versions.to_owned();
+            let pair = || {
+                versions
+                    .split_once(" -> ")
+                    .map(|(from, to)| (from.to_owned(), to.to_owned()))
+            };
+            Some(match verb {
+                "installed" => Operation::Installed {
+                    package,
+                    version: single(),
+                },
+                "reinstalled" => Operation::Reinstalled {
+                    package,
+                    version: single(),
+                },
+                "removed" => Operation::Removed {
+                    package,
+                    version: single(),
+                },
+                "upgraded" => {
+                    let (from, to) = pair()?;
+                    Operation::Upgraded { package, from, to }
+                }
+                "downgraded" => {
+                    let (from, to) = pair()?;
+                    Operation::Downgraded { package, from, to }
+                }
+                _ => return None,
+            })
+        });
+
+        parsed.unwrap_or_else(|| Operation::Other(message.to_owned()))
+    }
+
+    /// The package the operation changed.
+    pub(crate) fn package(&self) -> Option<&str> {
+        match self {
+            Operation::Installed { package, .. }
+            | Operation::Upgraded { package, .. }
+            | Operation::Downgraded { package, .. }
+            | Operation::Reinstalled { package, .. }
+            | Operation::Removed { package, .. } => Some(package),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> Kind {
+        match self {
+            Operation::Installed { .. } => Kind::Installed,
+            Operation::Upgraded { .. } => Kind::Upgraded,
+            Operation::Downgraded { .. } => Kind::Downgraded,
+            Operation::Reinstalled { .. } => Kind::Reinstalled,
+            Operation::Removed { .. } => Kind::Removed,
+            Operation::TransactionStarted
+            | Operation::TransactionCompleted
+            | Operation::TransactionFailed
+            | Operation::Command(_) => Kind::Transaction,
+            Operation::Hook(_) => Kind::Hook,
+            Operation::Warning(_) | Operation::Error(_) => Kind::Warning,
+            Operation::Other(_) => Kind::Other,
+        }
+    }
+
+    /// The versions or the message of the operation.
+    pub(crate) fn details(&self) -> String {
+        match self {
+            Operation::Installed { version, .. }
+            | Operation::Reinstalled { version, .. }
+            | Operation::Removed { version, .. } => version.clone(),
+            Operation::Upgraded { from, to, .. } | Operation::Downgraded { from, to, .. } => {
+                format!("{from} -> {to}")
+            }
+            Operation::TransactionStarted => String::from("started"),
+            Operation::TransactionCompleted => String::from("completed"),
+            Operation::TransactionFailed => String::from("failed"),
+            Operation::Command(message)
+            | Operation::Hook(message)
+            | Operation::Warning(message)
+            | Operation::Error(message)
+            | Operation::Other(message) => message.clone(),
+        }
+    }
+}
+
+/// What log entries can be filtered by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Installed,
+    Upgraded,
+    Downgraded,
+    Reinstalled,
+    Removed,
+    Transaction,
+    Hook,
+    Warning,
+    Other,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Installed => "installed",
+            Kind::Upgraded => "upgraded",
+            Kind::Downgraded => "downgraded",
+            Kind::Reinstalled => "reinstalled",
+            Kind::Removed => "removed",
+            Kind::Transaction => "transaction",
+            Kind::Hook => "hook",
+            Kind::Warning => "warning",
+            Kind::Other => "other",
+        })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Entry {
+    /// Unix timestamp.
+    pub(crate) time: i64,
+    pub(crate) operation: Operation,
+    /// Index of the transaction the entry was logged in.
+    pub(crate) transaction: Option<usize>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Transaction {
+    /// The pacman command line that started the transaction, if it was logged.
+    pub(crate) command: Option<String>,
+    /// Unix timestamp.
+    pub(crate) started: i64,
+    /// Whether pacman logged the transaction as completed.
+    pub(crate) completed: bool,
+    /// Indices of the entries of the transaction.
+    pub(crate) entries: Vec<usize>,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Log {
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) transactions: Vec<Transaction>,
+}
+
+/// Parses both `2024-05-01T10:22:33+0200` and the older `2019-01-01 10:00` in local time.
+fn parse_time(time: &str) -> Option<i64> {
+    if let Ok(time) = DateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%z") {
+        return Some(time.timestamp());
+    }
+    let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").ok()?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.timestamp())
+}
+
+impl Log {
+    pub(crate) fn read(path: &Path) -> eyre::Result<Self> {
+        // pacman writes whatever scriptlets print, which is not always UTF-8
+        let content = fs::read(path)?;
+        Ok(Self::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    pub(crate) fn parse(content: &str) -> Self {
+        let mut log = Log::default();
+        let mut command = None;
+        let mut current = None;
+
+        for line in content.lines() {
+            // `[time] [source] message`, scriptlet output continues without a prefix
+            let Some((time, rest)) = line
+                .strip_prefix('[')
+                .and_then(|line| line.split_once("] "))
+            else {
+                continue;
+            };
+            let Some(time) = parse_time(time) else {
+                continue;
+            };
+            let (source, message) = match rest
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once("] "))
+            {
+                Some((source, message)) => (source, message),
+                None => ("", rest),
+            };
+
+            let operation = Operation::parse(source, message);
+            let index = log.entries.len();
+
+            // hooks and scriptlets run after the transaction completed still belong to it,
+            // pacman itself only logs again for the next operation
+            if source == "PACMAN" {
+                current = None;
+            }
+            match operation {
+                Operation::Command(ref line) => command = Some(line.clone()),
+                Operation::TransactionStarted => {
+                    current = Some(log.transactions.len());
+                    log.transactions.push(Transaction {
+                        command: command.take(),
+                        started: time,
+                        completed: false,
+                        entries: Vec::new(),
+                    });
+                }
+                Operation::TransactionCompleted => {
+                    if let Some(transaction) = current {
+                        log.transactions[transaction].completed = true;
+                    }
+                }
+                _ => {}
+            }
+
+            if let Some(transaction) = current {
+                log.transactions[transaction].entries.push(index);
+            }
+            log.entries.push(Entry {
+                time,
+                operation,
+                transaction: current,
+            });
+        }
+
+        log
+    }
+
+    /// When the last full system upgrade started.
+    pub(crate) fn last_upgrade(&self) -> Option<i64> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| {
+                matches!(&entry.operation, Operation::Other(message) if message == "starting full system upgrade")
+            })
+            .map(|entry| entry.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+[2019-01-01 10:00] [PACMAN] synchronizing package lists
+[2024-05-01T10:00:00+0000] [PACMAN] Running 'pacman -Syu'
+[2024-05-01T10:00:01+0000] [PACMAN] starting full system upgrade
+[2024-05-01T10:00:05+0000] [ALPM] transaction started
+[2024-05-01T10:00:06+0000] [ALPM] upgraded linux (6.9.1.arch1-1 -> 6.9.2.arch1-1)
+[2024-05-01T10:00:07+0000] [ALPM] installed foo-bar (1:2.0-1)
+[2024-05-01T10:00:08+0000] [ALPM-SCRIPTLET] updating the cache
+scriptlet output without a prefix
+[2024-05-01T10:00:09+0000] [ALPM] warning: /etc/foo.conf installed as /etc/foo.conf.pacnew
+[2024-05-01T10:00:10+0000] [ALPM] transaction completed
+[2024-05-01T10:00:11+0000] [ALPM] running '60-mkinitcpio-remove.hook'...
+[2024-05-02T09:00:00+0000] [PACMAN] Running 'pacman -R foo-bar'
+[2024-05-02T09:00:01+0000] [ALPM] transaction started
+[2024-05-02T09:00:02+0000] [ALPM] removed foo-bar (1:2.0-1)
+[2024-05-02T09:00:03+0000] [ALPM] transaction interrupted
+[2024-05-03T08:00:00+0000] [PACMAN] Running 'pacman -Syu --ignore linux'
+[2024-05-03T08:00:01+0000] [PACMAN] starting full system upgrade
+[2024-05-03T08:00:02+0000] [PACMAN] there is nothing to do
+";
+
+    #[test]
+    fn parses_operations() {
+        let log = Log::parse(LOG);
+        let operations = log
+            .entries
+            .iter()
+            .map(|entry| (entry.operation.kind(), entry.operation.details()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(operations.len(), 17);
+        assert!(operations[0] == (Kind::Other, String::from("synchronizing package lists")));
+        assert!(
+            operations[4]
+                == (
+                    Kind::Upgraded,
+                    String::from("6.9.1.arch1-1 -> 6.9.2.arch1-1")
+                )
+        );
+        assert!(operations[5] == (Kind::Installed, String::from("1:2.0-1")));
+        assert_eq!(log.entries[5].operation.package(), Some("foo-bar"));
+        assert!(operations[7].0 == Kind::Warning);
+        assert!(operations[9] == (Kind::Hook, String::from("60-mkinitcpio-remove.hook")));
+        assert!(operations[12] == (Kind::Removed, String::from("1:2.0-1")));
+
+        assert_eq!(
+            Some(log.entries[0].time),
+            Local
+                .with_ymd_and_hms(2019, 1, 1, 10, 0, 0)
+                .earliest()
+                .map(|time| time.timestamp())
+        );
+        assert_eq!(log.entries[1].time, 1_714_557_600);
+    }
+
+    #[test]
+    fn groups_entries_into_transactions() {
+        let log = Log::parse(LOG);
+        assert_eq!(log.transactions.len(), 2);
+
+        let upgrade = &log.transactions[0];
+        assert_eq!(upgrade.command.as_deref(), Some("pacman -Syu"));
+        assert!(upgrade.completed);
+        // the hook after the completion still belongs to the transaction
+        assert_eq!(upgrade.entries, [3, 4, 5, 6, 7, 8, 9]);
+
+        let removal = &log.transactions[1];
+        assert_eq!(removal.command.as_deref(), Some("pacman -R foo-bar"));
+        assert!(!removal.completed);
+        assert_eq!(removal.entries, [11, 12, 13]);
+
+        assert_eq!(log.entries[10].transaction, None);
+        assert_eq!(log.entries[16].transaction, None);
+    }
+
+    #[test]
+    fn finds_the_last_upgrade() {
+        assert_eq!(Log::parse(LOG).last_upgrade(), Some(1_714_723_201));
+        assert_eq!(
+            Log::parse(&LOG[..LOG.find("[2024-05-03").unwrap()]).last_upgrade(),
+            Some(1_714_557_601)
+        );
+        assert_eq!(Log::parse("").last_upgrade(), None);
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use color_eyre::eyre;
+
+use crate::pacman::Pacman;
+
+/// Where pacman leaves most configuration files, scanned besides the backup arrays.
+pub(crate) const CONFIG_DIR: &str = "/etc";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// A new version pacman did not install over a modified file.
+    Pacnew,
+    /// A modified file saved when its package was removed or stopped shipping it.
+    Pacsave,
+}
+
+impl Kind {
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Kind::Pacnew => "pacnew",
+            Kind::Pacsave => "pacsave",
+        }
+    }
+}
+
+/// What to do with a `.pacnew` or `.pacsave` file.
+#[derive(Clone, Copy)]
+pub(crate) enum Resolution {
+    /// Use it instead of the live file.
+    Replace,
+    Merge,
+    Remove,
+}
+
+/// A `.pacnew` or `.pacsave` file next to the file it belongs to.
+#[derive(Clone)]
+pub(crate) struct ConfigFile {
+    pub(crate) path: PathBuf,
+    /// The file in use, which may not exist for `.pacsave` files.
+    pub(crate) live: PathBuf,
+    pub(crate) kind: Kind,
+    pub(crate) owner: Option<String>,
+    /// Unified diff from the live file, or why it could not be made.
+    pub(crate) diff: Result<String, String>,
+}
+
+impl ConfigFile {
+    fn new(path: PathBuf, owners: &HashMap<PathBuf, String>, pacman: &Pacman) -> Option<Self> {
+        let (live, kind) = live_file(&path)?;
+        let owner = owners.get(&live).cloned().or_else(|| {
+            let live = live.to_str()?;
+            pacman
+                .owners(live)
+                .into_iter()
+                .next()
+                .map(|package| package.name)
+        });
+        let diff = diff(&live, &path).map_err(|error| error.to_string());
+
+        Some(Self {
+            path,
+            live,
+            kind,
+            owner,
+            diff,
+        })
+    }
+}
+
+/// The file a `.pacnew` or `.pacsave` file belongs to, `None` for other files.
+fn live_file(path: &Path) -> Option<(PathBuf, Kind)> {
+    let file_name = path.file_name()?.to_str()?;
+    // pacman numbers `.pacsave` files when one is already there
+    let (live_name, kind) = if let Some(live) = file_name.strip_suffix(".pacnew") {
+        (live, Kind::Pacnew)
+    } else {
+        let (live, suffix) = file_name.split_once(".pacsave")?;
+        let numbered = suffix.strip_prefix('.').is_some_and(|number| {
+            !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit())
+        });
+        if !suffix.is_empty() && !numbered {
+            return None;
+        }
+        (live, Kind::Pacsave)
+    };
+    Some((path.with_file_name(live_name), kind))
+}
+
+/// Finds the `.pacnew` and `.pacsave` files of the configuration files installed packages
+/// back up and under [`CONFIG_DIR`].
+pub(crate) fn find(pacman: &Pacman) -> Vec<ConfigFile> {
+    let owners = pacman.backup_files();
+
+    let mut paths = Vec::new();
+    for live in owners.keys() {
+        let Some(file_name) = live.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        for suffix in [".pacnew", ".pacsave"] {
+            let path = live.with_file_name(format!("{file_name}{suffix}"));
+            if path.exists() {
+                paths.push(path);
+            }
+        }
+    }
+    walk(Path::new(CONFIG_DIR), &mut paths);
+
+    // files found both ways are listed once
+    let paths = paths.into_iter().collect::<BTreeSet<_>>();
+    paths
+        .into_iter()
+        .filter_map(|path| ConfigFile::new(path, &owners, pacman))
+        .collect()
+}
+
+/// Collects the `.pacnew` and `.pacsave` files under `dir`, skipping unreadable directories.
+fn walk(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            walk(&path, paths);
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".pacnew") || name.contains(".pacsave"))
+        {
+            paths.push(path);
+        }
+    }
+}
+
+/// `diff -u` from the live file to the new one, treating a missing live file as empty.
+fn diff(live: &Path, path: &Path) -> eyre::Result<String> {
+    let output = Command::new("diff")
+        .arg("--unified")
+        .arg("--new-file")
+        .arg(live)
+        .arg(path)
+        .output()?;
+
+    // diff exits with 1 when the files differ
+    if output.status.code() == Some(2) {
+        let error = String::from_utf8_lossy(&output.stderr);
+        eyre::bail!("{}", error.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Moves the file over the live one.
+pub(crate) fn replace(file: &ConfigFile) -> eyre::Result<ExitStatus> {
+    let status = Command::new("sudo")
+        .arg("mv")
+        .arg("-f")
+        .arg("--")
+        .arg(&file.path)
+        .arg(&file.live)
+        .status()?;
+    Ok(status)
+}
+
+pub(crate) fn remove(file: &ConfigFile) -> eyre::Result<ExitStatus> {
+    let status = Command::new("sudo")
+        .arg("rm")
+        .arg("-f")
+        .arg("--")
+        .arg(&file.path)
+        .status()?;
+    Ok(status)
+}
+
+/// Opens both files in `$DIFFPROG`, or in `$EDITOR` through `sudoedit` without one.
+pub(crate) fn merge(file: &ConfigFile) -> eyre::Result<ExitStatus> {
+    let diffprog = env::var("DIFFPROG").ok();
+    let mut command = match diffprog.as_deref().map(str::split_whitespace) {
+        Some(mut diffprog) => {
+            let program = diffprog
+                .next()
+                .ok_or_else(|| eyre::eyre!("DIFFPROG is empty"))?;
+            let mut command = Command::new("sudo");
+            command.arg(program).args(diffprog);
+            command
+        }
+        None => Command::new("sudoedit"),
+    };
+
+    let status = command.arg(&file.live).arg(&file.path).status()?;
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live(path: &str) -> Option<(PathBuf, Kind)> {
+        live_file(Path::new(path))
+    }
+
+    #[test]
+    fn finds_the_live_file_by_suffix() {
+        assert_eq!(
+            live("/etc/pacman.conf.pacnew"),
+            Some((PathBuf::from("/etc/pacman.conf"), Kind::Pacnew))
+        );
+        assert_eq!(
+            live("/etc/ssh/sshd_config.pacsave"),
+            Some((PathBuf::from("/etc/ssh/sshd_config"), Kind::Pacsave))
+        );
+        assert_eq!(
+            live("/etc/ssh/sshd_config.pacsave.1"),
+            Some((PathBuf::from("/etc/ssh/sshd_config"), Kind::Pacsave))
+        );
+        assert_eq!(
+            live("/etc/mkinitcpio.d/linux.preset.pacsave.12"),
+            Some((
+                PathBuf::from("/etc/mkinitcpio.d/linux.preset"),
+                Kind::Pacsave
+            ))
+        );
+    }
+
+    #[test]
+    fn ignores_names_that_only_look_similar() {
+        for path in [
+            "/etc/pacman.conf",
+            "/etc/pacman.conf.pacnew.bak",
+            "/etc/pacman.conf.pacnew~",
+            "/etc/fstab.pacsave.",
+            "/etc/fstab.pacsave.old",
+            "/etc/fstab.pacsave.1a",
+            "/etc/fstab.pacsaved",
+            "/etc/pacnew",
+        ] {
+            assert_eq!(live(path), None, "{path}");
+        }
+    }
+}
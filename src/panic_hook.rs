@@ -1,6 +1,6 @@
 use std::panic;
 
-use crate::tui::TUI;
+use crate::tui::Tui;
 
 use color_eyre::eyre;
 
@@ -10,13 +10,13 @@ pub(crate) fn init() -> eyre::Result<()> {
 
     let panic_hook = panic_hook.into_panic_hook();
     panic::set_hook(Box::new(move |panic_info| {
-        let _ = TUI::exit();
+        let _ = Tui::exit();
         panic_hook(panic_info);
     }));
 
     let eyre_hook = eyre_hook.into_eyre_hook();
     eyre::set_hook(Box::new(move |error| {
-        let _ = TUI::exit();
+        let _ = Tui::exit();
         eyre_hook(error)
     }))?;
 
@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre;
+use ratatui::style::{Color, Stylize as _};
+use ratatui::text::{Line, Span};
+
+use crate::config;
+
+/// Sync databases refreshed longer ago than this are pointed out in the header.
+pub(crate) const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+const SYNC_FILE: &str = "synced";
+
+/// When the sync databases were last refreshed successfully.
+#[derive(Default)]
+pub(crate) struct LastSync {
+    time: Option<SystemTime>,
+    /// A Unix timestamp, `$XDG_STATE_HOME/ptu/synced` unless there is no home.
+    path: Option<PathBuf>,
+}
+
+impl LastSync {
+    pub(crate) fn load() -> Self {
+        let path = config::state_dir().map(|dir| dir.join(SYNC_FILE));
+        let time = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| content.trim().parse().ok())
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+
+        Self { time, path }
+    }
+
+    pub(crate) fn age(&self) -> Option<Duration> {
+        SystemTime::now().duration_since(self.time?).ok()
+    }
+
+    /// Whether the databases were never refreshed or not for a day.
+    pub(crate) fn is_stale(&self) -> bool {
+        self.age().is_none_or(|age| age > STALE_AFTER)
+    }
+
+    /// Records a refresh that just succeeded and saves it.
+    pub(crate) fn record(&mut self) -> eyre::Result<()> {
+        let now = SystemTime::now();
+        self.time = Some(now);
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let secs = now.duration_since(UNIX_EPOCH)?.as_secs();
+        fs::write(path, format!("{secs}\n"))?;
+        Ok(())
+    }
+}
+
+/// How old the downloaded copy of a sync database is on the mirror it came from.
+#[derive(Clone)]
+pub(crate) struct DbStatus {
+    pub(crate) name: String,
+    /// When the mirror last changed the database, pacman keeps its Last-Modified time as the
+    /// modification time of the file. `None` if it was never synced.
+    pub(crate) modified: Option<SystemTime>,
+}
+
+impl DbStatus {
+    /// How long ago the mirror changed the downloaded database.
+    pub(crate) fn age(&self) -> Option<Duration> {
+        SystemTime::now().duration_since(self.modified?).ok()
+    }
+}
+
+/// A duration in its largest whole unit, e.g. `3h`.
+pub(crate) fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => String::from("just now"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// The header indicator: the download in progress, or when the databases were last refreshed
+/// followed by when each was last changed on the mirror it was downloaded from.
+pub(crate) fn indicator(
+    last_sync: &LastSync,
+    statuses: &[DbStatus],
+    progress: Option<&str>,
+) -> Line<'static> {
+    if let Some(progress) = progress {
+        return Line::from(format!(" {progress} ")).fg(Color::Yellow);
+    }
+
+    let synced = last_sync.age().map_or_else(
+        || String::from(" never synced "),
+        |age| format!(" updated {} ", format_age(age)),
+    );
+    let mut spans = vec![if last_sync.is_stale() {
+        Span::raw(synced).fg(Color::Red)
+    } else {
+        Span::raw(synced).fg(Color::DarkGray)
+    }];
+    for status in statuses {
+        if let Some(age) = status.age() {
+            spans.push(
+                Span::raw(format!(" {} mirror {} ", status.name, format_age(age)))
+                    .fg(Color::DarkGray),
+            );
+        }
+    }
+    if last_sync.is_stale() {
+        spans.push(Span::raw(" [^R] refresh ").fg(Color::Red));
+    }
+    Line::from(spans)
+}
+
+/// Refreshes the sync databases with `pacman -Sy` through sudo, like the alpm refresh does
+/// with the database lock. Nothing is upgraded, so installing before an upgrade would be a
+/// partial upgrade.
+pub(crate) fn refresh_with_pacman() -> eyre::Result<ExitStatus> {
+    let status = Command::new("sudo").arg("pacman").arg("-Sy").status()?;
+    Ok(status)
+}
@@ -4,31 +4,39 @@ use std::{
 };
 
 use color_eyre::eyre;
-use ratatui::crossterm::{self, terminal};
+use ratatui::crossterm::{self, event, terminal};
 
 type Terminal = ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>;
 
-pub(crate) struct TUI {
+pub(crate) struct Tui {
     terminal: Terminal,
+    mouse: bool,
 }
 
-impl TUI {
-    pub(crate) fn new() -> eyre::Result<Self> {
+impl Tui {
+    pub(crate) fn new(mouse: bool) -> eyre::Result<Self> {
         let writer = io::stdout();
         let backend = ratatui::backend::CrosstermBackend::new(writer);
         let terminal = ratatui::Terminal::new(backend)?;
-        Ok(Self { terminal })
+        Ok(Self { terminal, mouse })
     }
 
-    pub(crate) fn enter() -> eyre::Result<()> {
+    pub(crate) fn enter(&self) -> eyre::Result<()> {
         terminal::enable_raw_mode()?;
         crossterm::execute!(io::stdout(), terminal::EnterAlternateScreen)?;
+        if self.mouse {
+            crossterm::execute!(io::stdout(), event::EnableMouseCapture)?;
+        }
         Ok(())
     }
 
     pub(crate) fn exit() -> eyre::Result<()> {
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stdout(), terminal::LeaveAlternateScreen)?;
+        crossterm::execute!(
+            io::stdout(),
+            event::DisableMouseCapture,
+            terminal::LeaveAlternateScreen
+        )?;
         Ok(())
     }
 
@@ -38,13 +46,13 @@ impl TUI {
     {
         Self::exit()?;
         f()?;
-        Self::enter()?;
+        self.enter()?;
         self.clear()?;
         Ok(())
     }
 }
 
-impl Deref for TUI {
+impl Deref for Tui {
     type Target = Terminal;
 
     fn deref(&self) -> &Self::Target {
@@ -52,7 +60,7 @@ impl Deref for TUI {
     }
 }
 
-impl DerefMut for TUI {
+impl DerefMut for Tui {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.terminal
     }